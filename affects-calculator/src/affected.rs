@@ -0,0 +1,47 @@
+use std::borrow::Cow;
+
+use affects_common::{ItemKind, ModelSource};
+
+/// a single thing affected by a path
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct AffectedItem<'a> {
+    pub kind: ItemKind,
+    pub name: Cow<'a, str>,
+    /// the item's row in the Item sheet, for gear and weapons
+    pub item_id: Option<u32>,
+    pub icon_id: Option<u32>,
+    /// the model and variant the path was matched against, if it belongs to
+    /// one
+    pub source: Option<ModelSource>,
+}
+
+impl<'a> AffectedItem<'a> {
+    pub fn new(kind: ItemKind, name: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            kind,
+            name: name.into(),
+            item_id: None,
+            icon_id: None,
+            source: None,
+        }
+    }
+
+    /// the same item under a different name, such as when the name is
+    /// qualified by the part of it the path affects
+    pub fn renamed(self, name: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            name: name.into(),
+            ..self
+        }
+    }
+
+    pub fn into_owned(self) -> AffectedItem<'static> {
+        AffectedItem {
+            kind: self.kind,
+            name: Cow::Owned(self.name.into_owned()),
+            item_id: self.item_id,
+            icon_id: self.icon_id,
+            source: self.source,
+        }
+    }
+}
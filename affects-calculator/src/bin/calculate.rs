@@ -1,11 +1,38 @@
-use std::fs::File;
-
 use affects_calculator::CalculatesAffects;
-use affects_common::Affects;
+use affects_common::{AffectsData, AffectsView};
 
 fn main() {
-    let affects: Affects = serde_json::from_reader(File::open("affects.json").unwrap()).unwrap();
-    for arg in std::env::args().skip(1) {
-        println!("{arg}: {:#?}", affects.calculate_affected(&arg));
+    let paths = std::env::args().skip(1).collect::<Vec<_>>();
+
+    // prefer the binary database, whose names and lookup tables are searched
+    // in place rather than decoded
+    let data = std::fs::read("affects.bin")
+        .or_else(|_| std::fs::read("affects.json"))
+        .unwrap();
+    if AffectsView::is_binary(&data) {
+        calculate(&AffectsView::new(&data).unwrap(), &paths);
+    } else {
+        calculate(&affects_calculator::from_json_slice(&data).unwrap(), &paths);
+    }
+}
+
+fn calculate(affects: &impl AffectsData, paths: &[String]) {
+    match &affects.metadata().game_version {
+        Some(version) => println!("database is for game version {version}"),
+        None => eprintln!("warning: database does not say which game version it is for"),
+    }
+
+    for arg in paths {
+        println!("{arg}: {:#?}", affects.calculate_affected(arg));
+    }
+
+    let summary = affects.calculate_affected_many(paths);
+    println!("summary:");
+    for (kind, count) in summary.counts() {
+        println!("  {kind}: {count}");
+    }
+
+    for path in &summary.unmatched {
+        println!("  unmatched: {path}");
     }
 }
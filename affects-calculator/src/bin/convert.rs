@@ -0,0 +1,36 @@
+//! converts a database between json and the binary format. the input format
+//! is detected, and the output is json if its file name ends in `.json`
+//!
+//! usage: convert <input> <output> [--pretty]
+
+use std::{fs::File, io::BufWriter};
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let pretty = args.iter().any(|arg| arg == "--pretty");
+    let [input, output] = match args
+        .iter()
+        .filter(|arg| !arg.starts_with("--"))
+        .collect::<Vec<_>>()[..]
+    {
+        [input, output] => [input, output],
+        _ => {
+            eprintln!("usage: convert <input> <output> [--pretty]");
+            std::process::exit(1);
+        }
+    };
+
+    let data = std::fs::read(input).unwrap();
+    let affects = affects_calculator::from_slice(&data).unwrap();
+
+    if output.ends_with(".json") {
+        let mut file = BufWriter::new(File::create(output).unwrap());
+        if pretty {
+            serde_json::to_writer_pretty(&mut file, &affects).unwrap();
+        } else {
+            serde_json::to_writer(&mut file, &affects).unwrap();
+        }
+    } else {
+        std::fs::write(output, affects.to_binary().unwrap()).unwrap();
+    }
+}
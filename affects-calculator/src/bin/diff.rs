@@ -0,0 +1,29 @@
+//! compares two databases, such as ones generated before and after a patch
+//!
+//! usage: diff <old> <new> [--json]
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let json = args.iter().any(|arg| arg == "--json");
+    let [old, new] = match args
+        .iter()
+        .filter(|arg| !arg.starts_with("--"))
+        .collect::<Vec<_>>()[..]
+    {
+        [old, new] => [old, new],
+        _ => {
+            eprintln!("usage: diff <old> <new> [--json]");
+            std::process::exit(1);
+        }
+    };
+
+    let old = affects_calculator::from_slice(&std::fs::read(old).unwrap()).unwrap();
+    let new = affects_calculator::from_slice(&std::fs::read(new).unwrap()).unwrap();
+    let diff = affects_calculator::diff_affects(&old, &new);
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&diff).unwrap());
+    } else {
+        print!("{diff}");
+    }
+}
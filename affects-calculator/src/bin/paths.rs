@@ -0,0 +1,31 @@
+use affects_calculator::{FindsPaths, PathsFor};
+use affects_common::{AffectsData, AffectsView};
+
+fn main() {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+
+    let data = std::fs::read("affects.bin")
+        .or_else(|_| std::fs::read("affects.json"))
+        .unwrap();
+    if AffectsView::is_binary(&data) {
+        list_paths(&AffectsView::new(&data).unwrap(), &args);
+    } else {
+        list_paths(&affects_calculator::from_json_slice(&data).unwrap(), &args);
+    }
+}
+
+fn list_paths(affects: &impl AffectsData, args: &[String]) {
+    let index = affects.reverse_index();
+    for arg in args {
+        // numbers are item ids, anything else is a name
+        let target = match arg.parse() {
+            Ok(item_id) => PathsFor::Item(item_id),
+            Err(_) => PathsFor::Name(arg),
+        };
+
+        println!("{arg}:");
+        for path in index.paths_for(target) {
+            println!("  {path}");
+        }
+    }
+}
@@ -0,0 +1,423 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use affects_common::{
+    AffectsData, ItemKind, KeyedTable, Language, ModelQuery, ModelSource, ModelSourceKind, Names,
+};
+use path_parser::builder::{DemihumanModel, EquipmentModel, MonsterModel, WeaponModel};
+use serde::Serialize;
+
+/// what changed between two databases, usually generated before and after a
+/// patch. serialises to json for tooling, and displays as a readable report
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct AffectsDiff {
+    pub old_game_version: Option<String>,
+    pub new_game_version: Option<String>,
+    /// item kind => names of that kind that changed
+    pub names: BTreeMap<String, NameChanges>,
+    /// models and variants whose names changed, including ones that were
+    /// added or removed
+    pub models: Vec<ModelChange>,
+    /// avfx paths that are newly linked to a model
+    pub vfx_added: BTreeSet<String>,
+    /// vfx file stem => names, for effects that are newly linked to anything
+    pub effects_added: BTreeMap<String, BTreeSet<String>>,
+    /// map id => names
+    pub maps_added: BTreeMap<String, BTreeSet<String>>,
+    pub maps_removed: BTreeSet<String>,
+}
+
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct NameChanges {
+    pub added: BTreeSet<String>,
+    pub removed: BTreeSet<String>,
+    /// (old name, new name) for items that kept their id
+    pub renamed: BTreeSet<(String, String)>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelChange {
+    pub source: ModelSource,
+    /// empty when the model is new
+    pub old: BTreeSet<String>,
+    /// empty when the model was removed
+    pub new: BTreeSet<String>,
+}
+
+impl AffectsDiff {
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+            && self.models.is_empty()
+            && self.vfx_added.is_empty()
+            && self.effects_added.is_empty()
+            && self.maps_added.is_empty()
+            && self.maps_removed.is_empty()
+    }
+}
+
+/// compares two databases. names are compared in english
+pub fn diff_affects(old: &impl AffectsData, new: &impl AffectsData) -> AffectsDiff {
+    let mut names = BTreeMap::<ItemKind, NameChanges>::new();
+    let old_names = names_by_kind(old);
+    let new_names = names_by_kind(new);
+    let kinds = old_names.keys().chain(new_names.keys()).copied();
+    for kind in kinds.collect::<BTreeSet<_>>() {
+        let empty = BTreeSet::new();
+        let old_names = old_names.get(&kind).unwrap_or(&empty);
+        let new_names = new_names.get(&kind).unwrap_or(&empty);
+
+        let changes = NameChanges {
+            added: new_names.difference(old_names).cloned().collect(),
+            removed: old_names.difference(new_names).cloned().collect(),
+            renamed: BTreeSet::new(),
+        };
+        names.insert(kind, changes);
+    }
+
+    // items are the only things with ids that survive a regeneration, so
+    // they are the only things that can be told apart from a removal and an
+    // addition
+    for (item_id, old_idx) in old.item_ids() {
+        let new_idx = match new.item_name(item_id) {
+            Some(idx) => idx,
+            None => continue,
+        };
+
+        let (old_name, new_name) = match (
+            old.name(old_idx, Language::English),
+            new.name(new_idx, Language::English),
+        ) {
+            (Some(old_name), Some(new_name)) if old_name != new_name => (old_name, new_name),
+            _ => continue,
+        };
+
+        let kinds = new.name_kinds().get(&new_idx).into_iter().flatten();
+        for kind in kinds {
+            let changes = names.entry(*kind).or_default();
+            changes.added.remove(new_name);
+            changes.removed.remove(old_name);
+            changes
+                .renamed
+                .insert((old_name.to_string(), new_name.to_string()));
+        }
+    }
+
+    let names = names
+        .into_iter()
+        .filter(|(_, changes)| {
+            !changes.added.is_empty() || !changes.removed.is_empty() || !changes.renamed.is_empty()
+        })
+        .map(|(kind, changes)| (kind.to_string(), changes))
+        .collect();
+
+    let old_models = names_by_model(old);
+    let new_models = names_by_model(new);
+    let sources = old_models.keys().chain(new_models.keys()).copied();
+    let models = sources
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .filter_map(|source| {
+            let old = old_models.get(&source).cloned().unwrap_or_default();
+            let new = new_models.get(&source).cloned().unwrap_or_default();
+            (old != new).then_some(ModelChange { source, old, new })
+        })
+        .collect();
+
+    let vfx_added = vfx_paths(new)
+        .difference(&vfx_paths(old))
+        .cloned()
+        .collect();
+
+    let effects_added = keys_added(old, new, KeyedTable::Effects);
+    let maps_added = keys_added(old, new, KeyedTable::Maps);
+    let maps_removed = keys_added(new, old, KeyedTable::Maps).into_keys().collect();
+
+    AffectsDiff {
+        old_game_version: old.metadata().game_version.clone(),
+        new_game_version: new.metadata().game_version.clone(),
+        names,
+        models,
+        vfx_added,
+        effects_added,
+        maps_added,
+        maps_removed,
+    }
+}
+
+/// the keys of `table` in `new` that are not in `old`, and their names in
+/// `new`
+fn keys_added(
+    old: &impl AffectsData,
+    new: &impl AffectsData,
+    table: KeyedTable,
+) -> BTreeMap<String, BTreeSet<String>> {
+    new.keyed_range(table, "")
+        .into_iter()
+        .filter(|(key, _)| old.keyed(table, key).is_none())
+        .map(|(key, names)| (key.to_string(), resolve_names(new, names)))
+        .collect()
+}
+
+fn resolve_names(affects: &impl AffectsData, names: Names) -> BTreeSet<String> {
+    names
+        .iter()
+        .flat_map(|(_, idx)| affects.name(idx, Language::English))
+        .map(str::to_string)
+        .collect()
+}
+
+fn names_by_kind(affects: &impl AffectsData) -> BTreeMap<ItemKind, BTreeSet<String>> {
+    let mut names = BTreeMap::<ItemKind, BTreeSet<String>>::new();
+    for (&idx, kinds) in affects.name_kinds() {
+        let name = match affects.name(idx, Language::English) {
+            Some(name) => name,
+            None => continue,
+        };
+
+        for &kind in kinds {
+            names.entry(kind).or_default().insert(name.to_string());
+        }
+    }
+
+    names
+}
+
+fn names_by_model(affects: &impl AffectsData) -> BTreeMap<ModelSource, BTreeSet<String>> {
+    ModelSourceKind::ALL
+        .into_iter()
+        .flat_map(|kind| affects.models(ModelQuery::all(kind)))
+        .map(|(source, names)| (source, resolve_names(affects, names)))
+        .collect()
+}
+
+fn vfx_paths(affects: &impl AffectsData) -> BTreeSet<String> {
+    let mut paths = BTreeSet::new();
+
+    for kind in ModelSourceKind::ALL {
+        for (source, effect) in affects.model_effects(ModelQuery::all(kind)) {
+            let effect = effect as u16;
+            let path = match source {
+                ModelSource::Equipment { slot, model, .. } => {
+                    EquipmentModel::new(slot, model).avfx(effect)
+                }
+                ModelSource::Weapon { model, weapon, .. } => {
+                    Some(WeaponModel::new(model, weapon).avfx(effect))
+                }
+                ModelSource::Monster { model, base, .. } => {
+                    Some(MonsterModel::new(model, base as u16).avfx(effect))
+                }
+                ModelSource::Demihuman { model, base, .. } => {
+                    Some(DemihumanModel::new(model, base as u16).avfx(effect))
+                }
+            };
+            paths.extend(path.map(|path| path.to_string()));
+        }
+    }
+
+    paths
+}
+
+impl std::fmt::Display for AffectsDiff {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let version = |version: &Option<String>| version.clone().unwrap_or("unknown".into());
+        writeln!(
+            f,
+            "game version {} -> {}",
+            version(&self.old_game_version),
+            version(&self.new_game_version),
+        )?;
+
+        if self.is_empty() {
+            return writeln!(f, "no changes");
+        }
+
+        for (kind, changes) in &self.names {
+            writeln!(f, "\n{kind}")?;
+            for name in &changes.added {
+                writeln!(f, "  + {name}")?;
+            }
+
+            for name in &changes.removed {
+                writeln!(f, "  - {name}")?;
+            }
+
+            for (old, new) in &changes.renamed {
+                writeln!(f, "  ~ {old} -> {new}")?;
+            }
+        }
+
+        if !self.models.is_empty() {
+            writeln!(f, "\nModels")?;
+            for change in &self.models {
+                let marker = match (change.old.is_empty(), change.new.is_empty()) {
+                    (true, _) => '+',
+                    (_, true) => '-',
+                    _ => '~',
+                };
+
+                let names =
+                    |names: &BTreeSet<String>| names.iter().cloned().collect::<Vec<_>>().join(", ");
+                write!(f, "  {marker} {}:", describe_source(change.source))?;
+                match marker {
+                    '+' => writeln!(f, " {}", names(&change.new))?,
+                    '-' => writeln!(f, " {}", names(&change.old))?,
+                    _ => writeln!(f, " {} -> {}", names(&change.old), names(&change.new))?,
+                }
+            }
+        }
+
+        if !self.vfx_added.is_empty() || !self.effects_added.is_empty() {
+            writeln!(f, "\nVFX")?;
+            for path in &self.vfx_added {
+                writeln!(f, "  + {path}")?;
+            }
+
+            for (stem, names) in &self.effects_added {
+                let names = names.iter().cloned().collect::<Vec<_>>().join(", ");
+                writeln!(f, "  + {stem}: {names}")?;
+            }
+        }
+
+        if !self.maps_added.is_empty() || !self.maps_removed.is_empty() {
+            writeln!(f, "\nMaps")?;
+            for (id, names) in &self.maps_added {
+                let names = names.iter().cloned().collect::<Vec<_>>().join(", ");
+                writeln!(f, "  + {id}: {names}")?;
+            }
+
+            for id in &self.maps_removed {
+                writeln!(f, "  - {id}")?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn describe_source(source: ModelSource) -> String {
+    match source {
+        ModelSource::Equipment {
+            slot,
+            model,
+            variant,
+        } => {
+            let prefix = if slot.is_accessory() { 'a' } else { 'e' };
+            format!("{prefix}{model:<04} {} v{variant}", slot.as_str())
+        }
+        ModelSource::Weapon {
+            model,
+            weapon,
+            variant,
+        } => format!("w{model:<04} b{weapon:<04} v{variant}"),
+        ModelSource::Monster {
+            model,
+            base,
+            variant,
+        } => format!("m{model:<04} b{base:<04} v{variant}"),
+        ModelSource::Demihuman {
+            model,
+            base,
+            variant,
+        } => format!("d{model:<04} e{base:<04} v{variant}"),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use affects_common::{EquipSlot, ItemKind, ModelSource};
+    use path_parser::builder::WeaponModel;
+
+    use super::diff_affects;
+    use crate::test::{HELM, HELM_SOURCE, affects};
+
+    #[test]
+    fn unchanged() {
+        let diff = diff_affects(&affects(), &affects());
+
+        assert!(diff.is_empty());
+        assert!(diff.to_string().ends_with("no changes\n"));
+    }
+
+    #[test]
+    fn changes() {
+        let old = affects();
+        let mut new = affects();
+        new.metadata.game_version = Some("2024.02.01".into());
+        new.names[HELM as usize] = "Bronze Casque".into();
+        new.names.push("Iron Helm".into());
+        new.names.push("Central Shroud".into());
+        new.name_kinds.insert(2, [ItemKind::Gear].into());
+        new.name_kinds.insert(3, [ItemKind::Map].into());
+        new.equipment
+            .get_mut(&EquipSlot::Head)
+            .unwrap()
+            .get_mut(&6016)
+            .unwrap()
+            .insert(2, [(ItemKind::Gear, 2)].into());
+        new.vfx
+            .weapons
+            .entry(201)
+            .or_default()
+            .entry(1)
+            .or_default()
+            .insert(3, [1].into());
+        new.maps
+            .insert("f1f1/00".into(), [(ItemKind::Map, 3)].into());
+
+        let diff = diff_affects(&old, &new);
+        assert_eq!(None, diff.old_game_version);
+        assert_eq!(Some("2024.02.01".into()), diff.new_game_version);
+
+        // the helm kept its item id, so it was renamed rather than replaced
+        let gear = &diff.names["Gear"];
+        assert_eq!(BTreeSet::from(["Iron Helm".to_string()]), gear.added);
+        assert!(gear.removed.is_empty());
+        assert_eq!(
+            BTreeSet::from([("Bronze Helm".to_string(), "Bronze Casque".to_string())]),
+            gear.renamed
+        );
+        assert_eq!(
+            BTreeSet::from(["Central Shroud".to_string()]),
+            diff.names["Map"].added
+        );
+
+        assert_eq!(2, diff.models.len());
+        assert_eq!(HELM_SOURCE, diff.models[0].source);
+        assert_eq!(
+            ModelSource::Equipment {
+                slot: EquipSlot::Head,
+                model: 6016,
+                variant: 2,
+            },
+            diff.models[1].source
+        );
+        assert!(diff.models[1].old.is_empty());
+
+        assert_eq!(
+            BTreeSet::from([WeaponModel::new(201, 1).avfx(3).to_string()]),
+            diff.vfx_added
+        );
+        assert_eq!(
+            BTreeSet::from(["Central Shroud".to_string()]),
+            diff.maps_added["f1f1/00"]
+        );
+        assert!(diff.maps_removed.is_empty());
+
+        let report = diff.to_string();
+        assert!(report.starts_with("game version unknown -> 2024.02.01\n"));
+        assert!(report.contains("  ~ Bronze Helm -> Bronze Casque\n"));
+        assert!(report.contains("  ~ e6016 met v1: Bronze Helm -> Bronze Casque\n"));
+        assert!(report.contains("  + e6016 met v2: Iron Helm\n"));
+        assert!(report.contains("  + f1f1/00: Central Shroud\n"));
+
+        // and the other way round
+        let diff = diff_affects(&new, &old);
+        assert_eq!(
+            BTreeSet::from(["Iron Helm".to_string()]),
+            diff.names["Gear"].removed
+        );
+        assert_eq!(BTreeSet::from(["f1f1/00".to_string()]), diff.maps_removed);
+        assert!(diff.vfx_added.is_empty());
+    }
+}
@@ -3,21 +3,151 @@ use std::{
     collections::{BTreeMap, BTreeSet},
 };
 
-pub use affects_common::{Affects, EquipSlot, ItemKind};
+pub use affects_common::{
+    Affects, AffectsData, AttributeMask, EquipSlot, ItemKind, Language, ModelSource,
+};
+use affects_common::{KeyedTable, ModelQuery, ModelSourceKind, Names};
+pub use path_parser::types::{Gender, ModelInfo, ModelKind, Race};
 use path_parser::{
     GamePath,
-    path::chara::{
-        AccessoryPath, BodyType, BodyTypeSlot, CharacterPath, DemihumanPath, EquipmentPath,
-        MonsterPath, WeaponPath,
+    path::{
+        bg::{BgPath, TerritoryKind},
+        chara::{
+            AccessoryPath, BodyType, BodyTypeSlot, CharacterPath, DecalType, DemihumanPath,
+            EquipmentPath, MonsterPath, WeaponPath,
+        },
+        sound::SoundPath,
+        vfx::VfxPath,
     },
     types::SkeletonSlot,
 };
 
+pub use self::{
+    affected::AffectedItem,
+    diff::{AffectsDiff, ModelChange, NameChanges, diff_affects},
+    load::{LoadError, from_json_slice, from_slice},
+    paths::{FindsPaths, PathsFor, ReverseIndex},
+    races::affected_models,
+    summary::AffectsSummary,
+};
+
+mod affected;
+mod diff;
+mod load;
+mod paths;
+mod races;
+mod summary;
+#[cfg(test)]
+mod test;
+
 pub trait CalculatesAffects {
-    fn calculate_affected(&self, path: &str) -> BTreeMap<ItemKind, BTreeSet<Cow<'_, str>>>;
+    /// every item the path affects. names are given in `language` where a
+    /// translation exists, otherwise in english
+    fn calculate_affected_items_in(
+        &self,
+        path: &str,
+        language: Language,
+    ) -> BTreeSet<AffectedItem<'_>>;
+
+    fn calculate_affected_items(&self, path: &str) -> BTreeSet<AffectedItem<'_>> {
+        self.calculate_affected_items_in(path, Language::English)
+    }
+
+    /// like [`calculate_affected_items_in`](Self::calculate_affected_items_in),
+    /// but a model path only affects the variants that show at least one of
+    /// `attributes`, such as a mod that only changes the hood of a robe.
+    /// variants without recorded attributes, other paths and an empty mask
+    /// are treated as showing everything
+    fn calculate_affected_items_with_attributes_in(
+        &self,
+        path: &str,
+        attributes: AttributeMask,
+        language: Language,
+    ) -> BTreeSet<AffectedItem<'_>>;
+
+    fn calculate_affected_items_with_attributes(
+        &self,
+        path: &str,
+        attributes: AttributeMask,
+    ) -> BTreeSet<AffectedItem<'_>> {
+        self.calculate_affected_items_with_attributes_in(path, attributes, Language::English)
+    }
+
+    /// the gear whose variants use a material animation. material animations
+    /// have no files of their own, so they are looked up by their imc id
+    fn calculate_material_animation_users_in(
+        &self,
+        id: u8,
+        language: Language,
+    ) -> BTreeSet<AffectedItem<'_>>;
+
+    fn calculate_material_animation_users(&self, id: u8) -> BTreeSet<AffectedItem<'_>> {
+        self.calculate_material_animation_users_in(id, Language::English)
+    }
+
+    /// calculates every path at once, such as all the files in a mod. paths
+    /// are compared without regard to slash direction or surrounding
+    /// whitespace, and each distinct path is only calculated once. the
+    /// summary lists paths as they were given
+    fn calculate_affected_many_in<P: AsRef<str>>(
+        &self,
+        paths: impl IntoIterator<Item = P>,
+        language: Language,
+    ) -> AffectsSummary<'_> {
+        let mut normalised: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for path in paths {
+            let path = path.as_ref();
+            normalised
+                .entry(summary::normalise_path(path))
+                .or_default()
+                .insert(path.to_string());
+        }
+
+        let mut summary = AffectsSummary::default();
+        for (path, originals) in normalised {
+            let items = self.calculate_affected_items_in(&path, language);
+            for original in originals {
+                summary.add(&original, items.clone());
+            }
+        }
+
+        summary
+    }
+
+    fn calculate_affected_many<P: AsRef<str>>(
+        &self,
+        paths: impl IntoIterator<Item = P>,
+    ) -> AffectsSummary<'_> {
+        self.calculate_affected_many_in(paths, Language::English)
+    }
+
+    fn calculate_affected_in(
+        &self,
+        path: &str,
+        language: Language,
+    ) -> BTreeMap<ItemKind, BTreeSet<Cow<'_, str>>> {
+        let mut grouped: BTreeMap<ItemKind, BTreeSet<Cow<str>>> = Default::default();
+        for item in self.calculate_affected_items_in(path, language) {
+            grouped.entry(item.kind).or_default().insert(item.name);
+        }
+
+        grouped
+    }
+
+    fn calculate_affected(&self, path: &str) -> BTreeMap<ItemKind, BTreeSet<Cow<'_, str>>> {
+        self.calculate_affected_in(path, Language::English)
+    }
 
     fn calculate_affected_cloned(&self, path: &str) -> BTreeMap<ItemKind, BTreeSet<String>> {
-        self.calculate_affected(path)
+        self.calculate_affected_cloned_in(path, Language::English)
+    }
+
+    fn calculate_affected_cloned_in(
+        &self,
+        path: &str,
+        language: Language,
+    ) -> BTreeMap<ItemKind, BTreeSet<String>> {
+        self.calculate_affected_in(path, language)
             .into_iter()
             .map(|(kind, names)| {
                 (
@@ -32,18 +162,59 @@ pub trait CalculatesAffects {
     }
 }
 
-impl CalculatesAffects for Affects {
-    fn calculate_affected(&self, path: &str) -> BTreeMap<ItemKind, BTreeSet<Cow<'_, str>>> {
-        let convert_names = |names: &BTreeSet<(ItemKind, u16)>| {
+impl<T: AffectsData> CalculatesAffects for T {
+    fn calculate_affected_items_in(
+        &self,
+        path: &str,
+        language: Language,
+    ) -> BTreeSet<AffectedItem<'_>> {
+        let convert_names = |names: Names, source: Option<ModelSource>| {
             names
                 .iter()
-                .flat_map(|&(kind, index)| {
-                    self.names
-                        .get(index as usize)
-                        .map(|name| (kind, Cow::from(name.as_str())))
-                })
+                .flat_map(|(kind, index)| affected_items(self, kind, index, source, language))
                 .collect::<BTreeSet<_>>()
         };
+        // every model variant matching the query, or nothing if there are none
+        let convert_models = |query: ModelQuery| {
+            let names = self
+                .models(query)
+                .into_iter()
+                .flat_map(|(source, names)| convert_names(names, Some(source)))
+                .collect::<BTreeSet<_>>();
+            if names.is_empty() { None } else { Some(names) }
+        };
+        // the model variants matching the query whose imc files point them at
+        // a material variant
+        let convert_material = |query: ModelQuery, material: u8| {
+            let materials = self.materials();
+            let names = self
+                .models(query)
+                .into_iter()
+                .filter(|&(source, _)| materials.material(source) == material)
+                .flat_map(|(source, names)| convert_names(names, Some(source)))
+                .collect::<BTreeSet<_>>();
+            if names.is_empty() { None } else { Some(names) }
+        };
+        // the model variants matching the query that show an effect
+        let convert_effect = |query: ModelQuery, effect_id: u16| {
+            let names = self
+                .model_effects(query)
+                .into_iter()
+                .filter(|&(_, effect)| effect == effect_id as u8)
+                .flat_map(|(source, _)| convert_models(source.into()))
+                .flatten()
+                .collect::<BTreeSet<_>>();
+            if names.is_empty() { None } else { Some(names) }
+        };
+        // who can pick a hairstyle, face, tail or ears, and what unlocks it
+        let customisation = |model_info: ModelInfo, body_type: BodyType, primary_id: u16| {
+            self.customisations()
+                .models
+                .get(&model_info.to_raw_id()?)
+                .and_then(|body_types| body_types.get(body_type.as_str()))
+                .and_then(|ids| ids.get(&primary_id))
+                .map(|names| convert_names(names.into(), None))
+        };
 
         let res = GamePath::parse(path);
 
@@ -62,65 +233,33 @@ impl CalculatesAffects for Affects {
                     primary_id,
                     secondary_id,
                 },
-            )) => self
-                .monsters
-                .get(&primary_id)
-                .and_then(|secondaries| secondaries.get(&(secondary_id as u8)))
-                .map(|variants| {
-                    variants.values().fold(
-                        BTreeSet::new(),
-                        |mut acc: BTreeSet<(ItemKind, u16)>, x| {
-                            acc.extend(x);
-                            acc
-                        },
-                    )
-                })
-                .map(|names| convert_names(&names)),
+            )) => convert_models(
+                ModelQuery::model(ModelSourceKind::Monster, primary_id).secondary(secondary_id),
+            ),
             Ok(GamePath::Monster(MonsterPath::Mtrl {
                 primary_id,
                 secondary_id,
                 variant_id,
-            })) => self
-                .monsters
-                .get(&primary_id)
-                .and_then(|secondaries| secondaries.get(&(secondary_id as u8)))
-                .and_then(|variants| variants.get(&(variant_id as u8)))
-                .map(convert_names),
+            })) => convert_material(
+                ModelQuery::model(ModelSourceKind::Monster, primary_id).secondary(secondary_id),
+                variant_id as u8,
+            ),
             Ok(GamePath::Monster(MonsterPath::Tex {
                 primary_id,
                 secondary_id,
                 variant_id,
-            })) => self
-                .monsters
-                .get(&primary_id)
-                .and_then(|secondaries| secondaries.get(&(secondary_id as u8)))
-                .and_then(|variants| variants.get(&variant_id))
-                .map(convert_names),
+            })) => convert_material(
+                ModelQuery::model(ModelSourceKind::Monster, primary_id).secondary(secondary_id),
+                variant_id,
+            ),
             Ok(GamePath::Monster(MonsterPath::Avfx {
                 primary_id,
                 secondary_id,
                 effect_id,
-            })) => self
-                .vfx
-                .monsters
-                .get(&primary_id)
-                .and_then(|secondaries| secondaries.get(&(secondary_id as u8)))
-                .and_then(|effects| effects.get(&(effect_id as u8)))
-                .and_then(|variant_ids| {
-                    self.monsters
-                        .get(&primary_id)
-                        .and_then(|secondaries| secondaries.get(&(secondary_id as u8)))
-                        .map(|variants| {
-                            variant_ids.iter().flat_map(|id| variants.get(id)).fold(
-                                BTreeSet::new(),
-                                |mut acc: BTreeSet<(ItemKind, u16)>, x| {
-                                    acc.extend(x);
-                                    acc
-                                },
-                            )
-                        })
-                        .map(|names| convert_names(&names))
-                }),
+            })) => convert_effect(
+                ModelQuery::model(ModelSourceKind::Monster, primary_id).secondary(secondary_id),
+                effect_id,
+            ),
 
             // weapon
             Ok(GamePath::Weapon(
@@ -136,65 +275,33 @@ impl CalculatesAffects for Affects {
                     primary_id,
                     secondary_id,
                 },
-            )) => self
-                .weapons
-                .get(&primary_id)
-                .and_then(|secondaries| secondaries.get(&secondary_id))
-                .map(|variants| {
-                    variants.values().fold(
-                        BTreeSet::new(),
-                        |mut acc: BTreeSet<(ItemKind, u16)>, x| {
-                            acc.extend(x);
-                            acc
-                        },
-                    )
-                })
-                .map(|names| convert_names(&names)),
+            )) => convert_models(
+                ModelQuery::model(ModelSourceKind::Weapon, primary_id).secondary(secondary_id),
+            ),
             Ok(GamePath::Weapon(WeaponPath::Mtrl {
                 primary_id,
                 secondary_id,
                 variant_id,
-            })) => self
-                .weapons
-                .get(&primary_id)
-                .and_then(|secondaries| secondaries.get(&secondary_id))
-                .and_then(|variants| variants.get(&(variant_id as u8)))
-                .map(convert_names),
+            })) => convert_material(
+                ModelQuery::model(ModelSourceKind::Weapon, primary_id).secondary(secondary_id),
+                variant_id as u8,
+            ),
             Ok(GamePath::Weapon(WeaponPath::Tex {
                 primary_id,
                 secondary_id,
                 variant_id,
-            })) => self
-                .weapons
-                .get(&primary_id)
-                .and_then(|secondaries| secondaries.get(&secondary_id))
-                .and_then(|variants| variants.get(&variant_id))
-                .map(convert_names),
+            })) => convert_material(
+                ModelQuery::model(ModelSourceKind::Weapon, primary_id).secondary(secondary_id),
+                variant_id,
+            ),
             Ok(GamePath::Weapon(WeaponPath::Avfx {
                 primary_id,
                 secondary_id,
                 effect_id,
-            })) => self
-                .vfx
-                .weapons
-                .get(&primary_id)
-                .and_then(|secondaries| secondaries.get(&(secondary_id as u8)))
-                .and_then(|effects| effects.get(&(effect_id as u8)))
-                .and_then(|variant_ids| {
-                    self.monsters
-                        .get(&primary_id)
-                        .and_then(|secondaries| secondaries.get(&(secondary_id as u8)))
-                        .map(|variants| {
-                            variant_ids.iter().flat_map(|id| variants.get(id)).fold(
-                                BTreeSet::new(),
-                                |mut acc: BTreeSet<(ItemKind, u16)>, x| {
-                                    acc.extend(x);
-                                    acc
-                                },
-                            )
-                        })
-                        .map(|names| convert_names(&names))
-                }),
+            })) => convert_effect(
+                ModelQuery::model(ModelSourceKind::Weapon, primary_id).secondary(secondary_id),
+                effect_id,
+            ),
 
             // demihuman
             Ok(GamePath::Demihuman(
@@ -211,82 +318,43 @@ impl CalculatesAffects for Affects {
                     secondary_id,
                     ..
                 },
-            )) => self
-                .demihumans
-                .get(&primary_id)
-                .and_then(|secondaries| secondaries.get(&(secondary_id as u8)))
-                .map(|variants| {
-                    variants.values().fold(
-                        BTreeSet::new(),
-                        |mut acc: BTreeSet<(ItemKind, u16)>, x| {
-                            acc.extend(x);
-                            acc
-                        },
-                    )
-                })
-                .map(|names| convert_names(&names)),
+            )) => convert_models(
+                ModelQuery::model(ModelSourceKind::Demihuman, primary_id).secondary(secondary_id),
+            ),
             Ok(GamePath::Demihuman(DemihumanPath::Mtrl {
                 primary_id,
                 secondary_id,
                 variant_id,
                 ..
-            })) => self
-                .demihumans
-                .get(&primary_id)
-                .and_then(|secondaries| secondaries.get(&(secondary_id as u8)))
-                .and_then(|variants| variants.get(&(variant_id as u8)))
-                .map(convert_names),
+            })) => convert_models(
+                ModelQuery::model(ModelSourceKind::Demihuman, primary_id)
+                    .secondary(secondary_id)
+                    .variant(variant_id as u8),
+            ),
             Ok(GamePath::Demihuman(DemihumanPath::Tex {
                 primary_id,
                 secondary_id,
                 variant_id,
                 ..
-            })) => self
-                .demihumans
-                .get(&primary_id)
-                .and_then(|secondaries| secondaries.get(&(secondary_id as u8)))
-                .and_then(|variants| variants.get(&variant_id))
-                .map(convert_names),
+            })) => convert_models(
+                ModelQuery::model(ModelSourceKind::Demihuman, primary_id)
+                    .secondary(secondary_id)
+                    .variant(variant_id),
+            ),
             Ok(GamePath::Demihuman(DemihumanPath::Avfx {
                 primary_id,
                 secondary_id,
                 effect_id,
-            })) => self
-                .vfx
-                .demihumans
-                .get(&primary_id)
-                .and_then(|secondaries| secondaries.get(&(secondary_id as u8)))
-                .and_then(|effects| effects.get(&(effect_id as u8)))
-                .and_then(|variant_ids| {
-                    self.demihumans
-                        .get(&primary_id)
-                        .and_then(|secondaries| secondaries.get(&(secondary_id as u8)))
-                        .map(|variants| {
-                            variant_ids.iter().flat_map(|id| variants.get(id)).fold(
-                                BTreeSet::new(),
-                                |mut acc: BTreeSet<(ItemKind, u16)>, x| {
-                                    acc.extend(x);
-                                    acc
-                                },
-                            )
-                        })
-                        .map(|names| convert_names(&names))
-                }),
+            })) => convert_effect(
+                ModelQuery::model(ModelSourceKind::Demihuman, primary_id).secondary(secondary_id),
+                effect_id,
+            ),
 
-            // equipment/accessory
+            // equipment/accessory, in every slot
             Ok(
                 GamePath::Equipment(EquipmentPath::Imc(primary_id))
                 | GamePath::Accessory(AccessoryPath::Imc(primary_id)),
-            ) => Some(convert_names(
-                &self
-                    .equipment
-                    .values()
-                    .flat_map(|models| models.get(&primary_id))
-                    .flat_map(|variants| variants.values())
-                    .flatten()
-                    .copied()
-                    .collect::<BTreeSet<_>>(),
-            )),
+            ) => convert_models(ModelQuery::model(ModelSourceKind::Equipment, primary_id)),
 
             // smallclothes special case
             Ok(GamePath::Equipment(
@@ -328,20 +396,9 @@ impl CalculatesAffects for Affects {
                 | GamePath::Accessory(AccessoryPath::Mdl {
                     primary_id, slot, ..
                 }),
-            ) => self
-                .equipment
-                .get(&slot)
-                .and_then(|models| models.get(&primary_id))
-                .map(|variants| {
-                    variants.values().fold(
-                        BTreeSet::new(),
-                        |mut acc: BTreeSet<(ItemKind, u16)>, x| {
-                            acc.extend(x);
-                            acc
-                        },
-                    )
-                })
-                .map(|names| convert_names(&names)),
+            ) => convert_models(
+                ModelQuery::model(ModelSourceKind::Equipment, primary_id).secondary(slot as u16),
+            ),
             Ok(
                 GamePath::Equipment(EquipmentPath::Mtrl {
                     primary_id,
@@ -355,12 +412,10 @@ impl CalculatesAffects for Affects {
                     slot,
                     ..
                 }),
-            ) => self
-                .equipment
-                .get(&slot)
-                .and_then(|models| models.get(&primary_id))
-                .and_then(|variants| variants.get(&(variant_id as u8)))
-                .map(convert_names),
+            ) => convert_material(
+                ModelQuery::model(ModelSourceKind::Equipment, primary_id).secondary(slot as u16),
+                variant_id as u8,
+            ),
             Ok(
                 GamePath::Equipment(EquipmentPath::Tex {
                     primary_id,
@@ -374,61 +429,48 @@ impl CalculatesAffects for Affects {
                     slot,
                     ..
                 }),
-            ) => self
-                .equipment
-                .get(&slot)
-                .and_then(|models| models.get(&primary_id))
-                .and_then(|variants| variants.get(&variant_id))
-                .map(convert_names),
+            ) => convert_material(
+                ModelQuery::model(ModelSourceKind::Equipment, primary_id).secondary(slot as u16),
+                variant_id,
+            ),
             Ok(GamePath::Equipment(EquipmentPath::Avfx {
                 primary_id,
                 effect_id,
-            })) => self
-                .vfx
-                .equipment
-                .get(&primary_id)
-                .and_then(|effects| effects.get(&(effect_id as u8)))
-                .map(|variant_ids| {
-                    variant_ids
-                        .iter()
-                        .flat_map(|(slot, variant_id)| {
-                            self.equipment
-                                .get(slot)
-                                .and_then(|primaries| primaries.get(&primary_id))
-                                .and_then(|variants| variants.get(variant_id))
-                        })
-                        .fold(BTreeSet::new(), |mut acc: BTreeSet<(ItemKind, u16)>, x| {
-                            acc.extend(x);
-                            acc
-                        })
-                })
-                .map(|names| convert_names(&names)),
-
-            // character
-            Ok(GamePath::Character(CharacterPath::Mdl {
-                primary_id,
-                model_info,
-                body_type,
-                slot,
-            })) => single_name(
-                ItemKind::Customisation,
-                format!(
-                    "{model_info} {} {primary_id}",
-                    customisation_type(body_type, slot),
-                ),
+            })) => convert_effect(
+                ModelQuery::model(ModelSourceKind::Equipment, primary_id),
+                effect_id,
             ),
-            Ok(GamePath::Character(CharacterPath::Mtrl {
-                primary_id,
+            // character
+            Ok(GamePath::Character(
+                CharacterPath::Mdl {
+                    primary_id,
+                    model_info,
+                    body_type,
+                    slot,
+                }
+                | CharacterPath::Mtrl {
+                    primary_id,
+                    model_info,
+                    body_type,
+                    slot,
+                    ..
+                },
+            )) => customisation(model_info, body_type, primary_id).or_else(|| {
+                single_name(
+                    ItemKind::Customisation,
+                    format!(
+                        "{model_info} {} {primary_id}",
+                        customisation_type(body_type, slot),
+                    ),
+                )
+            }),
+            Ok(GamePath::Character(CharacterPath::Tex {
                 model_info,
-                body_type,
-                slot,
+                slot: None,
                 ..
             })) => single_name(
                 ItemKind::Customisation,
-                format!(
-                    "{model_info} {} {primary_id}",
-                    customisation_type(body_type, slot),
-                ),
+                format!("{model_info} Skin Textures"),
             ),
             Ok(GamePath::Character(CharacterPath::Tex {
                 primary_id,
@@ -436,247 +478,74 @@ impl CalculatesAffects for Affects {
                 body_type,
                 slot,
                 ..
-            })) => single_name(
-                ItemKind::Customisation,
-                if slot.is_none() {
-                    format!("{model_info} Skin Textures")
-                } else {
+            })) => customisation(model_info, body_type, primary_id).or_else(|| {
+                single_name(
+                    ItemKind::Customisation,
                     format!(
                         "{model_info} {} {primary_id}",
                         customisation_type(body_type, slot),
-                    )
-                },
-            ),
+                    ),
+                )
+            }),
             Ok(GamePath::Character(CharacterPath::Catchlight(catchlight))) => {
                 single_name(ItemKind::Customisation, format!("Catchlight {catchlight}"))
             }
-            Ok(GamePath::Character(CharacterPath::Eye { id, kind })) => {
-                use const_format::formatcp;
-
-                const EYES: &str = "Eyes";
-                const FEMALE: &str = "Female";
-                const MALE: &str = "Male";
-                const MIDLANDER: &str = "Midlander";
-                const HIGHLANDER: &str = "Highlander";
-                const DUSKWIGHT: &str = "Duskwight";
-                const WILDWOOD: &str = "Wildwood";
-                const PLAINSFOLK: &str = "Plainsfolk";
-                const DUNESFOLK: &str = "Dunesfolk";
-                const SEEKER: &str = "Seeker of the Sun";
-                const KEEPER: &str = "Keeper of the Moon";
-                const SEA_WOLF: &str = "Sea Wolf";
-                const HELLSGUARD: &str = "Hellsguard";
-                const RAEN: &str = "Raen";
-                const XAELA: &str = "Xaela";
-                const HELIONS: &str = "Helions";
-                const THE_LOST: &str = "The Lost";
-                const RAVA: &str = "Rava";
-                const VEENA: &str = "Veena";
-
-                let races_and_tribes = match id {
-                    1 => match kind {
-                        "norm" => Some(
-                            &[
-                                formatcp!("{FEMALE} {MIDLANDER} {EYES}"),
-                                formatcp!("{MALE} {MIDLANDER} {EYES}"),
-                                formatcp!("{FEMALE} {HIGHLANDER} {EYES}"),
-                                formatcp!("{MALE} {HIGHLANDER} {EYES}"),
-                                formatcp!("{FEMALE} {DUSKWIGHT} {EYES}"),
-                                formatcp!("{MALE} {DUSKWIGHT} {EYES}"),
-                                formatcp!("{FEMALE} {WILDWOOD} {EYES}"),
-                                formatcp!("{MALE} {WILDWOOD} {EYES}"),
-                                formatcp!("{FEMALE} {PLAINSFOLK} {EYES}"),
-                                formatcp!("{MALE} {PLAINSFOLK} {EYES}"),
-                                formatcp!("{FEMALE} {SEEKER} {EYES}"),
-                                formatcp!("{MALE} {SEEKER} {EYES}"),
-                                formatcp!("{FEMALE} {KEEPER} {EYES}"),
-                                formatcp!("{MALE} {KEEPER} {EYES}"),
-                                formatcp!("{FEMALE} {SEA_WOLF} {EYES}"),
-                                formatcp!("{MALE} {SEA_WOLF} {EYES}"),
-                                formatcp!("{FEMALE} {HELLSGUARD} {EYES}"),
-                                formatcp!("{MALE} {HELLSGUARD} {EYES}"),
-                                formatcp!("{FEMALE} {RAEN} {EYES}"),
-                                formatcp!("{FEMALE} {XAELA} {EYES}"),
-                                formatcp!("{FEMALE} {RAVA} {EYES}"),
-                                formatcp!("{MALE} {RAVA} {EYES}"),
-                                formatcp!("{FEMALE} {VEENA} {EYES}"),
-                                formatcp!("{MALE} {VEENA} {EYES}"),
-                            ][..],
-                        ),
-                        "mask" => Some(
-                            &[
-                                formatcp!("{FEMALE} {MIDLANDER} {EYES}"),
-                                formatcp!("{MALE} {MIDLANDER} {EYES}"),
-                                formatcp!("{FEMALE} {HIGHLANDER} {EYES}"),
-                                formatcp!("{MALE} {HIGHLANDER} {EYES}"),
-                                formatcp!("{FEMALE} {DUSKWIGHT} {EYES}"),
-                                formatcp!("{MALE} {DUSKWIGHT} {EYES}"),
-                                formatcp!("{FEMALE} {WILDWOOD} {EYES}"),
-                                formatcp!("{MALE} {WILDWOOD} {EYES}"),
-                                formatcp!("{FEMALE} {PLAINSFOLK} {EYES}"),
-                                formatcp!("{MALE} {PLAINSFOLK} {EYES}"),
-                                formatcp!("{FEMALE} {DUNESFOLK} {EYES}"),
-                                formatcp!("{MALE} {DUNESFOLK} {EYES}"),
-                                formatcp!("{FEMALE} {SEEKER} {EYES}"),
-                                formatcp!("{MALE} {SEEKER} {EYES}"),
-                                formatcp!("{FEMALE} {KEEPER} {EYES}"),
-                                formatcp!("{MALE} {KEEPER} {EYES}"),
-                                formatcp!("{FEMALE} {SEA_WOLF} {EYES}"),
-                                formatcp!("{MALE} {SEA_WOLF} {EYES}"),
-                                formatcp!("{FEMALE} {HELLSGUARD} {EYES}"),
-                                formatcp!("{MALE} {HELLSGUARD} {EYES}"),
-                                formatcp!("{FEMALE} {RAEN} {EYES}"),
-                                formatcp!("{MALE} {RAEN} {EYES}"),
-                                formatcp!("{FEMALE} {XAELA} {EYES}"),
-                                formatcp!("{MALE} {XAELA} {EYES}"),
-                                formatcp!("{FEMALE} {RAVA} {EYES}"),
-                                formatcp!("{MALE} {RAVA} {EYES}"),
-                                formatcp!("{FEMALE} {VEENA} {EYES}"),
-                                formatcp!("{MALE} {VEENA} {EYES}"),
-                            ][..],
-                        ),
-                        "base" => Some(
-                            &[
-                                formatcp!("{FEMALE} {MIDLANDER} {EYES}"),
-                                formatcp!("{FEMALE} {RAVA} {EYES}"),
-                                formatcp!("{MALE} {VEENA} {EYES}"),
-                            ][..],
-                        ),
-                        _ => None,
-                    },
-                    2 if kind == "base" => Some(
-                        &[
-                            formatcp!("{FEMALE} {SEEKER} {EYES}"),
-                            formatcp!("{MALE} {SEEKER} {EYES}"),
-                            formatcp!("{FEMALE} {VEENA} {EYES}"),
-                        ][..],
-                    ),
-                    3 if kind == "base" => Some(
-                        &[
-                            formatcp!("{FEMALE} {KEEPER} {EYES}"),
-                            formatcp!("{MALE} {KEEPER} {EYES}"),
-                        ][..],
-                    ),
-                    4 => match kind {
-                        "norm" => Some(
-                            &[
-                                formatcp!("{FEMALE} {DUNESFOLK} {EYES}"),
-                                formatcp!("{MALE} {DUNESFOLK} {EYES}"),
-                                formatcp!("{MALE} {RAEN} {EYES}"),
-                                formatcp!("{MALE} {XAELA} {EYES}"),
-                            ][..],
-                        ),
-                        "base" => Some(
-                            &[
-                                formatcp!("{FEMALE} {PLAINSFOLK} {EYES}"),
-                                formatcp!("{MALE} {PLAINSFOLK} {EYES}"),
-                            ][..],
-                        ),
-                        _ => None,
-                    },
-                    5 if kind == "base" => Some(
-                        &[
-                            formatcp!("{FEMALE} {DUNESFOLK} {EYES}"),
-                            formatcp!("{MALE} {DUNESFOLK} {EYES}"),
-                        ][..],
-                    ),
-                    6 => match kind {
-                        "norm" | "mask" => Some(
-                            &[
-                                formatcp!("{FEMALE} {HELIONS} {EYES}"),
-                                formatcp!("{MALE} {HELIONS} {EYES}"),
-                                formatcp!("{FEMALE} {THE_LOST} {EYES}"),
-                                formatcp!("{MALE} {THE_LOST} {EYES}"),
-                            ][..],
-                        ),
-                        "base" => Some(
-                            &[
-                                formatcp!("{FEMALE} {HELIONS} {EYES}"),
-                                formatcp!("{MALE} {HELIONS} {EYES}"),
-                            ][..],
-                        ),
-                        _ => None,
-                    },
-                    7 if kind == "base" => Some(
-                        &[
-                            formatcp!("{FEMALE} {THE_LOST} {EYES}"),
-                            formatcp!("{MALE} {THE_LOST} {EYES}"),
-                        ][..],
-                    ),
-                    9 if kind == "base" => Some(
-                        &[
-                            formatcp!("{FEMALE} {DUSKWIGHT} {EYES}"),
-                            formatcp!("{MALE} {DUSKWIGHT} {EYES}"),
-                            formatcp!("{FEMALE} {WILDWOOD} {EYES}"),
-                            formatcp!("{MALE} {WILDWOOD} {EYES}"),
-                            formatcp!("{MALE} {SEA_WOLF} {EYES}"), // faces 1 & 3
-                            formatcp!("{MALE} {HELLSGUARD} {EYES}"), // faces 1 & 3
-                        ][..],
-                    ),
-                    10 if kind == "base" => Some(
-                        &[
-                            formatcp!("{FEMALE} {SEA_WOLF} {EYES}"),
-                            formatcp!("{FEMALE} {HELLSGUARD} {EYES}"),
-                            formatcp!("{FEMALE} {RAEN} {EYES}"),
-                            formatcp!("{FEMALE} {XAELA} {EYES}"),
-                        ][..],
-                    ),
-                    11 if kind == "base" => Some(
-                        &[
-                            formatcp!("{MALE} {MIDLANDER} {EYES}"), // faces 1-3 & 5-7
-                            formatcp!("{MALE} {HIGHLANDER} {EYES}"),
-                            formatcp!("{FEMALE} {HIGHLANDER} {EYES}"), // faces 1-3
-                            formatcp!("{MALE} {SEA_WOLF} {EYES}"),     // face 4
-                            formatcp!("{MALE} {HELLSGUARD} {EYES}"),   // face 4
-                            formatcp!("{MALE} {RAVA} {EYES}"),
-                        ][..],
-                    ),
-                    12 if kind == "base" => Some(
-                        &[
-                            formatcp!("{MALE} {MIDLANDER} {EYES}"), // face 4
-                        ][..],
-                    ),
-                    13 if kind == "base" => Some(
-                        &[
-                            formatcp!("{FEMALE} {HIGHLANDER} {EYES}"), // face 4
-                        ][..],
-                    ),
-                    14 if kind == "base" => Some(
-                        &[
-                            formatcp!("{MALE} {RAEN} {EYES}"),
-                            formatcp!("{MALE} {XAELA} {EYES}"),
-                        ][..],
-                    ),
-                    _ => None,
-                };
-
-                races_and_tribes.map(|list| {
-                    list.iter()
-                        .map(|&s| (ItemKind::Customisation, Cow::from(s)))
-                        .collect::<BTreeSet<_>>()
-                })
-            }
+            Ok(GamePath::Character(CharacterPath::Eye { id, kind })) => self
+                .customisations()
+                .eyes
+                .get(&format!("eye{id:02}_{kind}"))
+                .map(|names| convert_names(names.into(), None)),
             Ok(GamePath::Character(CharacterPath::Skin(skin))) => {
                 single_name(ItemKind::Customisation, format!("Skin {skin}"))
             }
-            Ok(GamePath::Character(CharacterPath::Decal { kind, primary_id })) => single_name(
-                ItemKind::Customisation,
-                format!("{kind} Decal {primary_id}"),
-            ),
+            Ok(GamePath::Character(CharacterPath::Decal { kind, primary_id })) => {
+                // equipment decals are named for the gear using them and face
+                // paints for who can pick them, when either is known
+                let users = match kind {
+                    DecalType::Equip => u8::try_from(primary_id)
+                        .ok()
+                        .and_then(|id| self.materials().decals.get(&id))
+                        .map(|users| gear_using(self, users, language)),
+                    DecalType::Face => u16::try_from(primary_id)
+                        .ok()
+                        .and_then(|id| self.customisations().face_paints.get(&id))
+                        .map(|names| convert_names(names.into(), None)),
+                };
+
+                match users {
+                    Some(users) => Some(users),
+                    None => single_name(
+                        ItemKind::Customisation,
+                        format!("{kind} Decal {primary_id}"),
+                    ),
+                }
+            }
             Ok(GamePath::Character(CharacterPath::Skeleton {
                 primary_id,
                 model_info,
                 slot,
-            })) => single_name(
-                ItemKind::Customisation,
-                if slot == SkeletonSlot::Base {
-                    format!("{model_info} Skeleton {primary_id}")
-                } else {
-                    format!("{model_info} {slot} Skeleton {primary_id}")
-                },
-            ),
+            })) => {
+                let body_type = match slot {
+                    SkeletonSlot::Face => Some(BodyType::Face),
+                    SkeletonSlot::Hair => Some(BodyType::Hair),
+                    _ => None,
+                };
+
+                body_type
+                    .and_then(|body_type| customisation(model_info, body_type, primary_id))
+                    .or_else(|| {
+                        single_name(
+                            ItemKind::Customisation,
+                            if slot == SkeletonSlot::Base {
+                                format!("{model_info} Skeleton {primary_id}")
+                            } else {
+                                format!("{model_info} {slot} Skeleton {primary_id}")
+                            },
+                        )
+                    })
+            }
             Ok(GamePath::Character(CharacterPath::Tmb(anim_key))) => {
-                let names = check_basic_animations(self, anim_key);
+                let names = check_basic_animations(self, &anim_key, language);
                 if names.is_empty() { None } else { Some(names) }
             }
             Ok(GamePath::Character(CharacterPath::Pap {
@@ -685,9 +554,9 @@ impl CalculatesAffects for Affects {
                 key: anim_key,
                 ..
             })) => {
-                let mut names = check_basic_animations(self, anim_key);
+                let mut names = check_basic_animations(self, &anim_key, language);
 
-                let kind = match anim_key {
+                let kind = match &*anim_key {
                     "resident/idle" => Some("idle"),
                     "resident/move_a" => Some("movement"),
                     "resident/move_b" => Some("movement"),
@@ -697,7 +566,7 @@ impl CalculatesAffects for Affects {
                 };
 
                 if let Some(kind) = kind {
-                    let job = match category {
+                    let job = match category.as_deref() {
                         Some("common") => Some(""),
                         Some("2ax_emp") => Some(" WAR"),
                         Some("2bk_emp") => Some(" SCH/SMN"),
@@ -730,9 +599,9 @@ impl CalculatesAffects for Affects {
                     };
 
                     if let Some(job) = job {
-                        names.insert((
+                        names.insert(AffectedItem::new(
                             ItemKind::Animation,
-                            Cow::from(format!("{model_info}{job} {kind}")),
+                            format!("{model_info}{job} {kind}"),
                         ));
                     }
                 }
@@ -744,6 +613,103 @@ impl CalculatesAffects for Affects {
                 format!("{model_info} attachment offsets"),
             ),
 
+            // bg
+            Ok(GamePath::Bg(BgPath::Territory {
+                kind, zone, part, ..
+            })) => {
+                // maps are keyed by {zone}/{variant}, so every map for this
+                // zone shares the prefix
+                let prefix = format!("{zone}/");
+                let mut names = self
+                    .keyed_range(KeyedTable::Maps, &prefix)
+                    .into_iter()
+                    .flat_map(|(_, names)| convert_names(names, None))
+                    .map(|item| {
+                        let name = format!("{} ({part})", item.name);
+                        item.renamed(name)
+                    })
+                    .collect::<BTreeSet<_>>();
+
+                if names.is_empty() {
+                    names.insert(AffectedItem::new(
+                        ItemKind::Miscellaneous,
+                        format!("World ({part})"),
+                    ));
+                }
+
+                if kind == TerritoryKind::Housing {
+                    names.insert(AffectedItem::new(ItemKind::Miscellaneous, "Housing"));
+                }
+
+                Some(names)
+            }
+            Ok(GamePath::Bg(path @ BgPath::Common { .. })) => single_name_ref(
+                ItemKind::Miscellaneous,
+                if path.is_housing() {
+                    "Housing"
+                } else {
+                    "Common props"
+                },
+            ),
+
+            // vfx
+            Ok(GamePath::Vfx(path)) => {
+                let mut names = self
+                    .keyed(KeyedTable::Effects, path.file_stem())
+                    .map(|names| convert_names(names, None))
+                    .unwrap_or_default();
+
+                // effects belonging to a model are used by everything using
+                // that model, no matter the variant
+                let model = match path {
+                    VfxPath::Monster { primary_id, .. } => {
+                        Some(ModelQuery::model(ModelSourceKind::Monster, primary_id))
+                    }
+                    VfxPath::Weapon { primary_id, .. } => {
+                        Some(ModelQuery::model(ModelSourceKind::Weapon, primary_id))
+                    }
+                    _ => None,
+                };
+                names.extend(model.and_then(convert_models).into_iter().flatten());
+
+                if names.is_empty() {
+                    names.insert(AffectedItem::new(ItemKind::Miscellaneous, "VFX"));
+                }
+
+                Some(names)
+            }
+
+            // sound
+            Ok(GamePath::Sound(path)) => {
+                let label = match path {
+                    SoundPath::Music { .. } => "BGM",
+                    SoundPath::Battle(_) => "Battle sound",
+                    SoundPath::EmoteVoice(_) => "Emote voice",
+                    SoundPath::Footstep(_) => "Footsteps",
+                    SoundPath::Vfx(_) => "Effect sound",
+                    SoundPath::Other { .. } => "Sound",
+                };
+
+                // sheets don't agree on the case of paths, so the tables are
+                // keyed by the lowercase path
+                let key = path.to_string().to_lowercase();
+                let mut names = [&self.sounds().bgm, &self.sounds().timelines]
+                    .into_iter()
+                    .filter_map(|sounds| sounds.get(&key))
+                    .flat_map(|names| convert_names(names.into(), None))
+                    .map(|item| {
+                        let name = format!("{label}: {}", item.name);
+                        item.renamed(name)
+                    })
+                    .collect::<BTreeSet<_>>();
+
+                if names.is_empty() {
+                    names.insert(AffectedItem::new(ItemKind::Miscellaneous, label));
+                }
+
+                Some(names)
+            }
+
             // icon
             Ok(GamePath::Icon { primary_id, .. }) => {
                 single_name(ItemKind::Icon, format!("#{primary_id}"))
@@ -755,9 +721,8 @@ impl CalculatesAffects for Affects {
                 variant,
                 ..
             }) => self
-                .maps
-                .get(&format!("{primary_id}/{variant:<02}"))
-                .map(convert_names),
+                .keyed(KeyedTable::Maps, &format!("{primary_id}/{variant:<02}"))
+                .map(|names| convert_names(names, None)),
 
             // font
             Ok(GamePath::FontFile { family, size }) => {
@@ -795,53 +760,210 @@ impl CalculatesAffects for Affects {
             }
         };
 
-        let mut grouped: BTreeMap<ItemKind, BTreeSet<Cow<str>>> = Default::default();
-        let names = names.unwrap_or_default();
-        for (kind, name) in names {
-            grouped.entry(kind).or_default().insert(name);
+        names.unwrap_or_default()
+    }
+
+    fn calculate_material_animation_users_in(
+        &self,
+        id: u8,
+        language: Language,
+    ) -> BTreeSet<AffectedItem<'_>> {
+        self.materials()
+            .animations
+            .get(&id)
+            .map(|users| gear_using(self, users, language))
+            .unwrap_or_default()
+    }
+
+    fn calculate_affected_items_with_attributes_in(
+        &self,
+        path: &str,
+        attributes: AttributeMask,
+        language: Language,
+    ) -> BTreeSet<AffectedItem<'_>> {
+        if attributes.is_empty() {
+            return self.calculate_affected_items_in(path, language);
         }
 
-        grouped
+        // the variants matching the query that show one of the attributes.
+        // variants without recorded attributes show everything
+        let convert_shown = |query: ModelQuery, shown: Option<&BTreeMap<u8, AttributeMask>>| {
+            let names = self
+                .models(query)
+                .into_iter()
+                .filter(|(source, _)| {
+                    shown
+                        .and_then(|shown| shown.get(&source.variant()))
+                        .is_none_or(|mask| mask.intersects(attributes))
+                })
+                .flat_map(|(source, names)| {
+                    names.iter().flat_map(move |(kind, index)| {
+                        affected_items(self, kind, index, Some(source), language)
+                    })
+                })
+                .collect::<BTreeSet<_>>();
+            if names.is_empty() { None } else { Some(names) }
+        };
+
+        let names = match GamePath::parse(path) {
+            Ok(
+                GamePath::Equipment(EquipmentPath::Mdl {
+                    id: primary_id,
+                    slot,
+                    ..
+                })
+                | GamePath::Accessory(AccessoryPath::Mdl {
+                    primary_id, slot, ..
+                }),
+            ) if primary_id != 0 => {
+                let shown = self
+                    .attributes()
+                    .equipment
+                    .get(&slot)
+                    .and_then(|models| models.get(&primary_id));
+                convert_shown(
+                    ModelQuery::model(ModelSourceKind::Equipment, primary_id)
+                        .secondary(slot as u16),
+                    shown,
+                )
+            }
+            Ok(GamePath::Weapon(WeaponPath::Mdl {
+                primary_id,
+                secondary_id,
+            })) => {
+                let shown = self
+                    .attributes()
+                    .weapons
+                    .get(&primary_id)
+                    .and_then(|secondaries| secondaries.get(&secondary_id));
+                convert_shown(
+                    ModelQuery::model(ModelSourceKind::Weapon, primary_id).secondary(secondary_id),
+                    shown,
+                )
+            }
+            Ok(GamePath::Monster(MonsterPath::Mdl {
+                primary_id,
+                secondary_id,
+            })) => {
+                let shown = self
+                    .attributes()
+                    .monsters
+                    .get(&primary_id)
+                    .and_then(|secondaries| secondaries.get(&(secondary_id as u8)));
+                convert_shown(
+                    ModelQuery::model(ModelSourceKind::Monster, primary_id).secondary(secondary_id),
+                    shown,
+                )
+            }
+            _ => return self.calculate_affected_items_in(path, language),
+        };
+
+        names.unwrap_or_default()
     }
 }
 
-fn single_name<'a>(
+/// expands a name index into the items it stands for. gear and weapons that
+/// share a name are told apart by the models they use
+fn affected_items(
+    affects: &impl AffectsData,
     kind: ItemKind,
-    name: impl Into<String>,
-) -> Option<BTreeSet<(ItemKind, Cow<'a, str>)>> {
-    let name = name.into();
+    index: u32,
+    source: Option<ModelSource>,
+    language: Language,
+) -> Vec<AffectedItem<'_>> {
+    let name = match affects.name(index, language) {
+        Some(name) => name,
+        None => return Vec::new(),
+    };
+
+    let affected = AffectedItem {
+        source,
+        ..AffectedItem::new(kind, name)
+    };
+
+    let items = match kind {
+        ItemKind::Gear | ItemKind::Weapon => affects
+            .items(index)
+            .into_iter()
+            .filter(|item| source.is_none_or(|source| item.models.contains(&source)))
+            .map(|item| AffectedItem {
+                item_id: Some(item.item_id),
+                icon_id: Some(item.icon),
+                ..affected.clone()
+            })
+            .collect::<Vec<_>>(),
+        _ => Vec::new(),
+    };
+
+    if items.is_empty() {
+        vec![affected]
+    } else {
+        items
+    }
+}
+
+/// the gear for each (slot, model, variant), as recorded for decals and
+/// material animations
+fn gear_using<'a>(
+    affects: &'a impl AffectsData,
+    users: &BTreeSet<(EquipSlot, u16, u8)>,
+    language: Language,
+) -> BTreeSet<AffectedItem<'a>> {
+    users
+        .iter()
+        .flat_map(|&(slot, model, variant)| {
+            let query = ModelQuery::model(ModelSourceKind::Equipment, model)
+                .secondary(slot as u16)
+                .variant(variant);
+            affects
+                .models(query)
+                .into_iter()
+                .flat_map(move |(source, names)| {
+                    names.iter().flat_map(move |(kind, index)| {
+                        affected_items(affects, kind, index, Some(source), language)
+                    })
+                })
+        })
+        .collect()
+}
+
+fn single_name<'a>(kind: ItemKind, name: impl Into<String>) -> Option<BTreeSet<AffectedItem<'a>>> {
     let mut set = BTreeSet::new();
-    set.insert((kind, Cow::from(name)));
+    set.insert(AffectedItem::new(kind, name.into()));
     Some(set)
 }
 
-fn single_name_ref(kind: ItemKind, name: &str) -> Option<BTreeSet<(ItemKind, Cow<'_, str>)>> {
+fn single_name_ref(kind: ItemKind, name: &str) -> Option<BTreeSet<AffectedItem<'_>>> {
     let mut set = BTreeSet::new();
-    set.insert((kind, Cow::from(name)));
+    set.insert(AffectedItem::new(kind, name));
     Some(set)
 }
 
 fn check_basic_animations<'affects>(
-    affects: &'affects Affects,
+    affects: &'affects impl AffectsData,
     anim_key: &str,
-) -> BTreeSet<(ItemKind, Cow<'affects, str>)> {
+    language: Language,
+) -> BTreeSet<AffectedItem<'affects>> {
     let mut names = anim_key
         .split('/')
         .next_back()
-        .and_then(|key| affects.emotes.get(key))
+        .and_then(|key| affects.keyed(KeyedTable::Emotes, key))
         .map(|names| {
             names
                 .iter()
-                .flat_map(|(kind, name)| affects.names.get(*name as usize).map(|name| (kind, name)))
-                .map(|(kind, name)| (*kind, Cow::from(name)))
+                .flat_map(|(kind, name)| {
+                    affects
+                        .name(name, language)
+                        .map(|name| AffectedItem::new(kind, name))
+                })
                 .collect::<BTreeSet<_>>()
         })
         .unwrap_or_default();
 
-    if let Some(actions) = affects.actions.get(anim_key) {
-        for &(kind, idx) in actions {
-            if let Some(name) = affects.names.get(idx as usize) {
-                names.insert((kind, Cow::from(name)));
+    if let Some(actions) = affects.keyed(KeyedTable::Actions, anim_key) {
+        for (kind, idx) in actions.iter() {
+            if let Some(name) = affects.name(idx, language) {
+                names.insert(AffectedItem::new(kind, name));
             }
         }
     }
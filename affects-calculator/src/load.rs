@@ -0,0 +1,99 @@
+use affects_common::{Affects, BinaryError, Metadata, SchemaError};
+use serde::Deserialize;
+
+#[derive(Debug, thiserror::Error)]
+pub enum LoadError {
+    #[error("could not parse database: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("could not read database: {0}")]
+    Binary(#[from] BinaryError),
+    #[error(transparent)]
+    Schema(#[from] SchemaError),
+}
+
+/// just the metadata, so the schema can be checked before trying to load
+/// tables that may have changed shape
+#[derive(Deserialize)]
+struct MetadataOnly {
+    #[serde(default)]
+    metadata: Metadata,
+}
+
+/// loads a json database, refusing it if its schema version differs from
+/// the one this build expects
+pub fn from_json_slice(data: &[u8]) -> Result<Affects, LoadError> {
+    serde_json::from_slice::<MetadataOnly>(data)?
+        .metadata
+        .check_schema()?;
+
+    Ok(serde_json::from_slice(data)?)
+}
+
+/// loads a database in either format, copying all of it. see
+/// [`affects_common::AffectsView`] to read a binary database in place
+pub fn from_slice(data: &[u8]) -> Result<Affects, LoadError> {
+    if affects_common::AffectsView::is_binary(data) {
+        Ok(Affects::from_binary(data)?)
+    } else {
+        from_json_slice(data)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use affects_common::{BinaryError, SCHEMA_VERSION, SchemaError};
+
+    use super::{LoadError, from_json_slice, from_slice};
+    use crate::test::affects;
+
+    #[test]
+    fn json() {
+        let data = serde_json::to_vec(&affects()).unwrap();
+
+        let loaded = from_json_slice(&data).unwrap();
+        assert_eq!(affects().names, loaded.names);
+        assert_eq!(
+            SCHEMA_VERSION,
+            from_slice(&data).unwrap().metadata.schema_version
+        );
+    }
+
+    #[test]
+    fn binary() {
+        let data = affects().to_binary().unwrap();
+
+        assert_eq!(affects().names, from_slice(&data).unwrap().names);
+    }
+
+    #[test]
+    fn refuses_other_schemas() {
+        let mut affects = affects();
+        affects.metadata.schema_version = SCHEMA_VERSION + 1;
+
+        let json = serde_json::to_vec(&affects).unwrap();
+        assert!(matches!(
+            from_json_slice(&json),
+            Err(LoadError::Schema(SchemaError::Incompatible { found })) if found == SCHEMA_VERSION + 1
+        ));
+
+        let binary = affects.to_binary().unwrap();
+        assert!(matches!(
+            from_slice(&binary),
+            Err(LoadError::Binary(BinaryError::Schema(
+                SchemaError::Incompatible { .. }
+            )))
+        ));
+    }
+
+    #[test]
+    fn refuses_unversioned() {
+        // databases from before versioning have no metadata, and may have
+        // tables in a shape that no longer parses
+        let json = br#"{"names": [], "equipment": "old"}"#;
+
+        assert!(matches!(
+            from_json_slice(json),
+            Err(LoadError::Schema(SchemaError::Unversioned))
+        ));
+    }
+}
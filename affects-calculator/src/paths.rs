@@ -0,0 +1,258 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use affects_common::{AffectsData, EquipSlot, Language, ModelQuery, ModelSource, ModelSourceKind};
+use path_parser::{
+    GamePathBuf,
+    builder::{DemihumanModel, EquipmentModel, MonsterModel, WeaponModel},
+    types::{ModelInfo, ModelKind},
+};
+
+/// the slots demihumans have separate models for
+const DEMIHUMAN_SLOTS: [EquipSlot; 5] = [
+    EquipSlot::Head,
+    EquipSlot::Body,
+    EquipSlot::Hands,
+    EquipSlot::Legs,
+    EquipSlot::Feet,
+];
+
+/// what to list the paths of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathsFor<'a> {
+    /// a row in the Item sheet
+    Item(u32),
+    /// every model shown under an english name, such as a mount or a battle
+    /// npc. compared case-insensitively
+    Name(&'a str),
+}
+
+pub trait FindsPaths {
+    fn reverse_index(&self) -> ReverseIndex<'_>;
+
+    /// the paths making up `target`. gear is listed for every playable race,
+    /// even though some races fall back to another race's model. materials
+    /// and textures are listed by their first file, as the rest of the set
+    /// follows the same naming
+    fn paths_for(&self, target: PathsFor<'_>) -> BTreeSet<String> {
+        self.reverse_index().paths_for(target)
+    }
+}
+
+impl<T: AffectsData> FindsPaths for T {
+    fn reverse_index(&self) -> ReverseIndex<'_> {
+        ReverseIndex::new(self)
+    }
+}
+
+/// the model tables turned around, so names can be mapped back to the models
+/// using them. building this walks every table, so keep it around when
+/// looking up more than one thing
+pub struct ReverseIndex<'a> {
+    affects: &'a dyn AffectsData,
+    /// name index => models shown with that name
+    sources: BTreeMap<u32, BTreeSet<ModelSource>>,
+    /// item id => models the item is displayed with, across every name it
+    /// has, such as a weapon and its offhand
+    items: BTreeMap<u32, BTreeSet<ModelSource>>,
+}
+
+impl<'a> ReverseIndex<'a> {
+    pub fn new(affects: &'a dyn AffectsData) -> Self {
+        let mut sources: BTreeMap<u32, BTreeSet<ModelSource>> = BTreeMap::new();
+        for kind in ModelSourceKind::ALL {
+            for (source, names) in affects.models(ModelQuery::all(kind)) {
+                for (_, idx) in names.iter() {
+                    sources.entry(idx).or_default().insert(source);
+                }
+            }
+        }
+
+        let mut items: BTreeMap<u32, BTreeSet<ModelSource>> = BTreeMap::new();
+        for item in affects.all_items() {
+            items.entry(item.item_id).or_default().extend(item.models);
+        }
+
+        Self {
+            affects,
+            sources,
+            items,
+        }
+    }
+
+    /// the models shown for `target`
+    pub fn sources_for(&self, target: PathsFor<'_>) -> BTreeSet<ModelSource> {
+        match target {
+            // items sharing a name only keep their own models
+            PathsFor::Item(item_id) => match self.items.get(&item_id) {
+                Some(models) => models.clone(),
+                None => self
+                    .affects
+                    .item_name(item_id)
+                    .and_then(|name_idx| self.sources.get(&name_idx))
+                    .cloned()
+                    .unwrap_or_default(),
+            },
+            PathsFor::Name(name) => (0..self.affects.name_count() as u32)
+                .filter(|&idx| {
+                    self.affects
+                        .name(idx, Language::English)
+                        .is_some_and(|other| other.eq_ignore_ascii_case(name))
+                })
+                .flat_map(|idx| self.sources.get(&idx))
+                .flatten()
+                .copied()
+                .collect(),
+        }
+    }
+
+    /// see [`FindsPaths::paths_for`]
+    pub fn paths_for(&self, target: PathsFor<'_>) -> BTreeSet<String> {
+        self.sources_for(target)
+            .into_iter()
+            .flat_map(|source| self.source_paths(source))
+            .map(|path| path.to_string())
+            .collect()
+    }
+
+    /// the paths making up a single model and variant
+    pub fn source_paths(&self, source: ModelSource) -> Vec<GamePathBuf> {
+        let effects = self
+            .affects
+            .model_effects(source.into())
+            .into_iter()
+            .map(|(_, effect)| effect as u16);
+        // materials and textures are named by material, not imc variant
+        let material = self.affects.materials().material(source);
+
+        match source {
+            ModelSource::Equipment { slot, model, .. } => {
+                let builder = EquipmentModel::new(slot, model);
+                let mut paths = vec![builder.imc()];
+                for info in playable_model_infos() {
+                    paths.push(builder.mdl(info));
+                    paths.push(builder.mtrl(info, material as u16));
+                    paths.push(builder.tex(info, material));
+                }
+
+                paths.extend(effects.flat_map(|effect| builder.avfx(effect)));
+
+                paths
+            }
+            ModelSource::Weapon { model, weapon, .. } => {
+                let builder = WeaponModel::new(model, weapon);
+                let mut paths = vec![
+                    builder.imc(),
+                    builder.mdl(),
+                    builder.mtrl(material as u16),
+                    builder.tex(material),
+                ];
+
+                paths.extend(effects.map(|effect| builder.avfx(effect)));
+
+                paths
+            }
+            ModelSource::Monster { model, base, .. } => {
+                let builder = MonsterModel::new(model, base as u16);
+                let mut paths = vec![
+                    builder.imc(),
+                    builder.mdl(),
+                    builder.skeleton(),
+                    builder.mtrl(material as u16),
+                    builder.tex(material),
+                ];
+
+                paths.extend(effects.map(|effect| builder.avfx(effect)));
+
+                paths
+            }
+            ModelSource::Demihuman { model, base, .. } => {
+                let builder = DemihumanModel::new(model, base as u16);
+                let mut paths = vec![builder.imc(), builder.skeleton()];
+                for slot in DEMIHUMAN_SLOTS {
+                    paths.push(builder.mdl(slot));
+                    paths.push(builder.mtrl(slot, material as u16));
+                    paths.push(builder.tex(slot, material));
+                }
+
+                paths.extend(effects.map(|effect| builder.avfx(effect)));
+
+                paths
+            }
+        }
+    }
+}
+
+/// the adult model of every playable race and gender
+fn playable_model_infos() -> impl Iterator<Item = ModelInfo> {
+    (1..=18)
+        .filter_map(|id| ModelInfo::try_from(id * 100 + 1).ok())
+        .filter(|info| info.kind == ModelKind::Adult)
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use super::{FindsPaths, PathsFor, ReverseIndex};
+    use crate::test::{HELM_ITEM, HELM_SOURCE, SWORD, SWORD_ITEM, SWORD_SOURCE, affects};
+
+    #[test]
+    fn gear_item() {
+        let affects = affects();
+        let index = ReverseIndex::new(&affects);
+
+        assert_eq!(
+            BTreeSet::from([HELM_SOURCE]),
+            index.sources_for(PathsFor::Item(HELM_ITEM))
+        );
+        let paths = affects.paths_for(PathsFor::Item(HELM_ITEM));
+        assert!(paths.contains("chara/equipment/e6016/model/c0101e6016_met.mdl"));
+        assert!(paths.contains("chara/equipment/e6016/e6016.imc"));
+    }
+
+    #[test]
+    fn weapon_item() {
+        let affects = affects();
+        let index = ReverseIndex::new(&affects);
+
+        assert_eq!(
+            BTreeSet::from([SWORD_SOURCE]),
+            index.sources_for(PathsFor::Item(SWORD_ITEM))
+        );
+        assert_eq!(
+            BTreeSet::from(
+                [
+                    "chara/weapon/w0201/obj/body/b0001/b0001.imc",
+                    "chara/weapon/w0201/obj/body/b0001/model/w0201b0001.mdl",
+                    "chara/weapon/w0201/obj/body/b0001/material/v0001/mt_w0201b0001_a.mtrl",
+                    "chara/weapon/w0201/obj/body/b0001/texture/v01_w0201b0001_norm.tex",
+                ]
+                .map(str::to_string)
+            ),
+            affects.paths_for(PathsFor::Item(SWORD_ITEM))
+        );
+    }
+
+    #[test]
+    fn item_without_info() {
+        let mut affects = affects();
+        affects.items.remove(&SWORD);
+        affects.item_ids.insert(SWORD_ITEM, SWORD);
+
+        assert_eq!(
+            BTreeSet::from([SWORD_SOURCE]),
+            ReverseIndex::new(&affects).sources_for(PathsFor::Item(SWORD_ITEM))
+        );
+        assert!(affects.paths_for(PathsFor::Item(1)).is_empty());
+    }
+
+    #[test]
+    fn name() {
+        let affects = affects();
+
+        assert_eq!(
+            BTreeSet::from([SWORD_SOURCE]),
+            ReverseIndex::new(&affects).sources_for(PathsFor::Name("bronze sword"))
+        );
+    }
+}
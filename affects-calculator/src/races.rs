@@ -0,0 +1,128 @@
+use path_parser::{
+    GamePath,
+    builder::{CharacterModel, EquipmentModel},
+    path::chara::{AccessoryPath, BodyType, BodyTypeSlot, CharacterPath, EquipmentPath},
+    types::ModelInfo,
+};
+
+/// a model file that exists for each race, and which the game looks for
+/// along the racial fallback tree
+enum RacialModel {
+    Equipment(EquipmentModel),
+    Body {
+        body_type: BodyType,
+        primary_id: u16,
+        slot: Option<BodyTypeSlot>,
+    },
+}
+
+impl RacialModel {
+    fn path(&self, info: ModelInfo) -> String {
+        match *self {
+            Self::Equipment(ref model) => model.mdl(info).to_string(),
+            Self::Body {
+                body_type,
+                primary_id,
+                slot,
+            } => CharacterModel::new(info, body_type, primary_id)
+                .mdl(slot)
+                .to_string(),
+        }
+    }
+}
+
+/// every model that loads the equipment, accessory or body model at `path`:
+/// the model it is for, followed by every model that falls back to it
+/// without a file of its own on the way. `exists` says whether the game has
+/// a file, such as by checking its index files. other paths load for no
+/// model in particular and give nothing
+pub fn affected_models(path: &str, exists: impl Fn(&str) -> bool) -> Vec<ModelInfo> {
+    let (info, model) = match GamePath::parse(path) {
+        Ok(GamePath::Equipment(EquipmentPath::Mdl { id, info, slot })) => {
+            (info, RacialModel::Equipment(EquipmentModel::new(slot, id)))
+        }
+        Ok(GamePath::Accessory(AccessoryPath::Mdl {
+            primary_id,
+            info,
+            slot,
+        })) => (
+            info,
+            RacialModel::Equipment(EquipmentModel::new(slot, primary_id)),
+        ),
+        Ok(GamePath::Character(CharacterPath::Mdl {
+            primary_id,
+            model_info,
+            body_type,
+            slot,
+        })) => (
+            model_info,
+            RacialModel::Body {
+                body_type,
+                primary_id,
+                slot,
+            },
+        ),
+        _ => return Vec::new(),
+    };
+
+    let dependents = info.dependents().filter(|dependent| {
+        dependent
+            .fallback_chain()
+            .take_while(|fallback| *fallback != info)
+            .all(|fallback| !exists(&model.path(fallback)))
+    });
+
+    std::iter::once(info).chain(dependents).collect()
+}
+
+#[cfg(test)]
+mod test {
+    use affects_common::EquipSlot;
+    use path_parser::{builder::EquipmentModel, types::ModelInfo};
+
+    use super::affected_models;
+
+    fn info(id: u16) -> ModelInfo {
+        ModelInfo::try_from(id).unwrap()
+    }
+
+    fn helm(id: u16) -> String {
+        EquipmentModel::new(EquipSlot::Head, 6016)
+            .mdl(info(id))
+            .to_string()
+    }
+
+    #[test]
+    fn stops_at_models_with_their_own_file() {
+        // midlander females have a helm of their own, so none of the races
+        // falling back through them load the male one
+        let models = affected_models(&helm(101), |path| path == helm(101) || path == helm(201));
+
+        assert_eq!(info(101), models[0]);
+        for id in [104, 301, 501, 1301, 1501] {
+            assert!(models.contains(&info(id)), "{id}");
+        }
+
+        for id in [201, 401, 601, 1001, 1401] {
+            assert!(!models.contains(&info(id)), "{id}");
+        }
+    }
+
+    #[test]
+    fn every_race_has_a_file() {
+        assert_eq!(vec![info(901)], affected_models(&helm(901), |_| true));
+    }
+
+    #[test]
+    fn body_models() {
+        let path = "chara/human/c0901/obj/body/b0001/model/c0901b0001_top.mdl";
+        let models = affected_models(path, |path| path.contains("c0901"));
+
+        assert_eq!(vec![info(901), info(904), info(1501), info(1504)], models);
+    }
+
+    #[test]
+    fn other_paths() {
+        assert!(affected_models("chara/equipment/e6016/e6016.imc", |_| false).is_empty());
+    }
+}
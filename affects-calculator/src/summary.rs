@@ -0,0 +1,125 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use affects_common::ItemKind;
+
+use crate::AffectedItem;
+
+/// everything a set of paths affects, such as all the files in a mod
+#[derive(Debug, Default, Clone)]
+pub struct AffectsSummary<'a> {
+    /// affected item => the paths affecting it
+    pub items: BTreeMap<AffectedItem<'a>, BTreeSet<String>>,
+    /// paths that did not affect anything known
+    pub unmatched: BTreeSet<String>,
+}
+
+impl<'a> AffectsSummary<'a> {
+    pub(crate) fn add(&mut self, path: &str, items: BTreeSet<AffectedItem<'a>>) {
+        if items.is_empty() {
+            self.unmatched.insert(path.to_string());
+            return;
+        }
+
+        for item in items {
+            self.items.entry(item).or_default().insert(path.to_string());
+        }
+    }
+
+    /// the number of distinct names affected of each kind. items sharing a
+    /// name, like the same gear piece matched through several models, are
+    /// only counted once
+    pub fn counts(&self) -> BTreeMap<ItemKind, usize> {
+        let mut names: BTreeMap<ItemKind, BTreeSet<&str>> = BTreeMap::new();
+        for item in self.items.keys() {
+            names.entry(item.kind).or_default().insert(&item.name);
+        }
+
+        names
+            .into_iter()
+            .map(|(kind, names)| (kind, names.len()))
+            .collect()
+    }
+
+    /// the affected items grouped by kind
+    pub fn by_kind(&self) -> BTreeMap<ItemKind, Vec<(&AffectedItem<'a>, &BTreeSet<String>)>> {
+        let mut grouped: BTreeMap<ItemKind, Vec<_>> = BTreeMap::new();
+        for (item, paths) in &self.items {
+            grouped.entry(item.kind).or_default().push((item, paths));
+        }
+
+        grouped
+    }
+}
+
+/// trims the path and uses forward slashes, as mods are not always consistent
+/// about them. case is kept, as some path tokens are matched exactly, like
+/// `attachOffset`
+pub(crate) fn normalise_path(path: &str) -> String {
+    path.trim().replace('\\', "/")
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use affects_common::ItemKind;
+    use path_parser::GamePath;
+
+    use super::normalise_path;
+    use crate::{CalculatesAffects, test::affects};
+
+    const HELM_MDL: &str = "chara/equipment/e6016/model/c0101e6016_met.mdl";
+
+    #[test]
+    fn normalise() {
+        assert_eq!(
+            "chara/xls/attachOffset/c0501.atch",
+            normalise_path(" chara\\xls\\attachOffset\\c0501.atch\n")
+        );
+        assert!(GamePath::parse(&normalise_path("chara\\xls\\attachOffset\\c0501.atch")).is_ok());
+    }
+
+    #[test]
+    fn many() {
+        let affects = affects();
+        let windows = HELM_MDL.replace('/', "\\");
+        let summary = affects.calculate_affected_many([
+            HELM_MDL,
+            &windows,
+            "chara/equipment/e6016/texture/v01_c0101e6016_met_n.tex",
+            "Mods/Readme.TXT",
+        ]);
+
+        assert_eq!(1, summary.items.len());
+        let (item, paths) = summary.items.iter().next().unwrap();
+        assert_eq!("Bronze Helm", item.name);
+        assert_eq!(
+            BTreeSet::from(
+                [
+                    HELM_MDL,
+                    &windows,
+                    "chara/equipment/e6016/texture/v01_c0101e6016_met_n.tex"
+                ]
+                .map(str::to_string)
+            ),
+            *paths
+        );
+        assert_eq!(
+            BTreeSet::from(["Mods/Readme.TXT".to_string()]),
+            summary.unmatched
+        );
+
+        assert_eq!(BTreeMap::from([(ItemKind::Gear, 1)]), summary.counts());
+        assert_eq!(1, summary.by_kind()[&ItemKind::Gear].len());
+    }
+
+    #[test]
+    fn keeps_case() {
+        let affects = affects();
+        let upper = HELM_MDL.to_uppercase();
+        let summary = affects.calculate_affected_many([upper.as_str()]);
+
+        assert!(summary.items.is_empty());
+        assert_eq!(BTreeSet::from([upper]), summary.unmatched);
+    }
+}
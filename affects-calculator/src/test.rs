@@ -0,0 +1,219 @@
+//! a small hand-made database for the calculator tests, and the tests of
+//! [`CalculatesAffects`] itself
+
+use affects_common::{
+    Affects, AttributeMask, EquipSlot, ItemInfo, ItemKind, Metadata, ModelSource, SCHEMA_VERSION,
+};
+use path_parser::builder::{EquipmentModel, WeaponModel};
+
+use crate::{CalculatesAffects, ModelInfo};
+
+pub const HELM: u32 = 0;
+pub const SWORD: u32 = 1;
+
+pub const HELM_ITEM: u32 = 2048;
+pub const SWORD_ITEM: u32 = 1601;
+
+pub const HELM_SOURCE: ModelSource = ModelSource::Equipment {
+    slot: EquipSlot::Head,
+    model: 6016,
+    variant: 1,
+};
+pub const SWORD_SOURCE: ModelSource = ModelSource::Weapon {
+    model: 201,
+    weapon: 1,
+    variant: 1,
+};
+
+/// a helm on e6016 variant 1 and a sword on w0201 b0001 variant 1
+pub fn affects() -> Affects {
+    let mut affects = Affects {
+        metadata: Metadata {
+            schema_version: SCHEMA_VERSION,
+            ..Default::default()
+        },
+        names: vec!["Bronze Helm".to_string(), "Bronze Sword".to_string()],
+        ..Default::default()
+    };
+
+    affects.name_kinds.insert(HELM, [ItemKind::Gear].into());
+    affects.name_kinds.insert(SWORD, [ItemKind::Weapon].into());
+    affects
+        .equipment
+        .entry(EquipSlot::Head)
+        .or_default()
+        .entry(6016)
+        .or_default()
+        .insert(1, [(ItemKind::Gear, HELM)].into());
+    affects
+        .weapons
+        .entry(201)
+        .or_default()
+        .entry(1)
+        .or_default()
+        .insert(1, [(ItemKind::Weapon, SWORD)].into());
+
+    affects.item_ids.insert(HELM_ITEM, HELM);
+    affects.item_ids.insert(SWORD_ITEM, SWORD);
+    affects.items.insert(
+        HELM,
+        vec![ItemInfo {
+            item_id: HELM_ITEM,
+            icon: 40001,
+            models: vec![HELM_SOURCE],
+        }],
+    );
+    affects.items.insert(
+        SWORD,
+        vec![ItemInfo {
+            item_id: SWORD_ITEM,
+            icon: 30001,
+            models: vec![SWORD_SOURCE],
+        }],
+    );
+
+    affects
+}
+
+#[test]
+fn weapon_vfx() {
+    let mut affects = affects();
+    affects
+        .vfx
+        .weapons
+        .entry(201)
+        .or_default()
+        .entry(1)
+        .or_default()
+        .insert(3, [1].into());
+
+    let path = WeaponModel::new(201, 1).avfx(3).to_string();
+    let items = affects.calculate_affected_items(&path);
+    assert_eq!(1, items.len());
+
+    let item = items.first().unwrap();
+    assert_eq!(ItemKind::Weapon, item.kind);
+    assert_eq!("Bronze Sword", item.name);
+    assert_eq!(Some(SWORD_ITEM), item.item_id);
+    assert_eq!(Some(SWORD_SOURCE), item.source);
+}
+
+#[test]
+fn attributes() {
+    let mut affects = affects();
+    affects.names.push("Iron Helm".into());
+    affects.names.push("Steel Helm".into());
+    let variants = affects
+        .equipment
+        .get_mut(&EquipSlot::Head)
+        .unwrap()
+        .get_mut(&6016)
+        .unwrap();
+    variants.insert(2, [(ItemKind::Gear, 2)].into());
+    variants.insert(3, [(ItemKind::Gear, 3)].into());
+
+    let letter = |letter| AttributeMask::from_letter(letter).unwrap();
+    affects.attributes.equipment.insert(
+        EquipSlot::Head,
+        [(6016, [(1, letter('a')), (2, letter('b'))].into())].into(),
+    );
+    affects
+        .attributes
+        .weapons
+        .insert(201, [(1, [(1, letter('c'))].into())].into());
+
+    let names = |path: &str, attributes| {
+        affects
+            .calculate_affected_items_with_attributes(path, attributes)
+            .into_iter()
+            .map(|item| item.name.into_owned())
+            .collect::<Vec<_>>()
+    };
+
+    // variants without recorded attributes show everything
+    let helm = EquipmentModel::new(EquipSlot::Head, 6016);
+    let mdl = helm.mdl(ModelInfo::try_from(101).unwrap()).to_string();
+    assert_eq!(vec!["Bronze Helm", "Steel Helm"], names(&mdl, letter('a')));
+    assert_eq!(
+        vec!["Bronze Helm", "Iron Helm", "Steel Helm"],
+        names(&mdl, letter('a') | letter('b'))
+    );
+    assert_eq!(
+        vec!["Bronze Helm", "Iron Helm", "Steel Helm"],
+        names(&mdl, AttributeMask::NONE)
+    );
+
+    // only models are filtered
+    assert_eq!(3, names(&helm.imc().to_string(), letter('a')).len());
+
+    let sword = WeaponModel::new(201, 1).mdl().to_string();
+    assert!(names(&sword, letter('a')).is_empty());
+    assert_eq!(vec!["Bronze Sword"], names(&sword, letter('c')));
+}
+
+#[test]
+fn materials() {
+    let mut affects = affects();
+    affects.names.push("Iron Helm".into());
+    affects.names.push("Steel Helm".into());
+    let variants = affects
+        .equipment
+        .get_mut(&EquipSlot::Head)
+        .unwrap()
+        .get_mut(&6016)
+        .unwrap();
+    variants.insert(2, [(ItemKind::Gear, 2)].into());
+    variants.insert(3, [(ItemKind::Gear, 3)].into());
+
+    // the first two imc variants share a material, and the third has no imc
+    // entry, so uses its own
+    affects
+        .materials
+        .equipment
+        .insert(EquipSlot::Head, [(6016, [(1, 1), (2, 1)].into())].into());
+
+    let names = |material| {
+        let info = ModelInfo::try_from(101).unwrap();
+        let path = EquipmentModel::new(EquipSlot::Head, 6016)
+            .mtrl(info, material)
+            .to_string();
+        affects
+            .calculate_affected_items(&path)
+            .into_iter()
+            .map(|item| item.name.into_owned())
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(vec!["Bronze Helm", "Iron Helm"], names(1));
+    assert!(names(2).is_empty());
+    assert_eq!(vec!["Steel Helm"], names(3));
+}
+
+#[test]
+fn mount_effects_and_textures() {
+    let mut affects = affects();
+    affects.names.push("Company Chocobo".into());
+    affects.name_kinds.insert(2, [ItemKind::Mount].into());
+    for stem in ["chocobo_run", "chocobo_dust"] {
+        affects
+            .vfx
+            .effects
+            .insert(stem.into(), [(ItemKind::Mount, 2)].into());
+    }
+
+    let names = |path: &str| {
+        affects
+            .calculate_affected_items(path)
+            .into_iter()
+            .map(|item| (item.kind, item.name.into_owned()))
+            .collect::<Vec<_>>()
+    };
+
+    let chocobo = vec![(ItemKind::Mount, "Company Chocobo".to_string())];
+    assert_eq!(chocobo, names("vfx/mount/eff/chocobo_run.avfx"));
+    assert_eq!(chocobo, names("vfx/common/texture/chocobo_dust.atex"));
+    assert_eq!(
+        vec![(ItemKind::Miscellaneous, "VFX".to_string())],
+        names("vfx/mount/texture/magitek_dust.atex"),
+    );
+}
@@ -0,0 +1,69 @@
+use serde::{Deserialize, Serialize};
+
+/// a set of model attributes, atr_a to atr_j, one bit each. attributes are
+/// mesh parts that imc variants show or hide, like the hood of a robe
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct AttributeMask(pub u16);
+
+impl AttributeMask {
+    pub const ALL: Self = Self(0x3FF);
+    pub const NONE: Self = Self(0);
+
+    /// the attribute with the given letter, from `a` to `j`
+    pub fn from_letter(letter: char) -> Option<Self> {
+        match letter {
+            'a'..='j' => Some(Self(1 << (letter as u8 - b'a'))),
+            _ => None,
+        }
+    }
+
+    /// the attribute a mesh part is named for, such as `atr_b` or the
+    /// slot-specific `atr_tv_b`
+    pub fn from_name(name: &str) -> Option<Self> {
+        let rest = name.strip_prefix("atr_")?;
+        let letter = rest.rsplit('_').next()?;
+        let mut chars = letter.chars();
+        match (chars.next(), chars.next()) {
+            (Some(letter), None) => Self::from_letter(letter),
+            _ => None,
+        }
+    }
+
+    pub fn intersects(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// the letters of the attributes in the set, from `a` to `j`
+    pub fn letters(self) -> impl Iterator<Item = char> {
+        ('a'..='j').filter(move |&letter| {
+            Self::from_letter(letter).is_some_and(|attribute| self.intersects(attribute))
+        })
+    }
+}
+
+impl std::ops::BitOr for AttributeMask {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl std::ops::BitOrAssign for AttributeMask {
+    fn bitor_assign(&mut self, rhs: Self) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl FromIterator<AttributeMask> for AttributeMask {
+    fn from_iter<T: IntoIterator<Item = AttributeMask>>(iter: T) -> Self {
+        iter.into_iter().fold(Self::NONE, |all, mask| all | mask)
+    }
+}
@@ -0,0 +1,1374 @@
+//! the binary database format
+//!
+//! a file starts with a header and a table of sections:
+//!
+//! ```text
+//! magic: [u8; 4] = "AFFX"
+//! version: u16
+//! reserved: u16
+//! section count: u32
+//! sections: [(id: u32, offset: u32, length: u32); section count]
+//! ```
+//!
+//! integers are little endian unless they are part of a record key, and
+//! offsets are from the start of the file. names are stored as string pools:
+//! a `u32` count, `count + 1` `u32` offsets into the string data and then the
+//! string data itself.
+//!
+//! the model, emote, action, map, vfx and item tables are read in place.
+//! they are arrays of fixed-width records sorted by a big endian key, so the
+//! byte order of the keys is their numeric order and a lookup is a binary
+//! search. the names used by a record are a `(offset: u32, count: u32)` range
+//! of the name set section, which is an array of `(kind: u8, idx: u32)`
+//! entries. the tables keyed by a string are a `u32` length, a string pool of
+//! the sorted keys and then a name range for each key. every other table is
+//! encoded with postcard and decoded when the file is opened.
+//!
+//! every table is checked when the file is opened, so a corrupt table is
+//! reported up front rather than on first use. nothing in the format needs
+//! to be aligned, so it can be read straight out of a memory map
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use serde::{Serialize, de::DeserializeOwned};
+
+use crate::{
+    Affects, AffectsData, AttributeMaps, CustomisationMaps, ItemInfo, ItemKind, KeyedTable,
+    Language, MaterialMaps, Metadata, ModelQuery, ModelSource, ModelSourceKind, Names, SchemaError,
+    SoundMaps,
+};
+
+const MAGIC: [u8; 4] = *b"AFFX";
+const HEADER_LEN: usize = 12;
+const SECTION_ENTRY_LEN: usize = 12;
+
+/// the version written by [`Affects::to_binary`]. files with any other
+/// version are rejected
+pub const BINARY_VERSION: u16 = 2;
+
+/// `[kind][model][secondary][variant]`, see [`ModelSource::new`]
+const MODEL_KEY_LEN: usize = 6;
+/// a model key and a name range
+const MODEL_LEN: usize = MODEL_KEY_LEN + 8;
+/// a model key and an effect id
+const MODEL_EFFECT_LEN: usize = MODEL_KEY_LEN + 1;
+/// `[kind][idx]`
+const NAME_ENTRY_LEN: usize = 5;
+/// `[offset][count]`
+const NAME_RANGE_LEN: usize = 8;
+/// `[item id][name idx]`
+const ITEM_ID_LEN: usize = 8;
+/// `[name idx][item id][icon][model offset][model count]`, with the models in
+/// the item models section
+const ITEM_LEN: usize = 20;
+
+#[derive(Debug, thiserror::Error)]
+pub enum BinaryError {
+    #[error("not an affects database")]
+    BadMagic,
+    #[error("unsupported database version {0} (expected {BINARY_VERSION})")]
+    UnsupportedVersion(u16),
+    #[error("database is truncated")]
+    Truncated,
+    #[error(transparent)]
+    Schema(#[from] SchemaError),
+    #[error("database has no {0} section")]
+    MissingSection(&'static str),
+    #[error("{0} section has an invalid string pool")]
+    InvalidStrings(&'static str),
+    #[error("{0} section is invalid")]
+    InvalidTable(&'static str),
+    #[error("could not encode {section} section: {source}")]
+    Encode {
+        section: &'static str,
+        source: postcard::Error,
+    },
+    #[error("could not decode {section} section: {source}")]
+    Decode {
+        section: &'static str,
+        source: postcard::Error,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Metadata,
+    Names,
+    LocalisedNames(Language),
+    NameKinds,
+    NameSets,
+    Models,
+    ModelEffects,
+    Emotes,
+    Actions,
+    Maps,
+    Effects,
+    Sounds,
+    ItemIds,
+    Items,
+    ItemModels,
+    Attributes,
+    Materials,
+    Customisations,
+}
+
+impl Section {
+    fn id(self) -> u32 {
+        match self {
+            Self::Names => 0,
+            Self::NameKinds => 1,
+            Self::NameSets => 2,
+            Self::Models => 3,
+            Self::ModelEffects => 4,
+            Self::Emotes => 5,
+            Self::Actions => 6,
+            Self::Maps => 7,
+            Self::Effects => 8,
+            Self::Sounds => 9,
+            Self::ItemIds => 10,
+            Self::Items => 11,
+            Self::ItemModels => 12,
+            Self::Metadata => 13,
+            Self::Attributes => 14,
+            Self::Materials => 15,
+            Self::Customisations => 16,
+            Self::LocalisedNames(language) => 0x100 | language as u32,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Metadata => "metadata",
+            Self::Names => "names",
+            Self::LocalisedNames(_) => "localised names",
+            Self::NameKinds => "name kinds",
+            Self::NameSets => "name sets",
+            Self::Models => "models",
+            Self::ModelEffects => "model effects",
+            Self::Emotes => "emotes",
+            Self::Actions => "actions",
+            Self::Maps => "maps",
+            Self::Effects => "effects",
+            Self::Sounds => "sounds",
+            Self::ItemIds => "item ids",
+            Self::Items => "items",
+            Self::ItemModels => "item models",
+            Self::Attributes => "attributes",
+            Self::Materials => "materials",
+            Self::Customisations => "customisations",
+        }
+    }
+
+    fn keyed(table: KeyedTable) -> Self {
+        match table {
+            KeyedTable::Emotes => Self::Emotes,
+            KeyedTable::Actions => Self::Actions,
+            KeyedTable::Maps => Self::Maps,
+            KeyedTable::Effects => Self::Effects,
+        }
+    }
+}
+
+const KEYED_TABLES: [KeyedTable; 4] = [
+    KeyedTable::Emotes,
+    KeyedTable::Actions,
+    KeyedTable::Maps,
+    KeyedTable::Effects,
+];
+
+// writing
+
+impl Affects {
+    /// encodes the database in the binary format
+    pub fn to_binary(&self) -> Result<Vec<u8>, BinaryError> {
+        let mut sections = vec![
+            encode(Section::Metadata, &self.metadata)?,
+            (Section::Names, encode_strings(&self.names)),
+        ];
+        for (&language, names) in &self.localised_names {
+            sections.push((Section::LocalisedNames(language), encode_strings(names)));
+        }
+
+        sections.push(encode(Section::NameKinds, &self.name_kinds)?);
+
+        let mut name_sets = NameSetWriter::default();
+
+        let mut models = Vec::new();
+        let mut model_effects = Vec::new();
+        for kind in ModelSourceKind::ALL {
+            for (source, names) in self.models(ModelQuery::all(kind)) {
+                models.extend_from_slice(&model_key(source));
+                models.extend_from_slice(&name_sets.push(names));
+            }
+
+            let mut effects = self
+                .model_effects(ModelQuery::all(kind))
+                .into_iter()
+                .map(|(source, effect)| (model_key(source), effect))
+                .collect::<Vec<_>>();
+            effects.sort();
+            effects.dedup();
+            for (key, effect) in effects {
+                model_effects.extend_from_slice(&key);
+                model_effects.push(effect);
+            }
+        }
+        sections.push((Section::Models, models));
+        sections.push((Section::ModelEffects, model_effects));
+
+        for table in KEYED_TABLES {
+            let entries = self.keyed_range(table, "");
+            let keys = entries
+                .iter()
+                .map(|(key, _)| key.to_string())
+                .collect::<Vec<_>>();
+            let keys = encode_strings(&keys);
+
+            let mut out = Vec::new();
+            out.extend_from_slice(&(keys.len() as u32).to_le_bytes());
+            out.extend_from_slice(&keys);
+            for (_, names) in entries {
+                out.extend_from_slice(&name_sets.push(names));
+            }
+            sections.push((Section::keyed(table), out));
+        }
+
+        sections.push(encode(Section::Sounds, &self.sounds)?);
+
+        let mut item_ids = Vec::new();
+        for (&item_id, &name_idx) in &self.item_ids {
+            item_ids.extend_from_slice(&item_id.to_be_bytes());
+            item_ids.extend_from_slice(&name_idx.to_le_bytes());
+        }
+        sections.push((Section::ItemIds, item_ids));
+
+        let mut items = Vec::new();
+        let mut item_models = Vec::new();
+        for (&name_idx, infos) in &self.items {
+            for info in infos {
+                let offset = item_models.len() / MODEL_KEY_LEN;
+                for &source in &info.models {
+                    item_models.extend_from_slice(&model_key(source));
+                }
+
+                items.extend_from_slice(&name_idx.to_be_bytes());
+                items.extend_from_slice(&info.item_id.to_le_bytes());
+                items.extend_from_slice(&info.icon.to_le_bytes());
+                items.extend_from_slice(&(offset as u32).to_le_bytes());
+                items.extend_from_slice(&(info.models.len() as u32).to_le_bytes());
+            }
+        }
+        sections.push((Section::Items, items));
+        sections.push((Section::ItemModels, item_models));
+
+        sections.push(encode(Section::Attributes, &self.attributes)?);
+        sections.push(encode(Section::Materials, &self.materials)?);
+        sections.push(encode(Section::Customisations, &self.customisations)?);
+        sections.push((Section::NameSets, name_sets.0));
+
+        let mut out = Vec::new();
+        out.extend_from_slice(&MAGIC);
+        out.extend_from_slice(&BINARY_VERSION.to_le_bytes());
+        out.extend_from_slice(&0u16.to_le_bytes());
+        out.extend_from_slice(&(sections.len() as u32).to_le_bytes());
+
+        let mut offset = HEADER_LEN + sections.len() * SECTION_ENTRY_LEN;
+        for (section, data) in &sections {
+            out.extend_from_slice(&section.id().to_le_bytes());
+            out.extend_from_slice(&(offset as u32).to_le_bytes());
+            out.extend_from_slice(&(data.len() as u32).to_le_bytes());
+            offset += data.len();
+        }
+
+        for (_, data) in sections {
+            out.extend_from_slice(&data);
+        }
+
+        Ok(out)
+    }
+
+    /// decodes a whole database from the binary format. use [`AffectsView`]
+    /// to read it in place
+    pub fn from_binary(data: &[u8]) -> Result<Self, BinaryError> {
+        Ok(AffectsView::new(data)?.into_affects())
+    }
+}
+
+fn encode(section: Section, value: &impl Serialize) -> Result<(Section, Vec<u8>), BinaryError> {
+    postcard::to_stdvec(value)
+        .map(|data| (section, data))
+        .map_err(|source| BinaryError::Encode {
+            section: section.name(),
+            source,
+        })
+}
+
+fn encode_strings(strings: &[String]) -> Vec<u8> {
+    let mut out = Vec::new();
+    out.extend_from_slice(&(strings.len() as u32).to_le_bytes());
+
+    let mut offset = 0u32;
+    out.extend_from_slice(&offset.to_le_bytes());
+    for string in strings {
+        offset += string.len() as u32;
+        out.extend_from_slice(&offset.to_le_bytes());
+    }
+
+    for string in strings {
+        out.extend_from_slice(string.as_bytes());
+    }
+
+    out
+}
+
+fn model_key(source: ModelSource) -> [u8; MODEL_KEY_LEN] {
+    let [model_hi, model_lo] = source.model().to_be_bytes();
+    let [secondary_hi, secondary_lo] = source.secondary().to_be_bytes();
+    [
+        source.kind() as u8,
+        model_hi,
+        model_lo,
+        secondary_hi,
+        secondary_lo,
+        source.variant(),
+    ]
+}
+
+/// the name set section, as it is written
+#[derive(Default)]
+struct NameSetWriter(Vec<u8>);
+
+impl NameSetWriter {
+    /// appends `names` and returns their range
+    fn push(&mut self, names: Names) -> [u8; NAME_RANGE_LEN] {
+        let offset = self.0.len() / NAME_ENTRY_LEN;
+        let mut count = 0u32;
+        for (kind, idx) in names.iter() {
+            self.0.push(kind as u8);
+            self.0.extend_from_slice(&idx.to_le_bytes());
+            count += 1;
+        }
+
+        let mut range = [0; NAME_RANGE_LEN];
+        range[..4].copy_from_slice(&(offset as u32).to_le_bytes());
+        range[4..].copy_from_slice(&count.to_le_bytes());
+        range
+    }
+}
+
+// reading
+
+/// a database in the binary format. names and the lookup tables are read in
+/// place from `data`, without copying them out
+pub struct AffectsView<'a> {
+    metadata: Metadata,
+    names: StringPool<'a>,
+    localised_names: BTreeMap<Language, StringPool<'a>>,
+
+    name_kinds: BTreeMap<u32, BTreeSet<ItemKind>>,
+    name_sets: &'a [[u8; NAME_ENTRY_LEN]],
+    models: &'a [[u8; MODEL_LEN]],
+    model_effects: &'a [[u8; MODEL_EFFECT_LEN]],
+    emotes: KeyedRecords<'a>,
+    actions: KeyedRecords<'a>,
+    maps: KeyedRecords<'a>,
+    effects: KeyedRecords<'a>,
+    sounds: SoundMaps,
+    item_ids: &'a [[u8; ITEM_ID_LEN]],
+    items: &'a [[u8; ITEM_LEN]],
+    item_models: &'a [[u8; MODEL_KEY_LEN]],
+    attributes: AttributeMaps,
+    materials: MaterialMaps,
+    customisations: CustomisationMaps,
+}
+
+impl<'a> AffectsView<'a> {
+    /// whether `data` starts like a database in the binary format, as
+    /// opposed to json
+    pub fn is_binary(data: &[u8]) -> bool {
+        data.starts_with(&MAGIC)
+    }
+
+    /// checks the header, the schema version and every table, and decodes
+    /// the tables that are not read in place
+    pub fn new(data: &'a [u8]) -> Result<Self, BinaryError> {
+        if data.len() < HEADER_LEN {
+            return Err(BinaryError::Truncated);
+        }
+
+        if data[0..4] != MAGIC {
+            return Err(BinaryError::BadMagic);
+        }
+
+        let version = read_u16(data, 4).ok_or(BinaryError::Truncated)?;
+        if version != BINARY_VERSION {
+            return Err(BinaryError::UnsupportedVersion(version));
+        }
+
+        let count = read_u32(data, 8).ok_or(BinaryError::Truncated)? as usize;
+        let table_len = count
+            .checked_mul(SECTION_ENTRY_LEN)
+            .and_then(|len| len.checked_add(HEADER_LEN))
+            .ok_or(BinaryError::Truncated)?;
+        if table_len > data.len() {
+            return Err(BinaryError::Truncated);
+        }
+
+        let mut sections = Vec::with_capacity(count);
+        for i in 0..count {
+            let entry = HEADER_LEN + i * SECTION_ENTRY_LEN;
+            let id = read_u32(data, entry).ok_or(BinaryError::Truncated)?;
+            let offset = read_u32(data, entry + 4).ok_or(BinaryError::Truncated)? as usize;
+            let len = read_u32(data, entry + 8).ok_or(BinaryError::Truncated)? as usize;
+            let section = offset
+                .checked_add(len)
+                .and_then(|end| data.get(offset..end))
+                .ok_or(BinaryError::Truncated)?;
+            sections.push((id, section));
+        }
+
+        let sections = Sections(sections);
+
+        let metadata: Metadata = sections.decode(Section::Metadata)?;
+        metadata.check_schema()?;
+
+        let names = StringPool::new(sections.get(Section::Names)?, Section::Names)?;
+
+        let mut localised_names = BTreeMap::new();
+        for language in Language::ALL {
+            let section = Section::LocalisedNames(language);
+            if let Some(data) = sections.find(section) {
+                localised_names.insert(language, StringPool::new(data, section)?);
+            }
+        }
+
+        let view = Self {
+            metadata,
+            names,
+            localised_names,
+            name_kinds: sections.decode(Section::NameKinds)?,
+            name_sets: sections.records(Section::NameSets)?,
+            models: sections.records(Section::Models)?,
+            model_effects: sections.records(Section::ModelEffects)?,
+            emotes: KeyedRecords::new(sections.get(Section::Emotes)?, Section::Emotes)?,
+            actions: KeyedRecords::new(sections.get(Section::Actions)?, Section::Actions)?,
+            maps: KeyedRecords::new(sections.get(Section::Maps)?, Section::Maps)?,
+            effects: KeyedRecords::new(sections.get(Section::Effects)?, Section::Effects)?,
+            sounds: sections.decode(Section::Sounds)?,
+            item_ids: sections.records(Section::ItemIds)?,
+            items: sections.records(Section::Items)?,
+            item_models: sections.records(Section::ItemModels)?,
+            attributes: sections.decode(Section::Attributes)?,
+            materials: sections.decode(Section::Materials)?,
+            customisations: sections.decode(Section::Customisations)?,
+        };
+        view.check()?;
+
+        Ok(view)
+    }
+
+    /// checks the kinds, sort order and ranges of the tables read in place,
+    /// so that lookups can trust them
+    fn check(&self) -> Result<(), BinaryError> {
+        let invalid = |section: Section| Err(BinaryError::InvalidTable(section.name()));
+
+        if self
+            .name_sets
+            .iter()
+            .any(|entry| entry[0] as usize >= ItemKind::ALL.len())
+        {
+            return invalid(Section::NameSets);
+        }
+
+        let models_valid = self
+            .models
+            .iter()
+            .all(|record| read_model_key(record).is_some() && self.range_valid(&record[6..]))
+            && strictly_sorted(self.models, MODEL_KEY_LEN);
+        if !models_valid {
+            return invalid(Section::Models);
+        }
+
+        // effects of anything other than equipment are kept by a secondary
+        // id that fits in a byte
+        let effects_valid = self.model_effects.iter().all(|record| {
+            read_model_key(record).is_some_and(|source| {
+                source.kind() == ModelSourceKind::Equipment || source.secondary() <= 0xFF
+            })
+        }) && strictly_sorted(self.model_effects, MODEL_EFFECT_LEN);
+        if !effects_valid {
+            return invalid(Section::ModelEffects);
+        }
+
+        for table in KEYED_TABLES {
+            let records = self.keyed_records(table);
+            let sorted = (1..records.keys.len()).all(|i| records.key(i - 1) < records.key(i));
+            let ranges = records.names.iter().all(|range| self.range_valid(range));
+            if !sorted || !ranges {
+                return invalid(Section::keyed(table));
+            }
+        }
+
+        if !strictly_sorted(self.item_ids, 4) {
+            return invalid(Section::ItemIds);
+        }
+
+        let items_valid = self.items.iter().all(|record| {
+            let offset = read_u32(record, 12).unwrap_or_default() as usize;
+            let count = read_u32(record, 16).unwrap_or_default() as usize;
+            offset
+                .checked_add(count)
+                .is_some_and(|end| end <= self.item_models.len())
+        }) && self
+            .items
+            .windows(2)
+            .all(|pair| pair[0][..4] <= pair[1][..4]);
+        if !items_valid {
+            return invalid(Section::Items);
+        }
+
+        if !self
+            .item_models
+            .iter()
+            .all(|key| read_model_key(key).is_some())
+        {
+            return invalid(Section::ItemModels);
+        }
+
+        Ok(())
+    }
+
+    /// whether a name range is within the name set section
+    fn range_valid(&self, range: &[u8]) -> bool {
+        let offset = read_u32(range, 0).unwrap_or(u32::MAX) as usize;
+        let count = read_u32(range, 4).unwrap_or(u32::MAX) as usize;
+        offset
+            .checked_add(count)
+            .is_some_and(|end| end <= self.name_sets.len())
+    }
+
+    /// the names in a name range
+    fn names_in(&self, range: &[u8]) -> Names<'a> {
+        let offset = read_u32(range, 0).unwrap_or_default() as usize;
+        let count = read_u32(range, 4).unwrap_or_default() as usize;
+        let entries = self
+            .name_sets
+            .get(offset..offset + count)
+            .unwrap_or_default();
+        Names::packed(entries)
+    }
+
+    fn keyed_records(&self, table: KeyedTable) -> &KeyedRecords<'a> {
+        match table {
+            KeyedTable::Emotes => &self.emotes,
+            KeyedTable::Actions => &self.actions,
+            KeyedTable::Maps => &self.maps,
+            KeyedTable::Effects => &self.effects,
+        }
+    }
+
+    fn item(&self, record: &[u8; ITEM_LEN]) -> ItemInfo {
+        let offset = read_u32(record, 12).unwrap_or_default() as usize;
+        let count = read_u32(record, 16).unwrap_or_default() as usize;
+        let models = self
+            .item_models
+            .get(offset..offset + count)
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|key| read_model_key(key))
+            .collect();
+
+        ItemInfo {
+            item_id: read_u32(record, 4).unwrap_or_default(),
+            icon: read_u32(record, 8).unwrap_or_default(),
+            models,
+        }
+    }
+
+    /// copies every table out, giving a database that no longer borrows
+    /// `data`
+    pub fn into_affects(self) -> Affects {
+        let localised_names = self
+            .localised_names
+            .iter()
+            .map(|(&language, names)| (language, names.to_vec()))
+            .collect();
+
+        let mut affects = Affects {
+            metadata: self.metadata.clone(),
+            names: self.names.to_vec(),
+            localised_names,
+            name_kinds: self.name_kinds.clone(),
+            ..Default::default()
+        };
+
+        for kind in ModelSourceKind::ALL {
+            for (source, names) in self.models(ModelQuery::all(kind)) {
+                let names = names.to_set();
+                match source {
+                    ModelSource::Equipment {
+                        slot,
+                        model,
+                        variant,
+                    } => affects
+                        .equipment
+                        .entry(slot)
+                        .or_default()
+                        .entry(model)
+                        .or_default()
+                        .insert(variant, names),
+                    ModelSource::Weapon {
+                        model,
+                        weapon,
+                        variant,
+                    } => affects
+                        .weapons
+                        .entry(model)
+                        .or_default()
+                        .entry(weapon)
+                        .or_default()
+                        .insert(variant, names),
+                    ModelSource::Monster {
+                        model,
+                        base,
+                        variant,
+                    } => affects
+                        .monsters
+                        .entry(model)
+                        .or_default()
+                        .entry(base)
+                        .or_default()
+                        .insert(variant, names),
+                    ModelSource::Demihuman {
+                        model,
+                        base,
+                        variant,
+                    } => affects
+                        .demihumans
+                        .entry(model)
+                        .or_default()
+                        .entry(base)
+                        .or_default()
+                        .insert(variant, names),
+                };
+            }
+
+            for (source, effect) in self.model_effects(ModelQuery::all(kind)) {
+                let table = match source {
+                    ModelSource::Equipment {
+                        slot,
+                        model,
+                        variant,
+                    } => {
+                        affects
+                            .vfx
+                            .equipment
+                            .entry(model)
+                            .or_default()
+                            .entry(effect)
+                            .or_default()
+                            .insert((slot, variant));
+                        continue;
+                    }
+                    ModelSource::Weapon { .. } => &mut affects.vfx.weapons,
+                    ModelSource::Monster { .. } => &mut affects.vfx.monsters,
+                    ModelSource::Demihuman { .. } => &mut affects.vfx.demihumans,
+                };
+
+                table
+                    .entry(source.model())
+                    .or_default()
+                    .entry(source.secondary() as u8)
+                    .or_default()
+                    .entry(effect)
+                    .or_default()
+                    .insert(source.variant());
+            }
+        }
+
+        for table in KEYED_TABLES {
+            let entries = self
+                .keyed_range(table, "")
+                .into_iter()
+                .map(|(key, names)| (key.to_string(), names.to_set()))
+                .collect();
+            match table {
+                KeyedTable::Emotes => affects.emotes = entries,
+                KeyedTable::Actions => affects.actions = entries,
+                KeyedTable::Maps => affects.maps = entries,
+                KeyedTable::Effects => affects.vfx.effects = entries,
+            }
+        }
+
+        affects.item_ids = self.item_ids().into_iter().collect();
+        for record in self.items {
+            let name_idx = read_u32_be(record, 0);
+            affects
+                .items
+                .entry(name_idx)
+                .or_default()
+                .push(self.item(record));
+        }
+
+        affects.sounds = self.sounds;
+        affects.attributes = self.attributes;
+        affects.materials = self.materials;
+        affects.customisations = self.customisations;
+
+        affects
+    }
+}
+
+/// the sections of a file, by id
+struct Sections<'a>(Vec<(u32, &'a [u8])>);
+
+impl<'a> Sections<'a> {
+    fn find(&self, section: Section) -> Option<&'a [u8]> {
+        self.0
+            .iter()
+            .find(|(id, _)| *id == section.id())
+            .map(|&(_, data)| data)
+    }
+
+    fn get(&self, section: Section) -> Result<&'a [u8], BinaryError> {
+        self.find(section)
+            .ok_or(BinaryError::MissingSection(section.name()))
+    }
+
+    fn decode<T: DeserializeOwned>(&self, section: Section) -> Result<T, BinaryError> {
+        postcard::from_bytes(self.get(section)?).map_err(|source| BinaryError::Decode {
+            section: section.name(),
+            source,
+        })
+    }
+
+    fn records<const N: usize>(&self, section: Section) -> Result<&'a [[u8; N]], BinaryError> {
+        let (records, rest) = self.get(section)?.as_chunks::<N>();
+        match rest.is_empty() {
+            true => Ok(records),
+            false => Err(BinaryError::InvalidTable(section.name())),
+        }
+    }
+}
+
+impl AffectsData for AffectsView<'_> {
+    fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn name(&self, idx: u32, language: Language) -> Option<&str> {
+        self.localised_names
+            .get(&language)
+            .and_then(|names| names.get(idx as usize))
+            .filter(|name| !name.is_empty())
+            .or_else(|| self.names.get(idx as usize))
+    }
+
+    fn name_count(&self) -> usize {
+        self.names.len()
+    }
+
+    fn name_kinds(&self) -> &BTreeMap<u32, BTreeSet<ItemKind>> {
+        &self.name_kinds
+    }
+
+    fn models(&self, query: ModelQuery) -> Vec<(ModelSource, Names<'_>)> {
+        prefix_range(self.models, &query_prefix(query))
+            .iter()
+            .filter_map(|record| {
+                let source = read_model_key(record)?;
+                query
+                    .matches(source)
+                    .then(|| (source, self.names_in(&record[MODEL_KEY_LEN..])))
+            })
+            .collect()
+    }
+
+    fn model_effects(&self, query: ModelQuery) -> Vec<(ModelSource, u8)> {
+        prefix_range(self.model_effects, &query_prefix(query))
+            .iter()
+            .filter_map(|record| {
+                let source = read_model_key(record)?;
+                query
+                    .matches(source)
+                    .then_some((source, record[MODEL_KEY_LEN]))
+            })
+            .collect()
+    }
+
+    fn keyed(&self, table: KeyedTable, key: &str) -> Option<Names<'_>> {
+        let records = self.keyed_records(table);
+        let i = partition_point(records.keys.len(), |i| records.key(i) < key);
+        (i < records.keys.len() && records.key(i) == key).then(|| self.names_in(&records.names[i]))
+    }
+
+    fn keyed_range(&self, table: KeyedTable, prefix: &str) -> Vec<(&str, Names<'_>)> {
+        let records = self.keyed_records(table);
+        let start = partition_point(records.keys.len(), |i| records.key(i) < prefix);
+        (start..records.keys.len())
+            .map(|i| (records.key(i), i))
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, i)| (key, self.names_in(&records.names[i])))
+            .collect()
+    }
+
+    fn item_name(&self, item_id: u32) -> Option<u32> {
+        prefix_range(self.item_ids, &item_id.to_be_bytes())
+            .first()
+            .and_then(|record| read_u32(record, 4))
+    }
+
+    fn item_ids(&self) -> Vec<(u32, u32)> {
+        self.item_ids
+            .iter()
+            .map(|record| {
+                (
+                    read_u32_be(record, 0),
+                    read_u32(record, 4).unwrap_or_default(),
+                )
+            })
+            .collect()
+    }
+
+    fn items(&self, name_idx: u32) -> Vec<ItemInfo> {
+        prefix_range(self.items, &name_idx.to_be_bytes())
+            .iter()
+            .map(|record| self.item(record))
+            .collect()
+    }
+
+    fn all_items(&self) -> Vec<ItemInfo> {
+        self.items.iter().map(|record| self.item(record)).collect()
+    }
+
+    fn sounds(&self) -> &SoundMaps {
+        &self.sounds
+    }
+
+    fn attributes(&self) -> &AttributeMaps {
+        &self.attributes
+    }
+
+    fn materials(&self) -> &MaterialMaps {
+        &self.materials
+    }
+
+    fn customisations(&self) -> &CustomisationMaps {
+        &self.customisations
+    }
+}
+
+fn read_model_key(record: &[u8]) -> Option<ModelSource> {
+    let kind = *ModelSourceKind::ALL.get(*record.first()? as usize)?;
+    let model = u16::from_be_bytes([*record.get(1)?, *record.get(2)?]);
+    let secondary = u16::from_be_bytes([*record.get(3)?, *record.get(4)?]);
+    ModelSource::new(kind, model, secondary, *record.get(5)?)
+}
+
+/// the start of the model key shared by every model `query` matches. the ids
+/// after the first one that is not given are filtered afterwards
+fn query_prefix(query: ModelQuery) -> Vec<u8> {
+    let mut prefix = vec![query.kind as u8];
+    if let Some(model) = query.model {
+        prefix.extend_from_slice(&model.to_be_bytes());
+        if let Some(secondary) = query.secondary {
+            prefix.extend_from_slice(&secondary.to_be_bytes());
+            if let Some(variant) = query.variant {
+                prefix.push(variant);
+            }
+        }
+    }
+
+    prefix
+}
+
+/// the records whose keys start with `prefix`, from records sorted by key
+fn prefix_range<'a, const N: usize>(records: &'a [[u8; N]], prefix: &[u8]) -> &'a [[u8; N]] {
+    let start = records.partition_point(|record| record[..prefix.len()] < *prefix);
+    let end = records.partition_point(|record| record[..prefix.len()] <= *prefix);
+    &records[start..end]
+}
+
+/// whether records are sorted by their first `key_len` bytes, without any
+/// key appearing twice
+fn strictly_sorted<const N: usize>(records: &[[u8; N]], key_len: usize) -> bool {
+    records
+        .windows(2)
+        .all(|pair| pair[0][..key_len] < pair[1][..key_len])
+}
+
+/// the first index below `len` that `before` is false for, given that it is
+/// true for every index before that one
+fn partition_point(len: usize, before: impl Fn(usize) -> bool) -> usize {
+    let (mut low, mut high) = (0, len);
+    while low < high {
+        let mid = low + (high - low) / 2;
+        if before(mid) {
+            low = mid + 1;
+        } else {
+            high = mid;
+        }
+    }
+
+    low
+}
+
+/// a table keyed by a string, read in place from a section
+struct KeyedRecords<'a> {
+    keys: StringPool<'a>,
+    names: &'a [[u8; NAME_RANGE_LEN]],
+}
+
+impl<'a> KeyedRecords<'a> {
+    fn new(data: &'a [u8], section: Section) -> Result<Self, BinaryError> {
+        let invalid = || BinaryError::InvalidTable(section.name());
+
+        let keys_len = read_u32(data, 0).ok_or(invalid())? as usize;
+        let keys_end = keys_len.checked_add(4).ok_or(invalid())?;
+        let keys = StringPool::new(data.get(4..keys_end).ok_or(invalid())?, section)?;
+
+        let (names, rest) = data[keys_end..].as_chunks::<NAME_RANGE_LEN>();
+        if !rest.is_empty() || names.len() != keys.len() {
+            return Err(invalid());
+        }
+
+        Ok(Self { keys, names })
+    }
+
+    fn key(&self, i: usize) -> &'a str {
+        self.keys.get(i).unwrap_or_default()
+    }
+}
+
+/// strings read in place from a section
+struct StringPool<'a> {
+    offsets: &'a [u8],
+    strings: &'a str,
+}
+
+impl<'a> StringPool<'a> {
+    fn new(data: &'a [u8], section: Section) -> Result<Self, BinaryError> {
+        let invalid = || BinaryError::InvalidStrings(section.name());
+
+        let count = read_u32(data, 0).ok_or(invalid())? as usize;
+        let offsets_end = count
+            .checked_add(1)
+            .and_then(|n| n.checked_mul(4))
+            .and_then(|len| len.checked_add(4))
+            .ok_or(invalid())?;
+        let offsets = data.get(4..offsets_end).ok_or(invalid())?;
+        let strings = data
+            .get(offsets_end..)
+            .and_then(|strings| std::str::from_utf8(strings).ok())
+            .ok_or(invalid())?;
+
+        let pool = Self { offsets, strings };
+        let mut previous = 0;
+        for i in 0..=count {
+            let offset = pool.offset(i);
+            if offset < previous || !strings.is_char_boundary(offset) {
+                return Err(invalid());
+            }
+
+            previous = offset;
+        }
+
+        Ok(pool)
+    }
+
+    fn len(&self) -> usize {
+        self.offsets.len() / 4 - 1
+    }
+
+    fn offset(&self, i: usize) -> usize {
+        read_u32(self.offsets, i * 4).unwrap_or_default() as usize
+    }
+
+    fn get(&self, i: usize) -> Option<&'a str> {
+        if i >= self.len() {
+            return None;
+        }
+
+        self.strings.get(self.offset(i)..self.offset(i + 1))
+    }
+
+    fn to_vec(&self) -> Vec<String> {
+        (0..self.len())
+            .flat_map(|i| self.get(i))
+            .map(str::to_string)
+            .collect()
+    }
+}
+
+fn read_u16(data: &[u8], offset: usize) -> Option<u16> {
+    let bytes = data.get(offset..offset + 2)?;
+    Some(u16::from_le_bytes([bytes[0], bytes[1]]))
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    let bytes = data.get(offset..offset + 4)?;
+    Some(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+}
+
+/// reads a big endian record key
+fn read_u32_be(data: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ])
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use super::{AffectsView, BINARY_VERSION, BinaryError, HEADER_LEN, SECTION_ENTRY_LEN, Section};
+    use crate::{
+        Affects, AffectsData, AttributeMask, EquipSlot, ItemInfo, ItemKind, KeyedTable, Language,
+        Metadata, ModelQuery, ModelSource, ModelSourceKind, SCHEMA_VERSION, SchemaError,
+    };
+
+    fn affects() -> Affects {
+        let mut affects = Affects {
+            metadata: Metadata {
+                schema_version: SCHEMA_VERSION,
+                game_version: Some("2024.01.01.0000.0000".to_string()),
+                ..Default::default()
+            },
+            names: vec!["Bronze Helm".to_string(), "Bronze Sword".to_string()],
+            localised_names: BTreeMap::from([(
+                Language::German,
+                vec!["Bronzehelm".to_string(), String::new()],
+            )]),
+            ..Default::default()
+        };
+
+        let gear = (ItemKind::Gear, 0);
+        let weapon = (ItemKind::Weapon, 1);
+        affects.name_kinds.insert(0, [ItemKind::Gear].into());
+        affects.name_kinds.insert(1, [ItemKind::Weapon].into());
+        affects
+            .equipment
+            .entry(EquipSlot::Head)
+            .or_default()
+            .entry(6016)
+            .or_default()
+            .insert(1, [gear].into());
+        affects
+            .weapons
+            .entry(201)
+            .or_default()
+            .entry(1)
+            .or_default()
+            .insert(1, [weapon].into());
+        affects
+            .vfx
+            .weapons
+            .entry(201)
+            .or_default()
+            .entry(1)
+            .or_default()
+            .entry(4)
+            .or_default()
+            .insert(1);
+        affects
+            .vfx
+            .effects
+            .insert("ve0004".to_string(), [weapon].into());
+        affects
+            .emotes
+            .insert("emote/dance".to_string(), [gear].into());
+        affects
+            .maps
+            .insert("s1t1/01".to_string(), [gear, weapon].into());
+        affects.maps.insert("s1t2/01".to_string(), [weapon].into());
+        affects
+            .attributes
+            .equipment
+            .entry(EquipSlot::Head)
+            .or_default()
+            .entry(6016)
+            .or_default()
+            .insert(1, AttributeMask::from_letter('a').unwrap());
+        affects
+            .materials
+            .decals
+            .insert(3, [(EquipSlot::Head, 6016, 1)].into());
+        affects
+            .customisations
+            .eyes
+            .insert("eye09_base".to_string(), [gear].into());
+        affects.item_ids.insert(2048, 0);
+        affects.items.insert(
+            0,
+            vec![ItemInfo {
+                item_id: 2048,
+                icon: 40001,
+                models: vec![ModelSource::Equipment {
+                    slot: EquipSlot::Head,
+                    model: 6016,
+                    variant: 1,
+                }],
+            }],
+        );
+
+        affects
+    }
+
+    fn set_u32(data: &mut [u8], offset: usize, value: u32) {
+        data[offset..offset + 4].copy_from_slice(&value.to_le_bytes());
+    }
+
+    /// the entry of `section` in the section table
+    fn section_entry(data: &[u8], section: Section) -> usize {
+        (0..)
+            .map(|i| HEADER_LEN + i * SECTION_ENTRY_LEN)
+            .find(|&entry| data[entry..entry + 4] == section.id().to_le_bytes())
+            .unwrap()
+    }
+
+    /// every table, in a form that can be compared between implementations
+    fn debug(data: &dyn AffectsData) -> String {
+        let models = ModelSourceKind::ALL.map(|kind| {
+            let models = data
+                .models(ModelQuery::all(kind))
+                .into_iter()
+                .map(|(source, names)| (source, names.to_set()))
+                .collect::<Vec<_>>();
+            let mut effects = data.model_effects(ModelQuery::all(kind));
+            effects.sort();
+            (models, effects)
+        });
+        let keyed = [
+            KeyedTable::Emotes,
+            KeyedTable::Actions,
+            KeyedTable::Maps,
+            KeyedTable::Effects,
+        ]
+        .map(|table| {
+            data.keyed_range(table, "")
+                .into_iter()
+                .map(|(key, names)| (key.to_string(), names.to_set()))
+                .collect::<Vec<_>>()
+        });
+
+        format!(
+            "{:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?} {:?}",
+            data.name_kinds(),
+            models,
+            keyed,
+            data.sounds(),
+            data.attributes(),
+            data.materials(),
+            data.customisations(),
+            data.item_ids(),
+            data.all_items(),
+        )
+    }
+
+    #[test]
+    fn round_trip() {
+        let affects = affects();
+        let data = affects.to_binary().unwrap();
+        let view = AffectsView::new(&data).unwrap();
+
+        assert_eq!(affects.metadata, *view.metadata());
+        assert_eq!(affects.names.len(), view.name_count());
+        for language in Language::ALL {
+            for idx in 0..3 {
+                assert_eq!(affects.name(idx, language), view.name(idx, language));
+            }
+        }
+
+        assert_eq!(Some("Bronzehelm"), view.name(0, Language::German));
+        assert_eq!(Some("Bronze Sword"), view.name(1, Language::German));
+
+        assert_eq!(debug(&affects), debug(&view));
+
+        let decoded = Affects::from_binary(&data).unwrap();
+        assert_eq!(affects.names, decoded.names);
+        assert_eq!(affects.localised_names, decoded.localised_names);
+        assert_eq!(debug(&affects), debug(&decoded));
+    }
+
+    #[test]
+    fn lookups() {
+        let affects = affects();
+        let data = affects.to_binary().unwrap();
+        let view = AffectsView::new(&data).unwrap();
+
+        for data in [&affects as &dyn AffectsData, &view] {
+            let helm = ModelQuery::model(ModelSourceKind::Equipment, 6016);
+            let found = data.models(helm);
+            assert_eq!(1, found.len());
+            assert_eq!(
+                vec![(ItemKind::Gear, 0)],
+                found[0].1.iter().collect::<Vec<_>>()
+            );
+            assert!(
+                data.models(helm.secondary(EquipSlot::Body as u16))
+                    .is_empty()
+            );
+            assert!(
+                data.models(ModelQuery::model(ModelSourceKind::Equipment, 6017))
+                    .is_empty()
+            );
+
+            let sword = ModelQuery::model(ModelSourceKind::Weapon, 201).secondary(1);
+            assert_eq!(1, data.models(sword.variant(1)).len());
+            assert!(data.models(sword.variant(2)).is_empty());
+            assert_eq!(
+                vec![(
+                    ModelSource::Weapon {
+                        model: 201,
+                        weapon: 1,
+                        variant: 1
+                    },
+                    4
+                )],
+                data.model_effects(sword)
+            );
+
+            assert!(data.keyed(KeyedTable::Emotes, "emote/dance").is_some());
+            assert!(data.keyed(KeyedTable::Emotes, "emote/danc").is_none());
+            let maps = data.keyed_range(KeyedTable::Maps, "s1t1/");
+            assert_eq!(
+                vec!["s1t1/01"],
+                maps.iter().map(|(key, _)| *key).collect::<Vec<_>>()
+            );
+            assert_eq!(2, data.keyed_range(KeyedTable::Maps, "").len());
+
+            assert_eq!(Some(0), data.item_name(2048));
+            assert_eq!(None, data.item_name(2049));
+            assert_eq!(40001, data.items(0)[0].icon);
+            assert!(data.items(1).is_empty());
+        }
+    }
+
+    #[test]
+    fn truncated_header() {
+        let data = affects().to_binary().unwrap();
+
+        assert!(matches!(
+            AffectsView::new(&data[..HEADER_LEN - 1]),
+            Err(BinaryError::Truncated)
+        ));
+    }
+
+    #[test]
+    fn oversized_section_count() {
+        let mut data = affects().to_binary().unwrap();
+        set_u32(&mut data, 8, u32::MAX);
+
+        assert!(matches!(
+            AffectsView::new(&data),
+            Err(BinaryError::Truncated)
+        ));
+    }
+
+    #[test]
+    fn section_out_of_range() {
+        let mut data = affects().to_binary().unwrap();
+        let len = data.len() as u32;
+        set_u32(&mut data, HEADER_LEN + 4, len);
+
+        assert!(matches!(
+            AffectsView::new(&data),
+            Err(BinaryError::Truncated)
+        ));
+    }
+
+    #[test]
+    fn missing_section() {
+        let mut data = affects().to_binary().unwrap();
+        let entry = section_entry(&data, Section::Maps);
+        set_u32(&mut data, entry, 0xFFFF);
+
+        assert!(matches!(
+            AffectsView::new(&data),
+            Err(BinaryError::MissingSection("maps"))
+        ));
+    }
+
+    #[test]
+    fn bad_magic() {
+        let mut data = affects().to_binary().unwrap();
+        data[0] = b'X';
+
+        assert!(!AffectsView::is_binary(&data));
+        assert!(matches!(
+            AffectsView::new(&data),
+            Err(BinaryError::BadMagic)
+        ));
+    }
+
+    #[test]
+    fn bad_version() {
+        let mut data = affects().to_binary().unwrap();
+        data[4..6].copy_from_slice(&(BINARY_VERSION + 1).to_le_bytes());
+
+        assert!(matches!(
+            AffectsView::new(&data),
+            Err(BinaryError::UnsupportedVersion(version)) if version == BINARY_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn stale_schema() {
+        let mut affects = affects();
+        affects.metadata.schema_version = SCHEMA_VERSION - 1;
+        let data = affects.to_binary().unwrap();
+
+        assert!(matches!(
+            AffectsView::new(&data),
+            Err(BinaryError::Schema(SchemaError::Incompatible { .. }))
+        ));
+    }
+
+    #[test]
+    fn invalid_model_kind() {
+        let mut data = affects().to_binary().unwrap();
+        let entry = section_entry(&data, Section::Models);
+        let offset = u32::from_le_bytes(data[entry + 4..entry + 8].try_into().unwrap());
+        data[offset as usize] = 0xFF;
+
+        assert!(matches!(
+            AffectsView::new(&data),
+            Err(BinaryError::InvalidTable("models"))
+        ));
+    }
+
+    #[test]
+    fn name_range_out_of_bounds() {
+        let mut data = affects().to_binary().unwrap();
+        let entry = section_entry(&data, Section::Models);
+        let offset = u32::from_le_bytes(data[entry + 4..entry + 8].try_into().unwrap());
+        // the name count of the first model
+        set_u32(&mut data, offset as usize + 10, u32::MAX);
+
+        assert!(matches!(
+            AffectsView::new(&data),
+            Err(BinaryError::InvalidTable("models"))
+        ));
+    }
+
+    #[test]
+    fn corrupt_table() {
+        let affects = affects();
+        let mut data = affects.to_binary().unwrap();
+        // turn the length prefix of the sounds table into a huge varint
+        let entry = section_entry(&data, Section::Sounds);
+        let offset = u32::from_le_bytes(data[entry + 4..entry + 8].try_into().unwrap());
+        data[offset as usize] = 0xFF;
+
+        assert!(matches!(
+            AffectsView::new(&data),
+            Err(BinaryError::Decode {
+                section: "sounds",
+                ..
+            })
+        ));
+    }
+}
@@ -3,35 +3,190 @@ use std::collections::{BTreeMap, BTreeSet};
 use serde::{Deserialize, Serialize};
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
-use crate::EquipSlot;
+use crate::{AttributeMask, EquipSlot, Language, Metadata};
 
-type NameSet = BTreeSet<(ItemKind, u16)>;
+/// item kinds and name indices
+pub type NameSet = BTreeSet<(ItemKind, u32)>;
+/// slot => model => variant => set of name indices
+pub type EquipmentTable = BTreeMap<EquipSlot, BTreeMap<u16, BTreeMap<u8, NameSet>>>;
+/// model => secondary id => variant => set of name indices
+pub type ModelTable<S> = BTreeMap<u16, BTreeMap<S, BTreeMap<u8, NameSet>>>;
 
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct Affects {
+    #[serde(default)]
+    pub metadata: Metadata,
+
+    /// english names, which are also used when a name has no translation
     pub names: Vec<String>,
+    /// language => names by the same index as `names`
+    pub localised_names: BTreeMap<Language, Vec<String>>,
     // name index => set of item kinds
-    pub name_kinds: BTreeMap<u16, BTreeSet<ItemKind>>,
+    pub name_kinds: BTreeMap<u32, BTreeSet<ItemKind>>,
 
-    /// slot => model => variant => set of name indices
-    pub equipment: BTreeMap<EquipSlot, BTreeMap<u16, BTreeMap<u8, NameSet>>>,
-    /// model => secondary => variant => set of name indices
-    pub weapons: BTreeMap<u16, BTreeMap<u16, BTreeMap<u8, NameSet>>>,
+    /// slot => model => imc variant => set of name indices
+    pub equipment: EquipmentTable,
+    /// model => secondary => imc variant => set of name indices
+    pub weapons: ModelTable<u16>,
     /// timeline key => set of name indices
     pub emotes: BTreeMap<String, NameSet>,
-    /// model => base => variant => set of name indices
-    pub monsters: BTreeMap<u16, BTreeMap<u8, BTreeMap<u8, NameSet>>>,
-    /// model => base => variant => set of name indices
-    pub demihumans: BTreeMap<u16, BTreeMap<u8, BTreeMap<u8, NameSet>>>,
+    /// model => base => imc variant => set of name indices
+    pub monsters: ModelTable<u8>,
+    /// model => base => material variant => set of name indices
+    pub demihumans: ModelTable<u8>,
     /// animation id => set of name indices
     pub actions: BTreeMap<String, NameSet>,
     /// map id => set of name indices
     pub maps: BTreeMap<String, NameSet>,
 
     pub vfx: VfxMaps,
+    pub sounds: SoundMaps,
+    /// the attributes each model variant shows
+    #[serde(default)]
+    pub attributes: AttributeMaps,
+    #[serde(default)]
+    pub materials: MaterialMaps,
+    #[serde(default)]
+    pub customisations: CustomisationMaps,
 
     /// item id => name index
-    pub item_ids: BTreeMap<u32, u16>,
+    pub item_ids: BTreeMap<u32, u32>,
+    /// name index => items with that name
+    pub items: BTreeMap<u32, Vec<ItemInfo>>,
+}
+
+impl Affects {
+    /// gets the name at `idx` in `language`, falling back to english when
+    /// there is no translation for it
+    pub fn name(&self, idx: u32, language: Language) -> Option<&str> {
+        self.localised_names
+            .get(&language)
+            .and_then(|names| names.get(idx as usize))
+            .filter(|name| !name.is_empty())
+            .or_else(|| self.names.get(idx as usize))
+            .map(String::as_str)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ItemInfo {
+    pub item_id: u32,
+    pub icon: u32,
+    /// every model and variant the item is displayed with
+    pub models: Vec<ModelSource>,
+}
+
+/// a model and variant, as used to key the equipment, weapon, monster and
+/// demihuman tables
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+pub enum ModelSource {
+    Equipment {
+        slot: EquipSlot,
+        model: u16,
+        variant: u8,
+    },
+    Weapon {
+        model: u16,
+        weapon: u16,
+        variant: u8,
+    },
+    Monster {
+        model: u16,
+        base: u8,
+        variant: u8,
+    },
+    Demihuman {
+        model: u16,
+        base: u8,
+        variant: u8,
+    },
+}
+
+/// the table a [`ModelSource`] is kept in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum ModelSourceKind {
+    Equipment,
+    Weapon,
+    Monster,
+    Demihuman,
+}
+
+impl ModelSourceKind {
+    /// every kind, in the order of their discriminants
+    pub const ALL: [Self; 4] = [
+        Self::Equipment,
+        Self::Weapon,
+        Self::Monster,
+        Self::Demihuman,
+    ];
+}
+
+impl ModelSource {
+    /// builds a source from its ids. the secondary id of equipment is its
+    /// slot, and monster and demihuman bases have to fit in a byte
+    pub fn new(kind: ModelSourceKind, model: u16, secondary: u16, variant: u8) -> Option<Self> {
+        let source = match kind {
+            ModelSourceKind::Equipment => Self::Equipment {
+                slot: *EquipSlot::ALL.get(secondary as usize)?,
+                model,
+                variant,
+            },
+            ModelSourceKind::Weapon => Self::Weapon {
+                model,
+                weapon: secondary,
+                variant,
+            },
+            ModelSourceKind::Monster => Self::Monster {
+                model,
+                base: u8::try_from(secondary).ok()?,
+                variant,
+            },
+            ModelSourceKind::Demihuman => Self::Demihuman {
+                model,
+                base: u8::try_from(secondary).ok()?,
+                variant,
+            },
+        };
+
+        Some(source)
+    }
+
+    pub fn kind(self) -> ModelSourceKind {
+        match self {
+            Self::Equipment { .. } => ModelSourceKind::Equipment,
+            Self::Weapon { .. } => ModelSourceKind::Weapon,
+            Self::Monster { .. } => ModelSourceKind::Monster,
+            Self::Demihuman { .. } => ModelSourceKind::Demihuman,
+        }
+    }
+
+    pub fn model(self) -> u16 {
+        match self {
+            Self::Equipment { model, .. }
+            | Self::Weapon { model, .. }
+            | Self::Monster { model, .. }
+            | Self::Demihuman { model, .. } => model,
+        }
+    }
+
+    /// the weapon or base id, or the slot of equipment
+    pub fn secondary(self) -> u16 {
+        match self {
+            Self::Equipment { slot, .. } => slot as u16,
+            Self::Weapon { weapon, .. } => weapon,
+            Self::Monster { base, .. } | Self::Demihuman { base, .. } => base as u16,
+        }
+    }
+
+    pub fn variant(self) -> u8 {
+        match self {
+            Self::Equipment { variant, .. }
+            | Self::Weapon { variant, .. }
+            | Self::Monster { variant, .. }
+            | Self::Demihuman { variant, .. } => variant,
+        }
+    }
 }
 
 #[derive(Debug, Default, Serialize, Deserialize)]
@@ -44,6 +199,97 @@ pub struct VfxMaps {
     pub demihumans: BTreeMap<u16, BTreeMap<u8, BTreeMap<u8, BTreeSet<u8>>>>,
     /// model => weapon => vfx => variants
     pub weapons: BTreeMap<u16, BTreeMap<u8, BTreeMap<u8, BTreeSet<u8>>>>,
+    /// vfx file stem => set of name indices
+    pub effects: BTreeMap<String, NameSet>,
+}
+
+/// the attributes each variant of a model shows. variants that are not
+/// listed show all of them
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct AttributeMaps {
+    /// slot => model => variant => attributes
+    pub equipment: BTreeMap<EquipSlot, BTreeMap<u16, BTreeMap<u8, AttributeMask>>>,
+    /// model => weapon => variant => attributes
+    pub weapons: BTreeMap<u16, BTreeMap<u16, BTreeMap<u8, AttributeMask>>>,
+    /// model => base => variant => attributes
+    pub monsters: BTreeMap<u16, BTreeMap<u8, BTreeMap<u8, AttributeMask>>>,
+}
+
+/// the gear that uses each equipment decal and material animation, and the
+/// material each imc variant uses, taken from imc files
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct MaterialMaps {
+    /// decal id => (slot, model, variant)s
+    pub decals: BTreeMap<u8, BTreeSet<(EquipSlot, u16, u8)>>,
+    /// material animation id => (slot, model, variant)s
+    pub animations: BTreeMap<u8, BTreeSet<(EquipSlot, u16, u8)>>,
+    /// slot => model => imc variant => the material variant in its paths
+    pub equipment: BTreeMap<EquipSlot, BTreeMap<u16, BTreeMap<u8, u8>>>,
+    /// model => weapon => imc variant => material variant
+    pub weapons: BTreeMap<u16, BTreeMap<u16, BTreeMap<u8, u8>>>,
+    /// model => base => imc variant => material variant
+    pub monsters: BTreeMap<u16, BTreeMap<u8, BTreeMap<u8, u8>>>,
+}
+
+impl MaterialMaps {
+    /// the material variant used in the material and texture paths of
+    /// `source`. demihumans are already kept by material, and variants
+    /// without a known imc file use the material with their own id
+    pub fn material(&self, source: ModelSource) -> u8 {
+        let material = match source {
+            ModelSource::Equipment {
+                slot,
+                model,
+                variant,
+            } => self
+                .equipment
+                .get(&slot)
+                .and_then(|models| models.get(&model))
+                .and_then(|variants| variants.get(&variant)),
+            ModelSource::Weapon {
+                model,
+                weapon,
+                variant,
+            } => self
+                .weapons
+                .get(&model)
+                .and_then(|weapons| weapons.get(&weapon))
+                .and_then(|variants| variants.get(&variant)),
+            ModelSource::Monster {
+                model,
+                base,
+                variant,
+            } => self
+                .monsters
+                .get(&model)
+                .and_then(|bases| bases.get(&base))
+                .and_then(|variants| variants.get(&variant)),
+            ModelSource::Demihuman { .. } => None,
+        };
+
+        material.copied().unwrap_or(source.variant())
+    }
+}
+
+/// the races, tribes and genders that can pick each character customisation,
+/// and the items unlocking them
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct CustomisationMaps {
+    /// model info => body type => id => set of name indices. model infos and
+    /// body types are as they are in paths, like `801` for `c0801` and `hair`
+    pub models: BTreeMap<u16, BTreeMap<String, BTreeMap<u16, NameSet>>>,
+    /// eye texture file stem, like `eye09_base` => set of name indices
+    pub eyes: BTreeMap<String, NameSet>,
+    /// face paint decal id => set of name indices
+    pub face_paints: BTreeMap<u16, NameSet>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SoundMaps {
+    /// lowercase scd path => set of name indices for orchestrion rolls
+    pub bgm: BTreeMap<String, NameSet>,
+    /// lowercase scd path => set of name indices for timelines playing it
+    pub timelines: BTreeMap<String, NameSet>,
 }
 
 #[derive(Debug, Deserialize_repr, Serialize_repr, Clone, Copy, PartialEq, PartialOrd, Eq, Ord)]
@@ -64,6 +310,29 @@ pub enum ItemKind {
     Font,
     Miscellaneous,
     Animation,
+    Sound,
+}
+
+impl ItemKind {
+    /// every kind, in the order of their discriminants
+    pub const ALL: [Self; 16] = [
+        Self::Gear,
+        Self::Weapon,
+        Self::Emote,
+        Self::BattleNpc,
+        Self::EventNpc,
+        Self::Minion,
+        Self::Mount,
+        Self::FashionAccessory,
+        Self::Customisation,
+        Self::Action,
+        Self::Map,
+        Self::Icon,
+        Self::Font,
+        Self::Miscellaneous,
+        Self::Animation,
+        Self::Sound,
+    ];
 }
 
 impl std::fmt::Display for ItemKind {
@@ -84,6 +353,7 @@ impl std::fmt::Display for ItemKind {
             Self::Font => "Font",
             Self::Miscellaneous => "Miscellaneous",
             Self::Animation => "Animation",
+            Self::Sound => "Sound",
         };
 
         write!(f, "{s}")
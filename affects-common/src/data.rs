@@ -0,0 +1,380 @@
+use std::collections::{BTreeMap, BTreeSet, btree_map};
+
+use crate::{
+    Affects, AttributeMaps, CustomisationMaps, ItemInfo, ItemKind, Language, MaterialMaps,
+    Metadata, ModelSource, ModelSourceKind, ModelTable, NameSet, SoundMaps,
+};
+
+/// read access to an affects database, whether it has been loaded into an
+/// [`Affects`] or is read from the binary format as it is needed. the model,
+/// emote, action, map, vfx and item tables are queried rather than handed
+/// out whole, so the binary format can search them in place
+pub trait AffectsData {
+    fn metadata(&self) -> &Metadata;
+
+    /// see [`Affects::name`]
+    fn name(&self, idx: u32, language: Language) -> Option<&str>;
+    /// the number of english names, which every name index is below
+    fn name_count(&self) -> usize;
+    fn name_kinds(&self) -> &BTreeMap<u32, BTreeSet<ItemKind>>;
+
+    /// the names shown with each model variant matching `query`, in the
+    /// order of their ids
+    fn models(&self, query: ModelQuery) -> Vec<(ModelSource, Names<'_>)>;
+    /// the effect ids shown by each model variant matching `query`
+    fn model_effects(&self, query: ModelQuery) -> Vec<(ModelSource, u8)>;
+
+    /// the names using `key` in `table`
+    fn keyed(&self, table: KeyedTable, key: &str) -> Option<Names<'_>>;
+    /// every key in `table` starting with `prefix`, in order, and the names
+    /// using it. an empty prefix lists the whole table
+    fn keyed_range(&self, table: KeyedTable, prefix: &str) -> Vec<(&str, Names<'_>)>;
+
+    /// the name index of a row in the Item sheet
+    fn item_name(&self, item_id: u32) -> Option<u32>;
+    /// every (item id, name index), by item id
+    fn item_ids(&self) -> Vec<(u32, u32)>;
+    /// the items with the name at `name_idx`
+    fn items(&self, name_idx: u32) -> Vec<ItemInfo>;
+    /// every item, by name index
+    fn all_items(&self) -> Vec<ItemInfo>;
+
+    fn sounds(&self) -> &SoundMaps;
+    fn attributes(&self) -> &AttributeMaps;
+    fn materials(&self) -> &MaterialMaps;
+    fn customisations(&self) -> &CustomisationMaps;
+}
+
+/// the model variants to look up in [`AffectsData::models`]. ids that are not
+/// given match every id
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ModelQuery {
+    pub kind: ModelSourceKind,
+    pub model: Option<u16>,
+    /// see [`ModelSource::secondary`]
+    pub secondary: Option<u16>,
+    pub variant: Option<u8>,
+}
+
+impl ModelQuery {
+    /// every model of `kind`
+    pub fn all(kind: ModelSourceKind) -> Self {
+        Self {
+            kind,
+            model: None,
+            secondary: None,
+            variant: None,
+        }
+    }
+
+    pub fn model(kind: ModelSourceKind, model: u16) -> Self {
+        Self {
+            model: Some(model),
+            ..Self::all(kind)
+        }
+    }
+
+    pub fn secondary(self, secondary: u16) -> Self {
+        Self {
+            secondary: Some(secondary),
+            ..self
+        }
+    }
+
+    pub fn variant(self, variant: u8) -> Self {
+        Self {
+            variant: Some(variant),
+            ..self
+        }
+    }
+
+    pub fn matches(&self, source: ModelSource) -> bool {
+        source.kind() == self.kind
+            && self.model.is_none_or(|model| model == source.model())
+            && self
+                .secondary
+                .is_none_or(|secondary| secondary == source.secondary())
+            && self
+                .variant
+                .is_none_or(|variant| variant == source.variant())
+    }
+}
+
+impl From<ModelSource> for ModelQuery {
+    fn from(source: ModelSource) -> Self {
+        Self::model(source.kind(), source.model())
+            .secondary(source.secondary())
+            .variant(source.variant())
+    }
+}
+
+/// the tables keyed by a string
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyedTable {
+    /// timeline key => names
+    Emotes,
+    /// animation id => names
+    Actions,
+    /// map id, like `s1t1/01` => names
+    Maps,
+    /// vfx file stem => names
+    Effects,
+}
+
+/// a set of item kinds and name indices borrowed from a database
+#[derive(Debug, Clone, Copy)]
+pub struct Names<'a>(NamesRepr<'a>);
+
+#[derive(Debug, Clone, Copy)]
+enum NamesRepr<'a> {
+    Set(&'a NameSet),
+    /// see [`crate::binary`]
+    Packed(&'a [[u8; 5]]),
+}
+
+impl<'a> Names<'a> {
+    pub(crate) fn packed(entries: &'a [[u8; 5]]) -> Self {
+        Self(NamesRepr::Packed(entries))
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (ItemKind, u32)> + use<'a> {
+        let (set, packed) = match self.0 {
+            NamesRepr::Set(set) => (Some(set), None),
+            NamesRepr::Packed(entries) => (None, Some(entries)),
+        };
+
+        let set = set.into_iter().flatten().copied();
+        let packed = packed.into_iter().flatten().filter_map(|entry| {
+            let kind = *ItemKind::ALL.get(entry[0] as usize)?;
+            let idx = u32::from_le_bytes([entry[1], entry[2], entry[3], entry[4]]);
+            Some((kind, idx))
+        });
+
+        set.chain(packed)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        match self.0 {
+            NamesRepr::Set(set) => set.is_empty(),
+            NamesRepr::Packed(entries) => entries.is_empty(),
+        }
+    }
+
+    pub fn to_set(&self) -> NameSet {
+        self.iter().collect()
+    }
+}
+
+impl<'a> From<&'a NameSet> for Names<'a> {
+    fn from(set: &'a NameSet) -> Self {
+        Self(NamesRepr::Set(set))
+    }
+}
+
+impl AffectsData for Affects {
+    fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
+    fn name(&self, idx: u32, language: Language) -> Option<&str> {
+        Affects::name(self, idx, language)
+    }
+
+    fn name_count(&self) -> usize {
+        self.names.len()
+    }
+
+    fn name_kinds(&self) -> &BTreeMap<u32, BTreeSet<ItemKind>> {
+        &self.name_kinds
+    }
+
+    fn models(&self, query: ModelQuery) -> Vec<(ModelSource, Names<'_>)> {
+        let mut found = Vec::new();
+        let mut add = |source, names| {
+            if query.matches(source) {
+                found.push((source, Names::from(names)));
+            }
+        };
+
+        match query.kind {
+            // equipment is keyed by slot first, so every slot is looked at
+            ModelSourceKind::Equipment => {
+                for (&slot, models) in &self.equipment {
+                    for (&model, variants) in select(models, query.model) {
+                        for (&variant, names) in select(variants, query.variant) {
+                            let source = ModelSource::Equipment {
+                                slot,
+                                model,
+                                variant,
+                            };
+                            add(source, names);
+                        }
+                    }
+                }
+            }
+            ModelSourceKind::Weapon => {
+                for (model, weapon, variant, names) in table_models(&self.weapons, query) {
+                    let source = ModelSource::Weapon {
+                        model,
+                        weapon,
+                        variant,
+                    };
+                    add(source, names);
+                }
+            }
+            ModelSourceKind::Monster => {
+                for (model, base, variant, names) in table_models(&self.monsters, query) {
+                    let source = ModelSource::Monster {
+                        model,
+                        base,
+                        variant,
+                    };
+                    add(source, names);
+                }
+            }
+            ModelSourceKind::Demihuman => {
+                for (model, base, variant, names) in table_models(&self.demihumans, query) {
+                    let source = ModelSource::Demihuman {
+                        model,
+                        base,
+                        variant,
+                    };
+                    add(source, names);
+                }
+            }
+        }
+
+        found.sort_by_key(|(source, _)| (source.model(), source.secondary(), source.variant()));
+        found
+    }
+
+    fn model_effects(&self, query: ModelQuery) -> Vec<(ModelSource, u8)> {
+        let vfx = &self.vfx;
+        let mut found = Vec::new();
+
+        if query.kind == ModelSourceKind::Equipment {
+            for (&model, effects) in select(&vfx.equipment, query.model) {
+                for (&effect, used_by) in effects {
+                    for &(slot, variant) in used_by {
+                        let source = ModelSource::Equipment {
+                            slot,
+                            model,
+                            variant,
+                        };
+                        found.push((source, effect));
+                    }
+                }
+            }
+        } else {
+            let table = match query.kind {
+                ModelSourceKind::Weapon => &vfx.weapons,
+                ModelSourceKind::Monster => &vfx.monsters,
+                _ => &vfx.demihumans,
+            };
+
+            for (&model, secondaries) in select(table, query.model) {
+                for (&secondary, effects) in secondaries {
+                    for (&effect, variants) in effects {
+                        let sources = variants.iter().flat_map(|&variant| {
+                            ModelSource::new(query.kind, model, secondary as u16, variant)
+                        });
+                        found.extend(sources.map(|source| (source, effect)));
+                    }
+                }
+            }
+        }
+
+        found.retain(|&(source, _)| query.matches(source));
+        found
+    }
+
+    fn keyed(&self, table: KeyedTable, key: &str) -> Option<Names<'_>> {
+        self.keyed_table(table).get(key).map(Names::from)
+    }
+
+    fn keyed_range(&self, table: KeyedTable, prefix: &str) -> Vec<(&str, Names<'_>)> {
+        self.keyed_table(table)
+            .range(prefix.to_string()..)
+            .take_while(|(key, _)| key.starts_with(prefix))
+            .map(|(key, names)| (key.as_str(), Names::from(names)))
+            .collect()
+    }
+
+    fn item_name(&self, item_id: u32) -> Option<u32> {
+        self.item_ids.get(&item_id).copied()
+    }
+
+    fn item_ids(&self) -> Vec<(u32, u32)> {
+        self.item_ids
+            .iter()
+            .map(|(&item_id, &name_idx)| (item_id, name_idx))
+            .collect()
+    }
+
+    fn items(&self, name_idx: u32) -> Vec<ItemInfo> {
+        self.items.get(&name_idx).cloned().unwrap_or_default()
+    }
+
+    fn all_items(&self) -> Vec<ItemInfo> {
+        self.items.values().flatten().cloned().collect()
+    }
+
+    fn sounds(&self) -> &SoundMaps {
+        &self.sounds
+    }
+
+    fn attributes(&self) -> &AttributeMaps {
+        &self.attributes
+    }
+
+    fn materials(&self) -> &MaterialMaps {
+        &self.materials
+    }
+
+    fn customisations(&self) -> &CustomisationMaps {
+        &self.customisations
+    }
+}
+
+impl Affects {
+    fn keyed_table(&self, table: KeyedTable) -> &BTreeMap<String, NameSet> {
+        match table {
+            KeyedTable::Emotes => &self.emotes,
+            KeyedTable::Actions => &self.actions,
+            KeyedTable::Maps => &self.maps,
+            KeyedTable::Effects => &self.vfx.effects,
+        }
+    }
+}
+
+/// the entry of `map` at `key`, or every entry if there is no key
+fn select<K: Ord + Copy, V>(map: &BTreeMap<K, V>, key: Option<K>) -> btree_map::Range<'_, K, V> {
+    match key {
+        Some(key) => map.range(key..=key),
+        None => map.range(..),
+    }
+}
+
+/// the (model, secondary, variant, names) in `table` matching the ids of
+/// `query`
+fn table_models<S: Ord + Copy + TryFrom<u16>>(
+    table: &ModelTable<S>,
+    query: ModelQuery,
+) -> Vec<(u16, S, u8, &NameSet)> {
+    let secondary = match query.secondary.map(S::try_from) {
+        Some(Ok(secondary)) => Some(secondary),
+        Some(Err(_)) => return Vec::new(),
+        None => None,
+    };
+
+    let mut found = Vec::new();
+    for (&model, secondaries) in select(table, query.model) {
+        for (&secondary, variants) in select(secondaries, secondary) {
+            for (&variant, names) in select(variants, query.variant) {
+                found.push((model, secondary, variant, names));
+            }
+        }
+    }
+
+    found
+}
@@ -43,6 +43,20 @@ impl FromStr for EquipSlot {
 }
 
 impl EquipSlot {
+    /// every slot, in the order of their discriminants
+    pub const ALL: [Self; 10] = [
+        Self::Head,
+        Self::Hands,
+        Self::Legs,
+        Self::Feet,
+        Self::Body,
+        Self::Ears,
+        Self::Neck,
+        Self::RFinger,
+        Self::LFinger,
+        Self::Wrists,
+    ];
+
     pub fn to_id(self) -> u64 {
         match self {
             Self::Head => 3,
@@ -83,6 +97,21 @@ impl EquipSlot {
         )
     }
 
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Head => "met",
+            Self::Hands => "glv",
+            Self::Legs => "dwn",
+            Self::Feet => "sho",
+            Self::Body => "top",
+            Self::Ears => "ear",
+            Self::Neck => "nek",
+            Self::RFinger => "rir",
+            Self::LFinger => "ril",
+            Self::Wrists => "wrs",
+        }
+    }
+
     pub fn abbreviation(&self) -> &str {
         match self {
             Self::Head => "m",
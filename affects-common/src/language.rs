@@ -0,0 +1,44 @@
+use std::str::FromStr;
+
+use serde_repr::{Deserialize_repr, Serialize_repr};
+
+#[derive(
+    Debug, Serialize_repr, Deserialize_repr, Hash, PartialOrd, Ord, PartialEq, Eq, Clone, Copy,
+)]
+#[repr(u8)]
+pub enum Language {
+    English,
+    Japanese,
+    German,
+    French,
+}
+
+impl FromStr for Language {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let language = match s {
+            "en" => Self::English,
+            "ja" => Self::Japanese,
+            "de" => Self::German,
+            "fr" => Self::French,
+
+            _ => return Err(()),
+        };
+
+        Ok(language)
+    }
+}
+
+impl Language {
+    pub const ALL: [Self; 4] = [Self::English, Self::Japanese, Self::German, Self::French];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::English => "en",
+            Self::Japanese => "ja",
+            Self::German => "de",
+            Self::French => "fr",
+        }
+    }
+}
@@ -1,7 +1,20 @@
+mod attributes;
+mod binary;
 mod container;
+mod data;
 mod equip_slot;
+mod language;
+mod metadata;
 
 pub use self::{
-    container::{Affects, ItemKind},
+    attributes::AttributeMask,
+    binary::{AffectsView, BINARY_VERSION, BinaryError},
+    container::{
+        Affects, AttributeMaps, CustomisationMaps, EquipmentTable, ItemInfo, ItemKind,
+        MaterialMaps, ModelSource, ModelSourceKind, ModelTable, NameSet, SoundMaps, VfxMaps,
+    },
+    data::{AffectsData, KeyedTable, ModelQuery, Names},
     equip_slot::EquipSlot,
+    language::Language,
+    metadata::{Metadata, SCHEMA_VERSION, SchemaError},
 };
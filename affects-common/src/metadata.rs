@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+/// the layout of the tables in [`crate::Affects`]. this goes up whenever a
+/// table is added or changes shape, and databases with a different version
+/// are refused
+pub const SCHEMA_VERSION: u32 = 6;
+
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Metadata {
+    /// see [`SCHEMA_VERSION`]. databases from before versioning was added
+    /// have no metadata, which reads as version 0
+    pub schema_version: u32,
+    /// the contents of `ffxivgame.ver` in the install the database was
+    /// generated from, if it could be read
+    pub game_version: Option<String>,
+    /// when the database was generated, in seconds since the unix epoch
+    pub generated_at: u64,
+    /// the version of the generator that produced the database
+    pub generator_version: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum SchemaError {
+    #[error("database predates schema versioning (expected version {SCHEMA_VERSION})")]
+    Unversioned,
+    #[error("database has schema version {found} (expected version {SCHEMA_VERSION})")]
+    Incompatible { found: u32 },
+}
+
+impl Metadata {
+    /// errors if the tables are not laid out the way this build expects
+    pub fn check_schema(&self) -> Result<(), SchemaError> {
+        match self.schema_version {
+            SCHEMA_VERSION => Ok(()),
+            0 => Err(SchemaError::Unversioned),
+            found => Err(SchemaError::Incompatible { found }),
+        }
+    }
+
+    /// whether the database was generated from `game_version`, such as the
+    /// contents of the client's `ffxivgame.ver`. a database with no recorded
+    /// game version is never considered current
+    pub fn is_for_game_version(&self, game_version: &str) -> bool {
+        self.game_version.as_deref().map(str::trim) == Some(game_version.trim())
+    }
+}
@@ -1,26 +1,30 @@
 use affects_common::ItemKind;
+use ironworks::excel::Excel;
 
 use crate::{
-    analysers::GeneratorContext,
-    schema::{Action, ActionCastTimeline, ActionTimeline, MetadataProvider},
+    analysers::{
+        GeneratorContext, localised_row,
+        timelines::{TimelineCache, add_timeline_files},
+        vfx::{add_vfx, vfx_stem},
+    },
+    schema::{Action, ActionCastTimeline, ActionCastVfx, MetadataProvider, Vfx},
 };
 
-pub fn analyse_actions(ctx: &mut GeneratorContext) {
-    let actions = ctx
-        .excel
-        .sheet(MetadataProvider::<Action>::for_sheet())
-        .unwrap();
-    let action_timelines = ctx
-        .excel
-        .sheet(MetadataProvider::<ActionTimeline>::for_sheet())
-        .unwrap();
+pub fn analyse_actions(ctx: &mut GeneratorContext) -> anyhow::Result<()> {
+    let actions = ctx.excel.sheet(MetadataProvider::<Action>::for_sheet())?;
+    let action_timelines = ctx.cache.action_timelines(ctx.excel)?;
     let action_cast_timelines = ctx
         .excel
-        .sheet(MetadataProvider::<ActionCastTimeline>::for_sheet())
-        .unwrap();
+        .sheet(MetadataProvider::<ActionCastTimeline>::for_sheet())?;
+    let action_cast_vfxs = ctx
+        .excel
+        .sheet(MetadataProvider::<ActionCastVfx>::for_sheet())?;
+    let vfxs = ctx.excel.sheet(MetadataProvider::<Vfx>::for_sheet())?;
+
+    let mut timelines = TimelineCache::default();
 
     for action in actions {
-        let action = action.unwrap();
+        let action = action?;
 
         let name = match action.name.format() {
             Ok(name) if !name.is_empty() => name,
@@ -32,36 +36,135 @@ pub fn analyse_actions(ctx: &mut GeneratorContext) {
         //     continue;
         // }
 
-        let start_key = action_cast_timelines
+        let cast_timeline = action_cast_timelines
             .row(action.animation_start as u32)
-            .ok()
-            .and_then(|tl| action_timelines.row(tl.action_timeline as u32).ok())
+            .ok();
+        let start_key = cast_timeline
+            .as_ref()
+            .and_then(|tl| action_timelines.get(&(tl.action_timeline as u32)))
             .and_then(|tl| tl.key.format().ok());
         let end_key = action_timelines
-            .row(action.animation_end as u32)
-            .ok()
+            .get(&(action.animation_end as u32))
             .and_then(|tl| tl.key.format().ok());
         let hit_key = action_timelines
-            .row(action.animation_hit as u32)
-            .ok()
+            .get(&(action.animation_hit as u32))
             .and_then(|tl| tl.key.format().ok());
 
-        let mut add_action = |key: Option<String>, name: &str| {
+        let mut add_action = |key: Option<String>, name: &str| -> anyhow::Result<()> {
             let key = match key {
                 Some(key) if !key.is_empty() => key,
-                _ => return,
+                _ => return Ok(()),
             };
 
-            let name_idx = ctx.get_name_idx(ItemKind::Action, name);
+            let name_idx = ctx.get_name_idx(ItemKind::Action, name)?;
+            ctx.localise_name(name_idx, |excel| {
+                localised_action_name(excel, action.row_id)
+            });
+            if let Some(tmb) = timelines.get(ctx.ironworks, &key) {
+                add_timeline_files(ctx, tmb, ItemKind::Action, name_idx);
+            }
+
             ctx.affects
                 .actions
                 .entry(key)
                 .or_default()
                 .insert((ItemKind::Action, name_idx));
+
+            Ok(())
         };
 
-        add_action(start_key, &name);
-        add_action(end_key, &name);
-        add_action(hit_key, &name);
+        add_action(start_key, &name)?;
+        add_action(end_key, &name)?;
+        add_action(hit_key, &name)?;
+
+        // effects shown while casting, which are referenced directly by the
+        // sheets rather than through a timeline
+        let cast_vfx = action_cast_vfxs
+            .row(action.vfx as u32)
+            .ok()
+            .map(|cast_vfx| cast_vfx.vfx);
+        let cast_timeline_vfx = cast_timeline.map(|tl| tl.vfx);
+
+        for vfx_id in [cast_vfx, cast_timeline_vfx].into_iter().flatten() {
+            if vfx_id == 0 {
+                continue;
+            }
+
+            let stem = vfxs
+                .row(vfx_id as u32)
+                .ok()
+                .and_then(|vfx| vfx.location.format().ok())
+                .and_then(|location| vfx_stem(&location));
+            let stem = match stem {
+                Some(stem) => stem,
+                None => continue,
+            };
+
+            let name_idx = ctx.get_name_idx(ItemKind::Action, name.as_str())?;
+            ctx.localise_name(name_idx, |excel| {
+                localised_action_name(excel, action.row_id)
+            });
+            add_vfx(ctx, stem, ItemKind::Action, name_idx);
+        }
+    }
+
+    Ok(())
+}
+
+fn localised_action_name(excel: &Excel, row_id: u32) -> Option<String> {
+    localised_row::<Action>(excel, row_id)?.name.format().ok()
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{analysers::analyse_actions, test::Fixture};
+
+    #[test]
+    fn action() {
+        let mut fixture = Fixture::default();
+        fixture
+            .row("ActionTimeline", 1, &[(6, "magic/cast_start".into())])
+            .row("ActionTimeline", 2, &[(6, "magic/2ff_sage/mgc024".into())])
+            .row("ActionTimeline", 3, &[(6, "magic/hit_light".into())])
+            .row(
+                "ActionCastTimeline",
+                4,
+                &[(0, 1_u16.into()), (1, 20_u16.into())],
+            )
+            .row("ActionCastVFX", 5, &[(0, 21_u16.into())])
+            .row("VFX", 20, &[(0, "vfx/common/eff/cast_light.avfx".into())])
+            .row("VFX", 21, &[(0, "vfx/common/eff/cast_circle.avfx".into())])
+            .row(
+                "Action",
+                24_290,
+                &[
+                    (0, "Eukrasian Dosis".into()),
+                    (5, 4_u8.into()),
+                    (6, 5_u8.into()),
+                    (7, 2_i16.into()),
+                    (8, 3_u16.into()),
+                ],
+            )
+            .tmb(
+                "magic/2ff_sage/mgc024",
+                &["vfx/action/mgc024/eff/mgc024c0c.avfx"],
+            );
+
+        let output = fixture.run(analyse_actions);
+        let affects = &output.affects;
+        for key in [
+            "magic/cast_start",
+            "magic/2ff_sage/mgc024",
+            "magic/hit_light",
+        ] {
+            assert_eq!(vec!["Eukrasian Dosis"], output.names(&affects.actions[key]));
+        }
+
+        for stem in ["mgc024c0c", "cast_light", "cast_circle"] {
+            assert_eq!(
+                vec!["Eukrasian Dosis"],
+                output.names(&affects.vfx.effects[stem]),
+            );
+        }
     }
 }
@@ -1,57 +1,43 @@
 use std::collections::{BTreeMap, BTreeSet};
 
 use affects_common::ItemKind;
+use path_parser::builder::{DemihumanModel, MonsterModel};
 
 use crate::{
-    analysers::GeneratorContext,
-    formats::imc::{ImcFile, RawImcFile},
-    schema::{
-        BNpcBase, BNpcName, Companion, MetadataProvider, ModelChara, ModelCharaKind, NpcEquip,
-    },
+    analysers::{GeneratorContext, localised_row},
+    cache::ImcError,
+    report::Anomaly,
+    schema::{BNpcBase, BNpcName, Companion, MetadataProvider, ModelCharaKind, NpcEquip},
 };
 
-pub fn analyse_bnpcs(ctx: &mut GeneratorContext) {
+pub fn analyse_bnpcs(ctx: &mut GeneratorContext) -> anyhow::Result<()> {
     let bnpc_bases = ctx
         .excel
-        .sheet(MetadataProvider::<BNpcBase>::for_sheet())
-        .unwrap()
+        .sheet(MetadataProvider::<BNpcBase>::for_sheet())?
         .into_iter()
         .collect::<Vec<_>>();
     let bnpc_names = ctx
         .excel
-        .sheet(MetadataProvider::<BNpcName>::for_sheet())
-        .unwrap()
+        .sheet(MetadataProvider::<BNpcName>::for_sheet())?
         .into_iter()
         .map(|name| name.map(|name| (name.row_id, name)))
-        .collect::<Result<BTreeMap<_, _>, _>>()
-        .unwrap();
-    let model_charas = ctx
-        .excel
-        .sheet(MetadataProvider::<ModelChara>::for_sheet())
-        .unwrap()
-        .into_iter()
-        .map(|mc| mc.map(|mc| (mc.row_id, mc)))
-        .collect::<Result<BTreeMap<_, _>, _>>()
-        .unwrap();
+        .collect::<Result<BTreeMap<_, _>, _>>()?;
+    let model_charas = ctx.cache.model_charas(ctx.excel)?;
     let npc_equips = ctx
         .excel
-        .sheet(MetadataProvider::<NpcEquip>::for_sheet())
-        .unwrap()
+        .sheet(MetadataProvider::<NpcEquip>::for_sheet())?
         .into_iter()
         .map(|equip| equip.map(|equip| (equip.row_id, equip)))
-        .collect::<Result<BTreeMap<_, _>, _>>()
-        .unwrap();
+        .collect::<Result<BTreeMap<_, _>, _>>()?;
     let minions = ctx
         .excel
-        .sheet(MetadataProvider::<Companion>::for_sheet())
-        .unwrap()
+        .sheet(MetadataProvider::<Companion>::for_sheet())?
         .into_iter()
         .map(|minion| minion.map(|minion| minion.model))
-        .collect::<Result<BTreeSet<_>, _>>()
-        .unwrap();
+        .collect::<Result<BTreeSet<_>, _>>()?;
 
     for bnpc in bnpc_bases {
-        let bnpc = bnpc.unwrap();
+        let bnpc = bnpc?;
 
         if minions.contains(&bnpc.model_chara) {
             // we don't need the battle npc minions
@@ -59,6 +45,10 @@ pub fn analyse_bnpcs(ctx: &mut GeneratorContext) {
         }
 
         let model_chara = match model_charas.get(&(bnpc.model_chara as u32)) {
+            Some(mc) if mc.kind == ModelCharaKind::Unknown => {
+                ctx.anomaly(Anomaly::UnknownModelCharaKind { row_id: mc.row_id });
+                continue;
+            }
             Some(mc) if !mc.kind.is_other() => mc,
             _ => continue,
         };
@@ -73,14 +63,20 @@ pub fn analyse_bnpcs(ctx: &mut GeneratorContext) {
                     .get(&(info.bnpc_name))
                     .and_then(|name| name.singular.format().ok())
                     .filter(|name| !name.is_empty())
+                    .map(|name| (info.bnpc_name, name))
             })
-            .map(|name| {
-                (
-                    ItemKind::BattleNpc,
-                    ctx.get_name_idx(ItemKind::BattleNpc, name),
-                )
+            .map(|(name_id, name)| -> anyhow::Result<_> {
+                let name_idx = ctx.get_name_idx(ItemKind::BattleNpc, name)?;
+                ctx.localise_name(name_idx, |excel| {
+                    localised_row::<BNpcName>(excel, name_id)?
+                        .singular
+                        .format()
+                        .ok()
+                });
+
+                Ok((ItemKind::BattleNpc, name_idx))
             })
-            .collect::<Vec<_>>();
+            .collect::<anyhow::Result<Vec<_>>>()?;
 
         if names.is_empty() {
             continue;
@@ -88,22 +84,13 @@ pub fn analyse_bnpcs(ctx: &mut GeneratorContext) {
 
         let mut variant_id = model_chara.variant;
 
-        // need to use the imc file to map this variant_id to the
-        // correct variant_id used in the game path
+        // monster names are kept by imc variant, which should be in the imc
+        // file. demihumans are kept by the material of their equipment
         if model_chara.kind == ModelCharaKind::Monster {
-            let imc = ctx
-                .ironworks
-                .file::<RawImcFile>(&format!(
-                    "chara/monster/m{model:<04}/obj/body/b{base:<04}/b{base:<04}.imc",
-                    model = model_chara.model,
-                    base = model_chara.base,
-                ))
-                .ok()
-                .and_then(ImcFile::try_from_raw);
-            if let Some(imc) = imc {
-                let imc_variant = &imc.parts[0].variants[variant_id as usize - 1];
-                variant_id = imc_variant.material_id;
-            }
+            let imc_path = MonsterModel::new(model_chara.model, model_chara.base as u16)
+                .imc()
+                .to_string();
+            ctx.check_variant(&imc_path, 0, variant_id);
         } else if model_chara.kind == ModelCharaKind::Demihuman {
             // look up all the equipment models and their imc, then store the
             // vfx in the vfx map
@@ -116,20 +103,18 @@ pub fn analyse_bnpcs(ctx: &mut GeneratorContext) {
             for (slot_idx, (gear_model_id, gear_variant_id)) in
                 npc_equip.gear_models().into_iter().enumerate()
             {
-                let imc_path = format!(
-                    "chara/demihuman/d{model_chara:<04}/obj/equipment/e{gear_model:<04}/e{gear_model:<04}.imc",
-                    model_chara = model_chara.model,
-                    gear_model = gear_model_id,
-                );
-
-                let imc = match ctx
-                    .ironworks
-                    .file::<RawImcFile>(&imc_path)
-                    .ok()
-                    .and_then(ImcFile::try_from_raw)
-                {
-                    Some(imc) => imc,
-                    None => continue,
+                let imc_path = DemihumanModel::new(model_chara.model, gear_model_id)
+                    .imc()
+                    .to_string();
+
+                let imc = match ctx.cache.imc(ctx.ironworks, &imc_path) {
+                    Ok(imc) => imc,
+                    // empty slots have no model
+                    Err(ImcError::Missing) if gear_model_id == 0 => continue,
+                    Err(error) => {
+                        ctx.anomaly(error.anomaly(imc_path));
+                        continue;
+                    }
                 };
 
                 let slot_idx = if slot_idx >= 5 {
@@ -138,17 +123,22 @@ pub fn analyse_bnpcs(ctx: &mut GeneratorContext) {
                     slot_idx
                 };
 
-                let imc_slot = &imc.parts[slot_idx];
-                let imc_variant = if gear_variant_id == 0 {
-                    &imc_slot.default_variant
-                } else {
-                    &imc_slot.variants[gear_variant_id as usize - 1]
+                let imc_variant = match imc.variant(slot_idx, gear_variant_id.into()) {
+                    Some(imc_variant) => imc_variant,
+                    None => {
+                        ctx.anomaly(Anomaly::VariantOutOfRange {
+                            path: imc_path,
+                            part: slot_idx,
+                            variant: gear_variant_id,
+                        });
+                        continue;
+                    }
                 };
                 // update the variant id
                 variant_id = imc_variant.material_id;
 
                 // store the vfx => materials mapping
-                for variant in &imc_slot.variants {
+                for variant in &imc.parts[slot_idx].variants {
                     if variant.material_id == 0 || variant.vfx_id == 0 {
                         continue;
                     }
@@ -181,4 +171,123 @@ pub fn analyse_bnpcs(ctx: &mut GeneratorContext) {
             .or_default()
             .extend(names);
     }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use crate::{analysers::analyse_bnpcs, report::Anomaly, test::Fixture};
+
+    #[test]
+    fn monster() {
+        let mut fixture = Fixture::default();
+        fixture
+            .row(
+                "ModelChara",
+                10,
+                &[
+                    (0, 3_u8.into()),
+                    (1, 133_u16.into()),
+                    (2, 2_u8.into()),
+                    (3, 1_u8.into()),
+                ],
+            )
+            .row("BNpcBase", 100, &[(5, 10_u16.into())])
+            .row("BNpcName", 200, &[(0, "Ahriman".into())])
+            .bnpc(100, 200)
+            .imc(
+                "chara/monster/m0133/obj/body/b0002/b0002.imc",
+                &[&[(1, 0), (4, 0)]],
+            );
+
+        let output = fixture.run(analyse_bnpcs);
+        assert_eq!(
+            vec!["Ahriman"],
+            output.names(&output.affects.monsters[&133][&2][&1]),
+        );
+        assert!(output.anomalies.is_empty());
+    }
+
+    #[test]
+    fn demihuman_equipment_vfx() {
+        let mut fixture = Fixture::default();
+        fixture
+            .row(
+                "ModelChara",
+                11,
+                &[
+                    (0, 2_u8.into()),
+                    (1, 1_u16.into()),
+                    (2, 1_u8.into()),
+                    (3, 1_u8.into()),
+                ],
+            )
+            .row("BNpcBase", 101, &[(5, 11_u16.into()), (7, 5_u16.into())])
+            .row("BNpcName", 201, &[(0, "Amalj'aa Lancer".into())])
+            .row("NpcEquip", 5, &[(11, 0x0002_0027_u32.into())])
+            .bnpc(101, 201)
+            .imc(
+                "chara/demihuman/d0001/obj/equipment/e0039/e0039.imc",
+                &[
+                    &[(1, 0), (1, 0), (1, 0)],
+                    &[(1, 0), (1, 0), (3, 7)],
+                    &[(1, 0), (1, 0), (1, 0)],
+                    &[(1, 0), (1, 0), (1, 0)],
+                    &[(1, 0), (1, 0), (1, 0)],
+                ],
+            );
+
+        let output = fixture.run(analyse_bnpcs);
+        let affects = &output.affects;
+        assert_eq!(
+            vec!["Amalj'aa Lancer"],
+            output.names(&affects.demihumans[&1][&1][&3]),
+        );
+        assert_eq!(BTreeSet::from([3]), affects.vfx.demihumans[&1][&39][&7],);
+        assert!(output.anomalies.is_empty());
+    }
+
+    #[test]
+    fn skips_minions_and_unnamed_npcs() {
+        let mut fixture = Fixture::default();
+        fixture
+            .row(
+                "ModelChara",
+                10,
+                &[(0, 3_u8.into()), (1, 133_u16.into()), (2, 2_u8.into())],
+            )
+            .row(
+                "Companion",
+                1,
+                &[(0, "Wind-up Ahriman".into()), (8, 10_u16.into())],
+            )
+            .row("BNpcBase", 100, &[(5, 10_u16.into())])
+            .row("BNpcBase", 101, &[(5, 10_u16.into())])
+            .row("BNpcName", 200, &[(0, "Ahriman".into())])
+            .bnpc(100, 200);
+
+        let output = fixture.run(analyse_bnpcs);
+        assert!(output.affects.monsters.is_empty());
+        assert!(output.anomalies.is_empty());
+    }
+
+    #[test]
+    fn reports_unknown_model_chara_kind() {
+        let mut fixture = Fixture::default();
+        fixture
+            .row("ModelChara", 12, &[(0, 9_u8.into())])
+            .row("BNpcBase", 100, &[(5, 12_u16.into())])
+            .row("BNpcName", 200, &[(0, "Ahriman".into())])
+            .bnpc(100, 200);
+
+        let output = fixture.run(analyse_bnpcs);
+        assert!(output.affects.names.is_empty());
+        assert_eq!(
+            vec![Anomaly::UnknownModelCharaKind { row_id: 12 }],
+            output.anomalies,
+        );
+    }
 }
@@ -0,0 +1,461 @@
+use affects_common::ItemKind;
+use path_parser::{
+    GamePath,
+    builder::CharacterModel,
+    path::chara::{BodyType, BodyTypeSlot, CharacterPath},
+    types::{Gender, ModelInfo, ModelKind, Race},
+};
+
+use crate::{
+    analysers::{GeneratorContext, localised_row},
+    formats::{mtrl::MtrlFile, raw::RawFile},
+    report::Anomaly,
+    schema::{CharaMakeCustomize, CharaMakeType, HairMakeType, Item, MetadataProvider, Tribe},
+};
+
+/// the highest face, tail and ear ids looked for. faces for the second tribe
+/// of a race start from 101
+const MAX_MODEL_ID: u16 = 20;
+
+pub fn analyse_customisations(ctx: &mut GeneratorContext) -> anyhow::Result<()> {
+    let chara_make_types = ctx
+        .excel
+        .sheet(MetadataProvider::<CharaMakeType>::for_sheet())?;
+    let hair_make_types = ctx
+        .excel
+        .sheet(MetadataProvider::<HairMakeType>::for_sheet())?;
+    let customizes = ctx
+        .excel
+        .sheet(MetadataProvider::<CharaMakeCustomize>::for_sheet())?;
+    let tribes = ctx.excel.sheet(MetadataProvider::<Tribe>::for_sheet())?;
+
+    for chara_make_type in chara_make_types {
+        let chara_make_type = chara_make_type?;
+
+        let model_info = match model_info(&chara_make_type) {
+            Some(model_info) => model_info,
+            None => {
+                ctx.anomaly(Anomaly::UnknownCharaMakeType {
+                    row_id: chara_make_type.row_id,
+                });
+                continue;
+            }
+        };
+
+        let tribe = match tribes.row(chara_make_type.tribe as u32) {
+            Ok(tribe) => tribe,
+            Err(_) => {
+                ctx.anomaly(Anomaly::MissingRow {
+                    sheet: "Tribe".into(),
+                    row_id: chara_make_type.tribe as u32,
+                });
+                continue;
+            }
+        };
+
+        let name = match user_name(&tribe, model_info.gender) {
+            Some(name) => name,
+            None => continue,
+        };
+        let user = User {
+            name: &name,
+            tribe: chara_make_type.tribe as u32,
+            gender: model_info.gender,
+        };
+
+        // models, which are found by looking for their files. the tribes of
+        // a race share its models, apart from their faces
+        let face_offset = match model_info.race {
+            Some(Race::Highlander | Race::Midlander) => 0,
+            _ if chara_make_type.tribe % 2 == 0 => 100,
+            _ => 0,
+        };
+
+        for id in (1..=MAX_MODEL_ID).map(|id| id + face_offset) {
+            let face = CharacterModel::new(model_info, BodyType::Face, id);
+            if !exists(ctx, &face.mdl(Some(BodyTypeSlot::Face))) {
+                continue;
+            }
+
+            let name_idx = user.name_idx(ctx, &format!("Face {id}"))?;
+            add_model(ctx, model_info, BodyType::Face, id, name_idx);
+
+            let iris = face.mtrl(Some(BodyTypeSlot::Iris), None).to_string();
+            let eyes = match ctx.ironworks.file::<MtrlFile>(&iris) {
+                Ok(mtrl) => mtrl
+                    .texture_paths()
+                    .filter_map(|path| match GamePath::parse(path) {
+                        Ok(GamePath::Character(CharacterPath::Eye { id, kind })) => {
+                            Some(format!("eye{id:02}_{kind}"))
+                        }
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>(),
+                Err(_) => continue,
+            };
+
+            let name_idx = user.name_idx(ctx, "Eyes")?;
+            for eye in eyes {
+                ctx.affects
+                    .customisations
+                    .eyes
+                    .entry(eye)
+                    .or_default()
+                    .insert((ItemKind::Customisation, name_idx));
+            }
+        }
+
+        for (body_type, slot, kind) in [
+            (BodyType::Tail, BodyTypeSlot::Tail, "Tail"),
+            (BodyType::Ear, BodyTypeSlot::Ear, "Ears"),
+        ] {
+            for id in 1..=MAX_MODEL_ID {
+                let model = CharacterModel::new(model_info, body_type, id);
+                if !exists(ctx, &model.mdl(Some(slot))) {
+                    continue;
+                }
+
+                let name_idx = user.name_idx(ctx, &format!("{kind} {id}"))?;
+                add_model(ctx, model_info, body_type, id, name_idx);
+            }
+        }
+
+        // hairstyles and face paints, which are listed in the sheets along
+        // with the items unlocking them
+        let hair_make_type = match hair_make_types.row(chara_make_type.row_id) {
+            Ok(hair_make_type) => hair_make_type,
+            Err(_) => {
+                ctx.anomaly(Anomaly::MissingRow {
+                    sheet: "HairMakeType".into(),
+                    row_id: chara_make_type.row_id,
+                });
+                continue;
+            }
+        };
+
+        let hairstyles = hair_make_type.hair_start_index
+            ..hair_make_type.hair_start_index + HairMakeType::HAIRSTYLES;
+        for customize in hairstyles.filter_map(|row_id| customizes.row(row_id).ok()) {
+            if customize.feature_id == 0 {
+                continue;
+            }
+
+            let id = customize.feature_id as u16;
+            let name_idx = user.name_idx(ctx, &format!("Hair {id}"))?;
+            add_model(ctx, model_info, BodyType::Hair, id, name_idx);
+
+            if let Some(name_idx) = unlock_item(ctx, &customize)? {
+                add_model(ctx, model_info, BodyType::Hair, id, name_idx);
+            }
+        }
+
+        let face_paints = hair_make_type.face_paint_start_index
+            ..hair_make_type.face_paint_start_index + HairMakeType::FACE_PAINTS;
+        for customize in face_paints.filter_map(|row_id| customizes.row(row_id).ok()) {
+            if customize.feature_id == 0 {
+                continue;
+            }
+
+            let id = customize.feature_id as u16;
+            let name_idx = user.name_idx(ctx, &format!("Face Paint {id}"))?;
+            let unlocked_by = unlock_item(ctx, &customize)?;
+
+            let users = ctx
+                .affects
+                .customisations
+                .face_paints
+                .entry(id)
+                .or_default();
+            users.insert((ItemKind::Customisation, name_idx));
+            users.extend(unlocked_by.map(|name_idx| (ItemKind::Customisation, name_idx)));
+        }
+    }
+
+    Ok(())
+}
+
+/// the gender and tribe a customisation is for, such as "Female Seeker of
+/// the Sun". `None` if the tribe has no name for the gender
+fn user_name(tribe: &Tribe, gender: Gender) -> Option<String> {
+    let tribe = match gender {
+        Gender::Male => tribe.masculine.format(),
+        Gender::Female => tribe.feminine.format(),
+    };
+
+    match tribe {
+        Ok(tribe) if !tribe.is_empty() => Some(format!("{gender} {tribe}")),
+        _ => None,
+    }
+}
+
+/// who the customisations of a CharaMakeType row are for
+struct User<'a> {
+    name: &'a str,
+    tribe: u32,
+    gender: Gender,
+}
+
+impl User<'_> {
+    /// the name index of a customisation of this user, such as "Face 1".
+    /// translated using the names the tribe has in each language
+    fn name_idx(&self, ctx: &mut GeneratorContext, customisation: &str) -> anyhow::Result<u32> {
+        let name_idx = ctx.get_name_idx(
+            ItemKind::Customisation,
+            format!("{} {customisation}", self.name),
+        )?;
+        ctx.localise_name(name_idx, |excel| {
+            let tribe = localised_row::<Tribe>(excel, self.tribe)?;
+            Some(format!(
+                "{} {customisation}",
+                user_name(&tribe, self.gender)?
+            ))
+        });
+
+        Ok(name_idx)
+    }
+}
+
+/// the model a CharaMakeType row is for
+fn model_info(chara_make_type: &CharaMakeType) -> Option<ModelInfo> {
+    let race = match (chara_make_type.race, chara_make_type.tribe) {
+        (1, 1) => Race::Midlander,
+        (1, 2) => Race::Highlander,
+        (2, _) => Race::Elezen,
+        (3, _) => Race::Lalafell,
+        (4, _) => Race::Miqote,
+        (5, _) => Race::Roegadyn,
+        (6, _) => Race::AuRa,
+        (7, _) => Race::Hrothgar,
+        (8, _) => Race::Viera,
+        _ => return None,
+    };
+
+    let gender = match chara_make_type.gender {
+        0 => Gender::Male,
+        1 => Gender::Female,
+        _ => return None,
+    };
+
+    Some(ModelInfo {
+        race: Some(race),
+        gender,
+        kind: ModelKind::Adult,
+    })
+}
+
+fn exists(ctx: &GeneratorContext, path: &GamePath) -> bool {
+    ctx.ironworks.file::<RawFile>(&path.to_string()).is_ok()
+}
+
+fn add_model(
+    ctx: &mut GeneratorContext,
+    model_info: ModelInfo,
+    body_type: BodyType,
+    id: u16,
+    name_idx: u32,
+) {
+    let raw_id = match model_info.to_raw_id() {
+        Some(raw_id) => raw_id,
+        None => return,
+    };
+
+    ctx.affects
+        .customisations
+        .models
+        .entry(raw_id)
+        .or_default()
+        .entry(body_type.as_str().to_string())
+        .or_default()
+        .entry(id)
+        .or_default()
+        .insert((ItemKind::Customisation, name_idx));
+}
+
+/// the name index of the item unlocking a customisation, if it needs one
+fn unlock_item(
+    ctx: &mut GeneratorContext,
+    customize: &CharaMakeCustomize,
+) -> anyhow::Result<Option<u32>> {
+    if customize.hint_item == 0 {
+        return Ok(None);
+    }
+
+    let items = ctx.cache.items(ctx.excel)?;
+    let name = match items.get(&customize.hint_item) {
+        Some(item) => match item.name.format() {
+            Ok(name) => name,
+            Err(_) => return Ok(None),
+        },
+        None => {
+            ctx.anomaly(Anomaly::MissingRow {
+                sheet: "Item".into(),
+                row_id: customize.hint_item,
+            });
+            return Ok(None);
+        }
+    };
+
+    if name.is_empty() {
+        return Ok(None);
+    }
+
+    let name_idx = ctx.get_name_idx(ItemKind::Customisation, name)?;
+    ctx.localise_name(name_idx, |excel| {
+        localised_row::<Item>(excel, customize.hint_item)?
+            .name
+            .format()
+            .ok()
+    });
+
+    Ok(Some(name_idx))
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{analysers::analyse_customisations, report::Anomaly, test::Fixture};
+
+    /// female miqo'te of both tribes
+    fn miqote(fixture: &mut Fixture) -> &mut Fixture {
+        fixture
+            .row(
+                "CharaMakeType",
+                7,
+                &[(0, 4_i32.into()), (1, 7_i32.into()), (2, 1_i8.into())],
+            )
+            .row(
+                "CharaMakeType",
+                8,
+                &[(0, 4_i32.into()), (1, 8_i32.into()), (2, 1_i8.into())],
+            )
+            .row(
+                "Tribe",
+                7,
+                &[
+                    (0, "Seeker of the Sun".into()),
+                    (1, "Seeker of the Sun".into()),
+                ],
+            )
+            .row(
+                "Tribe",
+                8,
+                &[
+                    (0, "Keeper of the Moon".into()),
+                    (1, "Keeper of the Moon".into()),
+                ],
+            )
+            .row(
+                "HairMakeType",
+                7,
+                &[(0, 100_u32.into()), (1, 200_u32.into())],
+            )
+            .row(
+                "HairMakeType",
+                8,
+                &[(0, 300_u32.into()), (1, 400_u32.into())],
+            )
+    }
+
+    #[test]
+    fn models() {
+        let mut fixture = Fixture::default();
+        miqote(&mut fixture)
+            .file(
+                "chara/human/c0801/obj/face/f0001/model/c0801f0001_fac.mdl",
+                Vec::new(),
+            )
+            .file(
+                "chara/human/c0801/obj/face/f0101/model/c0801f0101_fac.mdl",
+                Vec::new(),
+            )
+            .file(
+                "chara/human/c0801/obj/face/f0001/material/mt_c0801f0001_iri_a.mtrl",
+                b"\x01\x00\x00chara/common/texture/eye/eye09_base.tex\0\
+                  chara/human/c0801/obj/face/f0001/texture/c0801f0001_iri_s.tex\0\
+                  shader.shpk\0"
+                    .to_vec(),
+            )
+            .file(
+                "chara/human/c0801/obj/tail/t0002/model/c0801t0002_til.mdl",
+                Vec::new(),
+            );
+
+        let output = fixture.run(analyse_customisations);
+        let models = &output.affects.customisations.models[&801];
+        assert_eq!(
+            vec!["Female Seeker of the Sun Face 1"],
+            output.names(&models["face"][&1]),
+        );
+        // the second tribe of a race has faces of its own
+        assert_eq!(
+            vec!["Female Keeper of the Moon Face 101"],
+            output.names(&models["face"][&101]),
+        );
+        assert_eq!(2, models["face"].len());
+        assert_eq!(
+            vec!["Female Seeker of the Sun Eyes"],
+            output.names(&output.affects.customisations.eyes["eye09_base"]),
+        );
+        assert_eq!(1, output.affects.customisations.eyes.len());
+        assert_eq!(
+            vec![
+                "Female Seeker of the Sun Tail 2",
+                "Female Keeper of the Moon Tail 2",
+            ],
+            output.names(&models["tail"][&2]),
+        );
+        assert!(output.anomalies.is_empty());
+    }
+
+    #[test]
+    fn hairstyles_and_face_paints() {
+        let mut fixture = Fixture::default();
+        miqote(&mut fixture)
+            .row("CharaMakeCustomize", 100, &[(0, 1_u8.into())])
+            .row(
+                "CharaMakeCustomize",
+                101,
+                &[(0, 130_u8.into()), (5, 5_000_u32.into())],
+            )
+            .row("CharaMakeCustomize", 200, &[(0, 3_u8.into())])
+            .row("CharaMakeCustomize", 300, &[(0, 1_u8.into())])
+            .row("Item", 5_000, &[(9, "Modern Aesthetics - Curls".into())]);
+
+        let output = fixture.run(analyse_customisations);
+        let hair = &output.affects.customisations.models[&801]["hair"];
+        assert_eq!(
+            vec![
+                "Female Seeker of the Sun Hair 1",
+                "Female Keeper of the Moon Hair 1",
+            ],
+            output.names(&hair[&1]),
+        );
+        assert_eq!(
+            vec![
+                "Female Seeker of the Sun Hair 130",
+                "Modern Aesthetics - Curls"
+            ],
+            output.names(&hair[&130]),
+        );
+        assert_eq!(
+            vec!["Female Seeker of the Sun Face Paint 3"],
+            output.names(&output.affects.customisations.face_paints[&3]),
+        );
+        assert!(output.anomalies.is_empty());
+    }
+
+    #[test]
+    fn reports_unknown_races() {
+        let mut fixture = Fixture::default();
+        fixture.row(
+            "CharaMakeType",
+            1,
+            &[(0, 9_i32.into()), (1, 17_i32.into()), (2, 0_i8.into())],
+        );
+
+        let output = fixture.run(analyse_customisations);
+        assert_eq!(
+            vec![Anomaly::UnknownCharaMakeType { row_id: 1 }],
+            output.anomalies,
+        );
+    }
+}
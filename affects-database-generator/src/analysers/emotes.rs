@@ -1,31 +1,29 @@
 use affects_common::ItemKind;
 
 use crate::{
-    analysers::GeneratorContext,
-    schema::{ActionTimeline, Emote, MetadataProvider, TextCommand},
+    analysers::{
+        GeneratorContext, localised_row,
+        timelines::{TimelineCache, add_timeline_files},
+    },
+    schema::{Emote, MetadataProvider, TextCommand},
 };
 
-pub fn analyse_emotes(ctx: &mut GeneratorContext) {
-    let emotes = ctx
-        .excel
-        .sheet(MetadataProvider::<Emote>::for_sheet())
-        .unwrap();
-    let action_timelines = ctx
-        .excel
-        .sheet(MetadataProvider::<ActionTimeline>::for_sheet())
-        .unwrap();
+pub fn analyse_emotes(ctx: &mut GeneratorContext) -> anyhow::Result<()> {
+    let emotes = ctx.excel.sheet(MetadataProvider::<Emote>::for_sheet())?;
+    let action_timelines = ctx.cache.action_timelines(ctx.excel)?;
     let text_commands = ctx
         .excel
-        .sheet(MetadataProvider::<TextCommand>::for_sheet())
-        .unwrap();
+        .sheet(MetadataProvider::<TextCommand>::for_sheet())?;
+
+    let mut timelines = TimelineCache::default();
 
     for emote in emotes {
-        let emote = emote.unwrap();
+        let emote = emote?;
 
-        let name = emote.name.format().unwrap();
-        if name.is_empty() {
-            continue;
-        }
+        let name = match emote.name.format() {
+            Ok(name) if !name.is_empty() => name,
+            _ => continue,
+        };
 
         let command = if emote.text_command == 0 {
             None
@@ -36,14 +34,18 @@ pub fn analyse_emotes(ctx: &mut GeneratorContext) {
                 .and_then(|tc| tc.command.format().ok())
         };
 
-        let key = emote
+        let keys = emote
             .action_timelines
             .iter()
-            .find(|&&id| id != 0)
-            .and_then(|&id| action_timelines.row(id as u32).ok())
-            .and_then(|tl| tl.key.format().ok());
+            .filter(|&&id| id != 0)
+            .filter_map(|&id| action_timelines.get(&(id as u32)))
+            .filter_map(|tl| tl.key.format().ok())
+            .collect::<Vec<_>>();
 
-        let key = match key.and_then(|key| key.split('/').next_back().map(ToString::to_string)) {
+        let key = match keys
+            .first()
+            .and_then(|key| key.split('/').next_back().map(ToString::to_string))
+        {
             Some(key) => key,
             None => continue,
         };
@@ -52,11 +54,78 @@ pub fn analyse_emotes(ctx: &mut GeneratorContext) {
             .map(|command| format!("{name} ({command})"))
             .unwrap_or(name);
 
-        let name_idx = ctx.get_name_idx(ItemKind::Emote, name);
+        let name_idx = ctx.get_name_idx(ItemKind::Emote, name)?;
+        ctx.localise_name(name_idx, |excel| {
+            let name = localised_row::<Emote>(excel, emote.row_id)?
+                .name
+                .format()
+                .ok()?;
+            let command = if emote.text_command == 0 {
+                None
+            } else {
+                localised_row::<TextCommand>(excel, emote.text_command as u32)
+                    .and_then(|tc| tc.command.format().ok())
+            };
+
+            Some(
+                command
+                    .map(|command| format!("{name} ({command})"))
+                    .unwrap_or(name),
+            )
+        });
+
+        for key in &keys {
+            if let Some(tmb) = timelines.get(ctx.ironworks, key) {
+                add_timeline_files(ctx, tmb, ItemKind::Emote, name_idx);
+            }
+        }
+
         ctx.affects
             .emotes
             .entry(key)
             .or_default()
             .insert((ItemKind::Emote, name_idx));
     }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{analysers::analyse_emotes, test::Fixture};
+
+    #[test]
+    fn emote() {
+        let mut fixture = Fixture::default();
+        fixture
+            .row("ActionTimeline", 10, &[(6, "emote/dance".into())])
+            .row("TextCommand", 5, &[(5, "/dance".into())])
+            .row(
+                "Emote",
+                1,
+                &[(0, "Dance".into()), (1, 10_u16.into()), (19, 5_i32.into())],
+            )
+            .row("Emote", 2, &[(0, "Unused".into())])
+            .tmb(
+                "emote/dance",
+                &["vfx/common/eff/dance01.avfx", "sound/emote/SE_Dance.scd"],
+            );
+
+        let output = fixture.run(analyse_emotes);
+        let affects = &output.affects;
+        assert_eq!(
+            vec!["Dance (/dance)"],
+            output.names(&affects.emotes["dance"])
+        );
+        assert_eq!(
+            vec!["Dance (/dance)"],
+            output.names(&affects.vfx.effects["dance01"]),
+        );
+        assert_eq!(
+            vec!["Dance (/dance)"],
+            output.names(&affects.sounds.timelines["sound/emote/se_dance.scd"]),
+        );
+        // emotes without a timeline are skipped
+        assert_eq!(1, affects.emotes.len());
+    }
 }
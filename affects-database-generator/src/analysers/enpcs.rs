@@ -1,41 +1,37 @@
 use std::collections::BTreeMap;
 
 use affects_common::ItemKind;
+use path_parser::builder::{DemihumanModel, MonsterModel};
 
 use crate::{
-    analysers::GeneratorContext,
-    formats::imc::{ImcFile, RawImcFile},
-    schema::{ENpcBase, ENpcResident, MetadataProvider, ModelChara, ModelCharaKind},
+    analysers::{GeneratorContext, localised_row},
+    cache::ImcError,
+    report::Anomaly,
+    schema::{ENpcBase, ENpcResident, MetadataProvider, ModelCharaKind},
 };
 
-pub fn analyse_enpcs(ctx: &mut GeneratorContext) {
+pub fn analyse_enpcs(ctx: &mut GeneratorContext) -> anyhow::Result<()> {
     let enpc_bases = ctx
         .excel
-        .sheet(MetadataProvider::<ENpcBase>::for_sheet())
-        .unwrap()
+        .sheet(MetadataProvider::<ENpcBase>::for_sheet())?
         .into_iter()
         .collect::<Vec<_>>();
     let enpc_residents = ctx
         .excel
-        .sheet(MetadataProvider::<ENpcResident>::for_sheet())
-        .unwrap()
+        .sheet(MetadataProvider::<ENpcResident>::for_sheet())?
         .into_iter()
         .map(|res| res.map(|res| (res.row_id, res)))
-        .collect::<Result<BTreeMap<_, _>, _>>()
-        .unwrap();
-    let model_charas = ctx
-        .excel
-        .sheet(MetadataProvider::<ModelChara>::for_sheet())
-        .unwrap()
-        .into_iter()
-        .map(|mc| mc.map(|mc| (mc.row_id, mc)))
-        .collect::<Result<BTreeMap<_, _>, _>>()
-        .unwrap();
+        .collect::<Result<BTreeMap<_, _>, _>>()?;
+    let model_charas = ctx.cache.model_charas(ctx.excel)?;
 
     for enpc in enpc_bases {
-        let enpc = enpc.unwrap();
+        let enpc = enpc?;
 
         let model_chara = match model_charas.get(&(enpc.model_chara as u32)) {
+            Some(mc) if mc.kind == ModelCharaKind::Unknown => {
+                ctx.anomaly(Anomaly::UnknownModelCharaKind { row_id: mc.row_id });
+                continue;
+            }
             Some(mc) if !mc.kind.is_other() => mc,
             _ => continue,
         };
@@ -60,21 +56,11 @@ pub fn analyse_enpcs(ctx: &mut GeneratorContext) {
         let mut variant_id = model_chara.variant;
 
         if model_chara.kind == ModelCharaKind::Monster {
-            // need to use the imc file to map this variant_id to the
-            // correct variant_id used in the game path
-            let imc = ctx
-                .ironworks
-                .file::<RawImcFile>(&format!(
-                    "chara/monster/m{model:<04}/obj/body/b{base:<04}/b{base:<04}.imc",
-                    model = model_chara.model,
-                    base = model_chara.base,
-                ))
-                .ok()
-                .and_then(ImcFile::try_from_raw);
-            if let Some(imc) = imc {
-                let imc_variant = &imc.parts[0].variants[variant_id as usize - 1];
-                variant_id = imc_variant.material_id;
-            }
+            // names are kept by imc variant, which should be in the imc file
+            let imc_path = MonsterModel::new(model_chara.model, model_chara.base as u16)
+                .imc()
+                .to_string();
+            ctx.check_variant(&imc_path, 0, variant_id);
         } else if model_chara.kind == ModelCharaKind::Demihuman {
             // look up all the equipment models and their imc, then store the
             // vfx in the vfx map
@@ -82,20 +68,18 @@ pub fn analyse_enpcs(ctx: &mut GeneratorContext) {
             for (slot_idx, (gear_model_id, gear_variant_id)) in
                 enpc.gear_models().into_iter().enumerate()
             {
-                let imc_path = format!(
-                    "chara/demihuman/d{model_chara:<04}/obj/equipment/e{gear_model:<04}/e{gear_model:<04}.imc",
-                    model_chara = model_chara.model,
-                    gear_model = gear_model_id,
-                );
-
-                let imc = match ctx
-                    .ironworks
-                    .file::<RawImcFile>(&imc_path)
-                    .ok()
-                    .and_then(ImcFile::try_from_raw)
-                {
-                    Some(imc) => imc,
-                    None => continue,
+                let imc_path = DemihumanModel::new(model_chara.model, gear_model_id)
+                    .imc()
+                    .to_string();
+
+                let imc = match ctx.cache.imc(ctx.ironworks, &imc_path) {
+                    Ok(imc) => imc,
+                    // empty slots have no model
+                    Err(ImcError::Missing) if gear_model_id == 0 => continue,
+                    Err(error) => {
+                        ctx.anomaly(error.anomaly(imc_path));
+                        continue;
+                    }
                 };
 
                 let slot_idx = if slot_idx >= 5 {
@@ -104,17 +88,22 @@ pub fn analyse_enpcs(ctx: &mut GeneratorContext) {
                     slot_idx
                 };
 
-                let imc_slot = &imc.parts[slot_idx];
-                let imc_variant = if gear_variant_id == 0 {
-                    &imc_slot.default_variant
-                } else {
-                    &imc_slot.variants[gear_variant_id as usize - 1]
+                let imc_variant = match imc.variant(slot_idx, gear_variant_id.into()) {
+                    Some(imc_variant) => imc_variant,
+                    None => {
+                        ctx.anomaly(Anomaly::VariantOutOfRange {
+                            path: imc_path,
+                            part: slot_idx,
+                            variant: gear_variant_id,
+                        });
+                        continue;
+                    }
                 };
                 // update the variant id
                 variant_id = imc_variant.material_id;
 
                 // store the vfx => materials mapping
-                for variant in &imc_slot.variants {
+                for variant in &imc.parts[slot_idx].variants {
                     if variant.material_id == 0 || variant.vfx_id == 0 {
                         continue;
                     }
@@ -133,7 +122,14 @@ pub fn analyse_enpcs(ctx: &mut GeneratorContext) {
             }
         }
 
-        let name_idx = ctx.get_name_idx(ItemKind::EventNpc, name);
+        let name_idx = ctx.get_name_idx(ItemKind::EventNpc, name)?;
+        ctx.localise_name(name_idx, |excel| {
+            localised_row::<ENpcResident>(excel, resident.row_id)?
+                .singular
+                .format()
+                .ok()
+        });
+
         let map = match model_chara.kind {
             ModelCharaKind::Demihuman => &mut ctx.affects.demihumans,
             ModelCharaKind::Monster => &mut ctx.affects.monsters,
@@ -148,4 +144,111 @@ pub fn analyse_enpcs(ctx: &mut GeneratorContext) {
             .or_default()
             .insert((ItemKind::EventNpc, name_idx));
     }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use crate::{analysers::analyse_enpcs, test::Fixture};
+
+    #[test]
+    fn monster() {
+        let mut fixture = Fixture::default();
+        fixture
+            .row(
+                "ModelChara",
+                10,
+                &[
+                    (0, 3_u8.into()),
+                    (1, 8_u16.into()),
+                    (2, 1_u8.into()),
+                    (3, 2_u8.into()),
+                ],
+            )
+            .row("ENpcBase", 1_000_001, &[(35, 10_u16.into())])
+            .row(
+                "ENpcResident",
+                1_000_001,
+                &[
+                    (0, "Wandering Chocobo".into()),
+                    (2, "wandering chocobos".into()),
+                ],
+            )
+            .imc(
+                "chara/monster/m0008/obj/body/b0001/b0001.imc",
+                &[&[(1, 0), (1, 0), (5, 0)]],
+            );
+
+        let output = fixture.run(analyse_enpcs);
+        assert_eq!(
+            vec!["Wandering Chocobo"],
+            output.names(&output.affects.monsters[&8][&1][&2]),
+        );
+        assert!(output.anomalies.is_empty());
+    }
+
+    #[test]
+    fn demihuman_equipment_vfx() {
+        let mut fixture = Fixture::default();
+        fixture
+            .row(
+                "ModelChara",
+                11,
+                &[
+                    (0, 2_u8.into()),
+                    (1, 2_u16.into()),
+                    (2, 1_u8.into()),
+                    (3, 1_u8.into()),
+                ],
+            )
+            .row(
+                "ENpcBase",
+                1_000_002,
+                &[(35, 11_u16.into()), (76, 0x0001_0004_u32.into())],
+            )
+            .row(
+                "ENpcResident",
+                1_000_002,
+                &[(0, "Kobold Miner".into()), (2, "kobold miners".into())],
+            )
+            .imc(
+                "chara/demihuman/d0002/obj/equipment/e0004/e0004.imc",
+                &[
+                    &[(1, 0), (1, 0)],
+                    &[(1, 0), (2, 3)],
+                    &[(1, 0), (1, 0)],
+                    &[(1, 0), (1, 0)],
+                    &[(1, 0), (1, 0)],
+                ],
+            );
+
+        let output = fixture.run(analyse_enpcs);
+        let affects = &output.affects;
+        assert_eq!(
+            vec!["Kobold Miner"],
+            output.names(&affects.demihumans[&2][&1][&2]),
+        );
+        assert_eq!(BTreeSet::from([2]), affects.vfx.demihumans[&2][&4][&3]);
+        assert!(output.anomalies.is_empty());
+    }
+
+    #[test]
+    fn skips_npcs_without_a_plural_name() {
+        let mut fixture = Fixture::default();
+        fixture
+            .row(
+                "ModelChara",
+                10,
+                &[(0, 3_u8.into()), (1, 8_u16.into()), (2, 1_u8.into())],
+            )
+            .row("ENpcBase", 1_000_001, &[(35, 10_u16.into())])
+            .row("ENpcResident", 1_000_001, &[(0, "Cid".into())]);
+
+        let output = fixture.run(analyse_enpcs);
+        assert!(output.affects.names.is_empty());
+        assert!(output.anomalies.is_empty());
+    }
 }
@@ -1,42 +1,90 @@
+use std::collections::BTreeSet;
+
 use affects_common::EquipSlot;
+use path_parser::builder::EquipmentModel;
 
 use crate::{
-    analysers::GeneratorContext,
-    formats::imc::{ImcFile, RawImcFile},
+    analysers::{
+        GeneratorContext,
+        imc::{record_variants, variants},
+    },
+    report::Anomaly,
 };
 
-pub fn analyse_equipment_imcs(ctx: &mut GeneratorContext) {
-    for primaries in ctx.affects.equipment.values() {
-        for &primary_id in primaries.keys() {
-            let imc = match ctx
-                .ironworks
-                .file::<RawImcFile>(&format!(
-                    "chara/equipment/e{primary_id:<04}/e{primary_id:<04}.imc"
-                ))
-                .ok()
-                .and_then(ImcFile::try_from_raw)
-            {
-                Some(imc) => imc,
-                None => {
-                    continue;
+/// the slot of each part of an equipment imc file
+const PART_SLOTS: [EquipSlot; 5] = [
+    EquipSlot::Head,
+    EquipSlot::Body,
+    EquipSlot::Hands,
+    EquipSlot::Legs,
+    EquipSlot::Feet,
+];
+
+pub fn analyse_equipment_imcs(ctx: &mut GeneratorContext) -> anyhow::Result<()> {
+    // every slot of a model shares the one imc file. accessories have their
+    // own, so are not read here
+    let primary_ids = PART_SLOTS
+        .iter()
+        .filter_map(|slot| ctx.affects.equipment.get(slot))
+        .flat_map(|primaries| primaries.keys().copied())
+        .collect::<BTreeSet<_>>();
+
+    for primary_id in primary_ids {
+        let imc_path = EquipmentModel::new(EquipSlot::Body, primary_id)
+            .imc()
+            .to_string();
+        let imc = match ctx.imc(&imc_path) {
+            Some(imc) => imc,
+            None => continue,
+        };
+
+        if imc.parts.len() > PART_SLOTS.len() {
+            ctx.anomalies.push(Anomaly::TooManyParts {
+                path: imc_path,
+                parts: imc.parts.len(),
+            });
+            continue;
+        }
+
+        for (part, slot) in imc.parts.iter().zip(PART_SLOTS) {
+            record_variants(
+                ctx.affects
+                    .attributes
+                    .equipment
+                    .entry(slot)
+                    .or_default()
+                    .entry(primary_id)
+                    .or_default(),
+                ctx.affects
+                    .materials
+                    .equipment
+                    .entry(slot)
+                    .or_default()
+                    .entry(primary_id)
+                    .or_default(),
+                part,
+            );
+
+            let materials = &mut ctx.affects.materials;
+            for (variant_id, variant) in variants(part) {
+                let user = (slot, primary_id, variant_id);
+                if variant.decal_id != 0 {
+                    materials
+                        .decals
+                        .entry(variant.decal_id)
+                        .or_default()
+                        .insert(user);
                 }
-            };
 
-            for (part_idx, part) in imc.parts.iter().enumerate() {
-                let slot = match part_idx {
-                    0 => EquipSlot::Head,
-                    1 => EquipSlot::Body,
-                    2 => EquipSlot::Hands,
-                    3 => EquipSlot::Legs,
-                    4 => EquipSlot::Feet,
-                    _ => panic!("too many parts"),
-                };
-
-                for variant in &part.variants {
-                    if variant.material_id == 0 || variant.vfx_id == 0 {
-                        continue;
-                    }
+                if variant.material_animation_id() != 0 {
+                    materials
+                        .animations
+                        .entry(variant.material_animation_id())
+                        .or_default()
+                        .insert(user);
+                }
 
+                if variant.vfx_id != 0 {
                     ctx.affects
                         .vfx
                         .equipment
@@ -44,9 +92,164 @@ pub fn analyse_equipment_imcs(ctx: &mut GeneratorContext) {
                         .or_default()
                         .entry(variant.vfx_id)
                         .or_default()
-                        .insert((slot, variant.material_id));
+                        .insert((slot, variant_id));
                 }
             }
         }
     }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use affects_common::{Affects, AttributeMask, EquipSlot};
+    use imc::{ImageChangeData, ImageChangeParts, ImcFile, PartMask};
+
+    use crate::{analysers::imc::analyse_equipment_imcs, report::Anomaly, test::Fixture};
+
+    fn affects_with_model(model: u16) -> Affects {
+        let mut affects = Affects::default();
+        affects
+            .equipment
+            .entry(EquipSlot::Body)
+            .or_default()
+            .entry(model)
+            .or_default();
+        affects
+    }
+
+    #[test]
+    fn vfx() {
+        let mut fixture = Fixture::default();
+        fixture.imc(
+            "chara/equipment/e0005/e0005.imc",
+            &[
+                &[(1, 0), (1, 0), (2, 0)],
+                &[(1, 0), (1, 4), (2, 4)],
+                &[(1, 0), (1, 0), (2, 0)],
+                &[(1, 0), (1, 0), (2, 0)],
+                &[(1, 0), (0, 6), (3, 6)],
+            ],
+        );
+
+        let output = fixture.run_on(affects_with_model(5), analyse_equipment_imcs);
+        let vfx = &output.affects.vfx.equipment[&5];
+        assert_eq!(
+            BTreeSet::from([(EquipSlot::Body, 1), (EquipSlot::Body, 2)]),
+            vfx[&4],
+        );
+        // variants without a material are skipped
+        assert_eq!(BTreeSet::from([(EquipSlot::Feet, 2)]), vfx[&6]);
+        assert!(output.anomalies.is_empty());
+    }
+
+    #[test]
+    fn attributes() {
+        let entry = |material_id, attribute_mask| {
+            let mut entry = ImageChangeData {
+                material_id,
+                ..Default::default()
+            };
+            entry.set_attribute_mask(attribute_mask).unwrap();
+            entry
+        };
+
+        let mut parts = vec![ImageChangeParts::default(); 5];
+        parts[1] = ImageChangeParts {
+            default_variant: entry(1, 0b001),
+            variants: vec![entry(1, 0b010), entry(2, 0b100), entry(0, 0b1000)],
+        };
+
+        let mut fixture = Fixture::default();
+        fixture.imc_file(
+            "chara/equipment/e0005/e0005.imc",
+            &ImcFile {
+                part_mask: PartMask::EQUIPMENT,
+                parts,
+            },
+        );
+
+        let output = fixture.run_on(affects_with_model(5), analyse_equipment_imcs);
+        let attributes = &output.affects.attributes.equipment[&EquipSlot::Body][&5];
+        // imc variants sharing a material keep their own attributes
+        assert_eq!(
+            BTreeMap::from([
+                (0, AttributeMask(0b001)),
+                (1, AttributeMask(0b010)),
+                (2, AttributeMask(0b100)),
+            ]),
+            *attributes,
+        );
+        assert!(output.affects.attributes.equipment[&EquipSlot::Head][&5].is_empty());
+
+        let materials = &output.affects.materials.equipment[&EquipSlot::Body][&5];
+        assert_eq!(BTreeMap::from([(0, 1), (1, 1), (2, 2)]), *materials);
+    }
+
+    #[test]
+    fn decals_and_material_animations() {
+        let mut animated = ImageChangeData {
+            material_id: 2,
+            ..Default::default()
+        };
+        animated.set_material_animation_id(3).unwrap();
+
+        let mut parts = vec![ImageChangeParts::default(); 5];
+        parts[0] = ImageChangeParts {
+            default_variant: ImageChangeData {
+                material_id: 1,
+                decal_id: 12,
+                ..Default::default()
+            },
+            variants: vec![
+                animated,
+                // variants without a material are skipped
+                ImageChangeData {
+                    decal_id: 13,
+                    ..Default::default()
+                },
+            ],
+        };
+
+        let mut fixture = Fixture::default();
+        fixture.imc_file(
+            "chara/equipment/e0005/e0005.imc",
+            &ImcFile {
+                part_mask: PartMask::EQUIPMENT,
+                parts,
+            },
+        );
+
+        let output = fixture.run_on(affects_with_model(5), analyse_equipment_imcs);
+        let materials = &output.affects.materials;
+        assert_eq!(
+            BTreeSet::from([(EquipSlot::Head, 5, 0)]),
+            materials.decals[&12],
+        );
+        assert_eq!(1, materials.decals.len());
+        assert_eq!(
+            BTreeSet::from([(EquipSlot::Head, 5, 1)]),
+            materials.animations[&3],
+        );
+    }
+
+    #[test]
+    fn reports_too_many_parts() {
+        let part: &[(u8, u8)] = &[(1, 0), (1, 1)];
+        let mut fixture = Fixture::default();
+        fixture.imc("chara/equipment/e0005/e0005.imc", &[part; 6]);
+
+        let output = fixture.run_on(affects_with_model(5), analyse_equipment_imcs);
+        assert!(output.affects.vfx.equipment.is_empty());
+        assert_eq!(
+            vec![Anomaly::TooManyParts {
+                path: "chara/equipment/e0005/e0005.imc".into(),
+                parts: 6,
+            }],
+            output.anomalies,
+        );
+    }
 }
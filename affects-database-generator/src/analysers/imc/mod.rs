@@ -1,3 +1,8 @@
+use std::collections::BTreeMap;
+
+use affects_common::AttributeMask;
+use imc::{ImageChangeData, ImageChangeParts};
+
 mod equipment;
 mod monsters;
 mod weapons;
@@ -5,3 +10,24 @@ mod weapons;
 pub use self::{
     equipment::analyse_equipment_imcs, monsters::analyse_monster_imcs, weapons::analyse_weapon_imcs,
 };
+
+/// every variant of a part and its id, skipping the ones without a material
+fn variants(part: &ImageChangeParts) -> impl Iterator<Item = (u8, &ImageChangeData)> {
+    std::iter::once(&part.default_variant)
+        .chain(&part.variants)
+        .enumerate()
+        .map_while(|(id, variant)| Some((u8::try_from(id).ok()?, variant)))
+        .filter(|(_, variant)| variant.material_id != 0)
+}
+
+/// adds the attributes and material each imc variant of a part shows
+fn record_variants(
+    attributes: &mut BTreeMap<u8, AttributeMask>,
+    materials: &mut BTreeMap<u8, u8>,
+    part: &ImageChangeParts,
+) {
+    for (id, variant) in variants(part) {
+        attributes.insert(id, AttributeMask(variant.attribute_mask()));
+        materials.insert(id, variant.material_id);
+    }
+}
@@ -1,43 +1,104 @@
-use crate::{
-    analysers::GeneratorContext,
-    formats::imc::{ImcFile, RawImcFile},
+use path_parser::builder::MonsterModel;
+
+use crate::analysers::{
+    GeneratorContext,
+    imc::{record_variants, variants},
 };
 
-pub fn analyse_monster_imcs(ctx: &mut GeneratorContext) {
-    for (&model_id, bases) in &ctx.affects.monsters {
-        for &base_id in bases.keys() {
-            let imc = match ctx
-                .ironworks
-                .file::<RawImcFile>(&format!(
-                    "chara/monster/m{model:<04}/obj/body/b{base:<04}/b{base:<04}.imc",
-                    model = model_id,
-                    base = base_id,
-                ))
-                .ok()
-                .and_then(ImcFile::try_from_raw)
-            {
-                Some(imc) => imc,
-                None => continue,
-            };
-
-            for part in imc.parts {
-                for variant in part.variants {
-                    if variant.material_id == 0 || variant.vfx_id == 0 {
-                        continue;
-                    }
-
-                    ctx.affects
-                        .vfx
-                        .monsters
-                        .entry(model_id)
-                        .or_default()
-                        .entry(base_id)
-                        .or_default()
-                        .entry(variant.vfx_id)
-                        .or_default()
-                        .insert(variant.material_id);
+pub fn analyse_monster_imcs(ctx: &mut GeneratorContext) -> anyhow::Result<()> {
+    let models = ctx
+        .affects
+        .monsters
+        .iter()
+        .flat_map(|(&model_id, bases)| bases.keys().map(move |&base_id| (model_id, base_id)))
+        .collect::<Vec<_>>();
+
+    for (model_id, base_id) in models {
+        let imc_path = MonsterModel::new(model_id, base_id as u16)
+            .imc()
+            .to_string();
+        let imc = match ctx.imc(&imc_path) {
+            Some(imc) => imc,
+            None => continue,
+        };
+
+        for part in &imc.parts {
+            record_variants(
+                ctx.affects
+                    .attributes
+                    .monsters
+                    .entry(model_id)
+                    .or_default()
+                    .entry(base_id)
+                    .or_default(),
+                ctx.affects
+                    .materials
+                    .monsters
+                    .entry(model_id)
+                    .or_default()
+                    .entry(base_id)
+                    .or_default(),
+                part,
+            );
+
+            for (variant_id, variant) in variants(part) {
+                if variant.vfx_id == 0 {
+                    continue;
                 }
+
+                ctx.affects
+                    .vfx
+                    .monsters
+                    .entry(model_id)
+                    .or_default()
+                    .entry(base_id)
+                    .or_default()
+                    .entry(variant.vfx_id)
+                    .or_default()
+                    .insert(variant_id);
             }
         }
     }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use affects_common::Affects;
+
+    use crate::{analysers::imc::analyse_monster_imcs, test::Fixture};
+
+    #[test]
+    fn vfx() {
+        let mut affects = Affects::default();
+        affects
+            .monsters
+            .entry(133)
+            .or_default()
+            .entry(2)
+            .or_default();
+        // models without an imc file are skipped
+        affects
+            .monsters
+            .entry(134)
+            .or_default()
+            .entry(1)
+            .or_default();
+
+        let mut fixture = Fixture::default();
+        fixture.imc(
+            "chara/monster/m0133/obj/body/b0002/b0002.imc",
+            &[&[(1, 0), (1, 0), (2, 5)]],
+        );
+
+        let output = fixture.run_on(affects, analyse_monster_imcs);
+        assert_eq!(
+            BTreeSet::from([2]),
+            output.affects.vfx.monsters[&133][&2][&5],
+        );
+        assert_eq!(1, output.affects.vfx.monsters.len());
+    }
 }
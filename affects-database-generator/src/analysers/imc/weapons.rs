@@ -1,41 +1,138 @@
-use crate::{
-    analysers::GeneratorContext,
-    formats::imc::{ImcFile, RawImcFile},
+use path_parser::builder::WeaponModel;
+
+use crate::analysers::{
+    GeneratorContext,
+    imc::{record_variants, variants},
 };
 
-pub fn analyse_weapon_imcs(ctx: &mut GeneratorContext) {
-    for (&model_id, weapons) in &ctx.affects.weapons {
-        for &weapon_id in weapons.keys() {
-            let imc = match ctx
-                .ironworks
-                .file::<RawImcFile>(&format!(
-                    "chara/weapon/w{model_id:<04}/obj/body/b{weapon_id:<04}/b{weapon_id:<04}.imc"
-                ))
-                .ok()
-                .and_then(ImcFile::try_from_raw)
-            {
-                Some(imc) => imc,
-                None => continue,
-            };
-
-            for part in imc.parts {
-                for variant in part.variants {
-                    if variant.material_id == 0 || variant.vfx_id == 0 {
-                        continue;
-                    }
-
-                    ctx.affects
-                        .vfx
-                        .weapons
-                        .entry(model_id)
-                        .or_default()
-                        .entry(weapon_id as u8)
-                        .or_default()
-                        .entry(variant.vfx_id)
-                        .or_default()
-                        .insert(variant.material_id);
+pub fn analyse_weapon_imcs(ctx: &mut GeneratorContext) -> anyhow::Result<()> {
+    let models = ctx
+        .affects
+        .weapons
+        .iter()
+        .flat_map(|(&model_id, weapons)| {
+            weapons.keys().map(move |&weapon_id| (model_id, weapon_id))
+        })
+        .collect::<Vec<_>>();
+
+    for (model_id, weapon_id) in models {
+        let imc_path = WeaponModel::new(model_id, weapon_id).imc().to_string();
+        let imc = match ctx.imc(&imc_path) {
+            Some(imc) => imc,
+            None => continue,
+        };
+
+        for part in &imc.parts {
+            record_variants(
+                ctx.affects
+                    .attributes
+                    .weapons
+                    .entry(model_id)
+                    .or_default()
+                    .entry(weapon_id)
+                    .or_default(),
+                ctx.affects
+                    .materials
+                    .weapons
+                    .entry(model_id)
+                    .or_default()
+                    .entry(weapon_id)
+                    .or_default(),
+                part,
+            );
+
+            for (variant_id, variant) in variants(part) {
+                if variant.vfx_id == 0 {
+                    continue;
                 }
+
+                ctx.affects
+                    .vfx
+                    .weapons
+                    .entry(model_id)
+                    .or_default()
+                    .entry(weapon_id as u8)
+                    .or_default()
+                    .entry(variant.vfx_id)
+                    .or_default()
+                    .insert(variant_id);
             }
         }
     }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use affects_common::Affects;
+    use imc::ImcFile;
+
+    use crate::{analysers::imc::analyse_weapon_imcs, report::Anomaly, test::Fixture};
+
+    #[test]
+    fn vfx() {
+        let mut affects = Affects::default();
+        affects
+            .weapons
+            .entry(201)
+            .or_default()
+            .entry(1)
+            .or_default();
+
+        let mut fixture = Fixture::default();
+        fixture.imc(
+            "chara/weapon/w0201/obj/body/b0001/b0001.imc",
+            &[&[(1, 0), (1, 2), (2, 2), (3, 0)]],
+        );
+
+        let output = fixture.run_on(affects, analyse_weapon_imcs);
+        assert_eq!(
+            BTreeSet::from([1, 2]),
+            output.affects.vfx.weapons[&201][&1][&2],
+        );
+        assert_eq!(1, output.affects.vfx.weapons[&201][&1].len());
+        assert_eq!(
+            BTreeMap::from([(0, 1), (1, 1), (2, 2), (3, 3)]),
+            output.affects.materials.weapons[&201][&1],
+        );
+    }
+
+    #[test]
+    fn missing_and_invalid_imcs() {
+        let mut affects = Affects::default();
+        let weapons = affects.weapons.entry(201).or_default();
+        for weapon in 1..=3 {
+            weapons.entry(weapon).or_default();
+        }
+
+        // two weapons sharing the same broken file
+        let data = [1, 2, 3];
+        let mut fixture = Fixture::default();
+        fixture
+            .file("chara/weapon/w0201/obj/body/b0002/b0002.imc", data)
+            .file("chara/weapon/w0201/obj/body/b0003/b0003.imc", data);
+
+        let output = fixture.run_on(affects, analyse_weapon_imcs);
+        let error = ImcFile::from_bytes(&data).unwrap_err().to_string();
+        assert_eq!(
+            vec![
+                Anomaly::MissingImc {
+                    path: "chara/weapon/w0201/obj/body/b0001/b0001.imc".into(),
+                },
+                Anomaly::InvalidImc {
+                    path: "chara/weapon/w0201/obj/body/b0002/b0002.imc".into(),
+                    error: error.clone(),
+                },
+                Anomaly::InvalidImc {
+                    path: "chara/weapon/w0201/obj/body/b0003/b0003.imc".into(),
+                    error,
+                },
+            ],
+            output.anomalies,
+        );
+        assert!(output.affects.attributes.weapons.is_empty());
+    }
 }
@@ -1,32 +1,25 @@
 use std::collections::BTreeMap;
 
-use affects_common::{EquipSlot, ItemKind};
+use affects_common::{EquipSlot, ItemInfo, ItemKind, ModelSource};
+use ironworks::excel::Excel;
+use path_parser::builder::{EquipmentModel, WeaponModel};
 
 use crate::{
-    analysers::GeneratorContext,
-    formats::imc::{ImcFile, RawImcFile},
+    analysers::{GeneratorContext, localised_row},
+    report::Anomaly,
     schema::{EquipSlotCategory, Item, MetadataProvider},
 };
 
-pub fn analyse_items(ctx: &mut GeneratorContext) {
-    let items = ctx
-        .excel
-        .sheet(MetadataProvider::<Item>::for_sheet())
-        .unwrap()
-        .into_iter()
-        .collect::<Vec<_>>();
+pub fn analyse_items(ctx: &mut GeneratorContext) -> anyhow::Result<()> {
+    let items = ctx.cache.items(ctx.excel)?;
     let equip_slot_categories = ctx
         .excel
-        .sheet(MetadataProvider::<EquipSlotCategory>::for_sheet())
-        .unwrap()
+        .sheet(MetadataProvider::<EquipSlotCategory>::for_sheet())?
         .into_iter()
         .map(|esc| esc.map(|esc| (esc.row_id, esc)))
-        .collect::<Result<BTreeMap<_, _>, _>>()
-        .unwrap();
-
-    for item in items {
-        let item = item.unwrap();
+        .collect::<Result<BTreeMap<_, _>, _>>()?;
 
+    for item in items.values() {
         let name = match item.name.format() {
             Ok(name) if !name.is_empty() => name,
             _ => continue,
@@ -39,37 +32,34 @@ pub fn analyse_items(ctx: &mut GeneratorContext) {
         let esc = if item.equip_slot_category == 0 {
             continue;
         } else {
-            equip_slot_categories
-                .get(&(item.equip_slot_category as u32))
-                .unwrap()
+            match equip_slot_categories.get(&(item.equip_slot_category as u32)) {
+                Some(esc) => esc,
+                None => {
+                    ctx.anomaly(Anomaly::MissingRow {
+                        sheet: "EquipSlotCategory".into(),
+                        row_id: item.equip_slot_category as u32,
+                    });
+                    continue;
+                }
+            }
         };
 
         match EquipSlot::try_from(esc) {
             Ok(slot) => {
                 // equipment
                 let model_id = (item.model_main & 0xFFFF) as u16;
-                let mut variant_id = ((item.model_main >> 16) & 0xFF) as u8;
-
-                // need to use the imc file to map this variant_id to the
-                // correct variant_id used in the game path
-                let imc_path = if slot.is_accessory() {
-                    format!("chara/accessory/a{model_id:<04}/a{model_id:<04}.imc")
-                } else {
-                    format!("chara/equipment/e{model_id:<04}/e{model_id:<04}.imc")
-                };
-                let imc = ctx
-                    .ironworks
-                    .file::<RawImcFile>(&imc_path)
-                    .ok()
-                    .and_then(ImcFile::try_from_raw);
-                if let Some(imc) = imc
-                    && let Some(part_idx) = slot.to_imc_part_idx()
-                {
-                    let imc_variant = &imc.parts[part_idx].variants[variant_id as usize - 1];
-                    variant_id = imc_variant.material_id;
+                let variant_id = ((item.model_main >> 16) & 0xFF) as u8;
+
+                // names are kept by imc variant, which should be in the imc
+                // file
+                let imc_path = EquipmentModel::new(slot, model_id).imc().to_string();
+                if let Some(part_idx) = slot.to_imc_part_idx() {
+                    ctx.check_variant(&imc_path, part_idx, variant_id);
                 }
 
-                let name_idx = ctx.get_name_idx(ItemKind::Gear, &name);
+                let name_idx = ctx.get_name_idx(ItemKind::Gear, &name)?;
+                ctx.localise_name(name_idx, |excel| localised_item_name(excel, item.row_id));
+
                 ctx.affects
                     .equipment
                     .entry(slot)
@@ -81,6 +71,16 @@ pub fn analyse_items(ctx: &mut GeneratorContext) {
                     .insert((ItemKind::Gear, name_idx));
 
                 ctx.affects.item_ids.entry(item.row_id).or_insert(name_idx);
+                add_item_info(
+                    ctx,
+                    name_idx,
+                    item,
+                    ModelSource::Equipment {
+                        slot,
+                        model: model_id,
+                        variant: variant_id,
+                    },
+                );
 
                 let other_ring = match slot {
                     EquipSlot::LFinger => Some(EquipSlot::RFinger),
@@ -100,6 +100,16 @@ pub fn analyse_items(ctx: &mut GeneratorContext) {
                         .insert((ItemKind::Gear, name_idx));
 
                     ctx.affects.item_ids.entry(item.row_id).or_insert(name_idx);
+                    add_item_info(
+                        ctx,
+                        name_idx,
+                        item,
+                        ModelSource::Equipment {
+                            slot,
+                            model: model_id,
+                            variant: variant_id,
+                        },
+                    );
                 }
             }
             Err(()) => {
@@ -110,22 +120,16 @@ pub fn analyse_items(ctx: &mut GeneratorContext) {
 
                 let model_id = (item.model_main & 0xFFFF) as u16;
                 let weapon_id = ((item.model_main >> 16) & 0xFFFF) as u16;
-                let mut variant_id = ((item.model_main >> 32) & 0xFF) as u8;
-
-                // need to use the imc file to map this variant_id to the
-                // correct variant_id used in the game path
-                let imc = ctx.ironworks
-                    .file::<RawImcFile>(&format!(
-                        "chara/weapon/w{model_id:<04}/obj/body/b{weapon_id:<04}/b{weapon_id:<04}.imc"
-                    ))
-                    .ok()
-                    .and_then(ImcFile::try_from_raw);
-                if let Some(imc) = imc {
-                    let imc_variant = &imc.parts[0].variants[variant_id as usize - 1];
-                    variant_id = imc_variant.material_id;
-                }
+                let variant_id = ((item.model_main >> 32) & 0xFF) as u8;
+
+                // names are kept by imc variant, which should be in the imc
+                // file
+                let imc_path = WeaponModel::new(model_id, weapon_id).imc().to_string();
+                ctx.check_variant(&imc_path, 0, variant_id);
+
+                let name_idx = ctx.get_name_idx(ItemKind::Weapon, &name)?;
+                ctx.localise_name(name_idx, |excel| localised_item_name(excel, item.row_id));
 
-                let name_idx = ctx.get_name_idx(ItemKind::Weapon, &name);
                 ctx.affects
                     .weapons
                     .entry(model_id)
@@ -136,6 +140,18 @@ pub fn analyse_items(ctx: &mut GeneratorContext) {
                     .or_default()
                     .insert((ItemKind::Weapon, name_idx));
 
+                ctx.affects.item_ids.entry(item.row_id).or_insert(name_idx);
+                add_item_info(
+                    ctx,
+                    name_idx,
+                    item,
+                    ModelSource::Weapon {
+                        model: model_id,
+                        weapon: weapon_id,
+                        variant: variant_id,
+                    },
+                );
+
                 // https://github.com/xivapi/ffxiv-datamining/blob/master/csv/ItemUICategory.csv
                 let append = match item.item_ui_category {
                     // MNK
@@ -189,7 +205,11 @@ pub fn analyse_items(ctx: &mut GeneratorContext) {
                 let weapon = ((item.model_sub >> 16) & 0xFFFF) as u16;
                 let variant = ((item.model_sub >> 32) & 0xFF) as u8;
 
-                let name_idx = ctx.get_name_idx(ItemKind::Weapon, format!("{name}{append}"));
+                let name_idx = ctx.get_name_idx(ItemKind::Weapon, format!("{name}{append}"))?;
+                ctx.localise_name(name_idx, |excel| {
+                    localised_item_name(excel, item.row_id).map(|name| format!("{name}{append}"))
+                });
+
                 ctx.affects
                     .weapons
                     .entry(model)
@@ -199,7 +219,240 @@ pub fn analyse_items(ctx: &mut GeneratorContext) {
                     .entry(variant)
                     .or_default()
                     .insert((ItemKind::Weapon, name_idx));
+                add_item_info(
+                    ctx,
+                    name_idx,
+                    item,
+                    ModelSource::Weapon {
+                        model,
+                        weapon,
+                        variant,
+                    },
+                );
             }
         }
     }
+
+    Ok(())
+}
+
+fn localised_item_name(excel: &Excel, row_id: u32) -> Option<String> {
+    localised_row::<Item>(excel, row_id)?.name.format().ok()
+}
+
+/// records that `item` is displayed with `source`, so items sharing a name
+/// can be told apart by the model a path belongs to
+fn add_item_info(ctx: &mut GeneratorContext, name_idx: u32, item: &Item, source: ModelSource) {
+    let items = ctx.affects.items.entry(name_idx).or_default();
+    let info = match items
+        .iter_mut()
+        .position(|info| info.item_id == item.row_id)
+    {
+        Some(idx) => &mut items[idx],
+        None => {
+            items.push(ItemInfo {
+                item_id: item.row_id,
+                icon: item.icon as u32,
+                models: Vec::new(),
+            });
+            items.last_mut().unwrap()
+        }
+    };
+
+    if !info.models.contains(&source) {
+        info.models.push(source);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use affects_common::{EquipSlot, ModelSource};
+
+    use crate::{analysers::analyse_items, report::Anomaly, test::Fixture};
+
+    /// adds equip slot categories for a main hand weapon (1), body armour (4)
+    /// and a left ring (12)
+    fn slot_categories(fixture: &mut Fixture) -> &mut Fixture {
+        fixture
+            .row("EquipSlotCategory", 1, &[(0, 1i8.into())])
+            .row("EquipSlotCategory", 4, &[(3, 1i8.into())])
+            .row("EquipSlotCategory", 12, &[(11, 1i8.into())])
+    }
+
+    #[test]
+    fn equipment() {
+        let mut fixture = Fixture::default();
+        slot_categories(&mut fixture)
+            .row(
+                "Item",
+                1,
+                &[
+                    (9, "Iron Cuirass".into()),
+                    (10, 40_u16.into()),
+                    (17, 4_u8.into()),
+                    (47, 0x0002_0005_u64.into()),
+                ],
+            )
+            .imc(
+                "chara/equipment/e0005/e0005.imc",
+                &[
+                    &[(1, 0), (1, 0), (1, 0)],
+                    &[(1, 0), (1, 0), (3, 0)],
+                    &[(1, 0), (1, 0), (1, 0)],
+                    &[(1, 0), (1, 0), (1, 0)],
+                    &[(1, 0), (1, 0), (1, 0)],
+                ],
+            );
+
+        let output = fixture.run(analyse_items);
+        let affects = &output.affects;
+        assert_eq!(
+            vec!["Iron Cuirass"],
+            output.names(&affects.equipment[&EquipSlot::Body][&5][&2]),
+        );
+        assert_eq!(Some(&0), affects.item_ids.get(&1));
+        assert_eq!(40, affects.items[&0][0].icon);
+        assert_eq!(
+            vec![ModelSource::Equipment {
+                slot: EquipSlot::Body,
+                model: 5,
+                variant: 2,
+            }],
+            affects.items[&0][0].models,
+        );
+        assert!(output.anomalies.is_empty());
+    }
+
+    #[test]
+    fn rings_fit_either_finger() {
+        let mut fixture = Fixture::default();
+        slot_categories(&mut fixture)
+            .row(
+                "Item",
+                1,
+                &[
+                    (9, "Iron Ring".into()),
+                    (17, 12_u8.into()),
+                    (47, 0x0001_0010_u64.into()),
+                ],
+            )
+            .imc(
+                "chara/accessory/a0016/a0016.imc",
+                &[
+                    &[(1, 0), (1, 0)],
+                    &[(1, 0), (1, 0)],
+                    &[(1, 0), (1, 0)],
+                    &[(1, 0), (1, 0)],
+                    &[(1, 0), (2, 0)],
+                ],
+            );
+
+        let output = fixture.run(analyse_items);
+        for slot in [EquipSlot::LFinger, EquipSlot::RFinger] {
+            assert_eq!(
+                vec!["Iron Ring"],
+                output.names(&output.affects.equipment[&slot][&16][&1]),
+            );
+        }
+        assert_eq!(2, output.affects.items[&0][0].models.len());
+    }
+
+    #[test]
+    fn weapon_and_offhand() {
+        let mut fixture = Fixture::default();
+        slot_categories(&mut fixture)
+            .row(
+                "Item",
+                1,
+                &[
+                    (9, "Iron Kunai".into()),
+                    (15, 84_u8.into()),
+                    (17, 1_u8.into()),
+                    (47, 0x0001_0001_00C9_u64.into()),
+                    (48, 0x0001_0001_00FB_u64.into()),
+                ],
+            )
+            .imc(
+                "chara/weapon/w0201/obj/body/b0001/b0001.imc",
+                &[&[(1, 0), (2, 0)]],
+            );
+
+        let output = fixture.run(analyse_items);
+        let affects = &output.affects;
+        assert_eq!(
+            vec!["Iron Kunai"],
+            output.names(&affects.weapons[&201][&1][&1]),
+        );
+        assert_eq!(
+            vec!["Iron Kunai (Offhand)"],
+            output.names(&affects.weapons[&251][&1][&1]),
+        );
+        // the item is looked up by its main hand name
+        assert_eq!(Some(&0), affects.item_ids.get(&1));
+        assert_eq!(1, affects.items[&1][0].item_id);
+        assert!(output.anomalies.is_empty());
+    }
+
+    #[test]
+    fn skips_dated_and_unequippable_items() {
+        let mut fixture = Fixture::default();
+        slot_categories(&mut fixture)
+            .row(
+                "Item",
+                1,
+                &[
+                    (9, "Dated Iron Cuirass".into()),
+                    (17, 4_u8.into()),
+                    (47, 0x0001_0005_u64.into()),
+                ],
+            )
+            .row("Item", 2, &[(9, "Iron Ore".into())]);
+
+        let output = fixture.run(analyse_items);
+        assert!(output.affects.names.is_empty());
+        assert!(output.anomalies.is_empty());
+    }
+
+    #[test]
+    fn reports_missing_data() {
+        let mut fixture = Fixture::default();
+        slot_categories(&mut fixture)
+            .row(
+                "Item",
+                1,
+                &[
+                    (9, "Iron Cuirass".into()),
+                    (17, 4_u8.into()),
+                    (47, 0x0002_0005_u64.into()),
+                ],
+            )
+            .row(
+                "Item",
+                2,
+                &[
+                    (9, "Iron Bracelets".into()),
+                    (17, 9_u8.into()),
+                    (47, 0x0001_0005_u64.into()),
+                ],
+            );
+
+        let output = fixture.run(analyse_items);
+        // the variant is kept without an imc file to check it against
+        assert_eq!(
+            vec!["Iron Cuirass"],
+            output.names(&output.affects.equipment[&EquipSlot::Body][&5][&2]),
+        );
+        assert_eq!(
+            vec![
+                Anomaly::MissingImc {
+                    path: "chara/equipment/e0005/e0005.imc".into(),
+                },
+                Anomaly::MissingRow {
+                    sheet: "EquipSlotCategory".into(),
+                    row_id: 9,
+                },
+            ],
+            output.anomalies,
+        );
+    }
 }
@@ -1,77 +1,147 @@
 use affects_common::ItemKind;
 
 use crate::{
-    analysers::GeneratorContext,
+    analysers::{GeneratorContext, localised_row},
     schema::{Map, MetadataProvider, PlaceName},
 };
 
-pub fn analyse_maps(ctx: &mut GeneratorContext) {
-    let maps = ctx
-        .excel
-        .sheet(MetadataProvider::<Map>::for_sheet())
-        .unwrap();
+pub fn analyse_maps(ctx: &mut GeneratorContext) -> anyhow::Result<()> {
+    let maps = ctx.excel.sheet(MetadataProvider::<Map>::for_sheet())?;
     let place_names = ctx
         .excel
-        .sheet(MetadataProvider::<PlaceName>::for_sheet())
-        .unwrap();
+        .sheet(MetadataProvider::<PlaceName>::for_sheet())?;
 
     for map in maps {
-        let map = map.unwrap();
+        let map = map?;
 
         let id = match map.id.format() {
             Ok(id) if !id.is_empty() => id,
             _ => continue,
         };
 
-        let place_name_region = place_names
-            .row(map.place_name_region as u32)
-            .ok()
-            .and_then(|pn| pn.name.format().ok())
-            .and_then(|name| if name.is_empty() { None } else { Some(name) });
-        let place_name = place_names
-            .row(map.place_name as u32)
-            .ok()
-            .and_then(|pn| pn.name.format().ok())
-            .and_then(|name| if name.is_empty() { None } else { Some(name) });
-        let place_name_sub = place_names
-            .row(map.place_name_sub as u32)
-            .ok()
-            .and_then(|pn| pn.name.format().ok())
-            .and_then(|name| if name.is_empty() { None } else { Some(name) });
-
-        let mut name = String::new();
-        if let Some(region) = &place_name_region {
-            name.push_str(region);
-        }
-
-        if let Some(pn) = &place_name {
-            if !name.is_empty() {
-                name.push_str(" - ");
-            }
-
-            name.push_str(pn);
-        }
-
-        if let Some(sub) = &place_name_sub
-            && place_name_sub != place_name
-        {
-            let empty = name.is_empty();
-            if !empty {
-                name.push_str(" (");
-            }
+        let place_name = |id: u16| {
+            place_names
+                .row(id as u32)
+                .ok()
+                .and_then(|pn| pn.name.format().ok())
+        };
+        let name = map_name(
+            place_name(map.place_name_region),
+            place_name(map.place_name),
+            place_name(map.place_name_sub),
+        );
 
-            name.push_str(sub);
+        let name_idx = ctx.get_name_idx(ItemKind::Map, name)?;
+        ctx.localise_name(name_idx, |excel| {
+            let place_name = |id: u16| {
+                localised_row::<PlaceName>(excel, id as u32).and_then(|pn| pn.name.format().ok())
+            };
 
-            if !empty {
-                name.push(')');
-            }
-        }
+            Some(map_name(
+                place_name(map.place_name_region),
+                place_name(map.place_name),
+                place_name(map.place_name_sub),
+            ))
+        });
 
-        let name_idx = ctx.get_name_idx(ItemKind::Map, name);
         ctx.affects
             .maps
             .entry(id)
             .or_default()
             .insert((ItemKind::Map, name_idx));
     }
+
+    Ok(())
+}
+
+/// builds a name like "Region - Place (Sub-place)" from whichever of the
+/// place names exist
+fn map_name(
+    place_name_region: Option<String>,
+    place_name: Option<String>,
+    place_name_sub: Option<String>,
+) -> String {
+    let non_empty = |name: Option<String>| name.filter(|name| !name.is_empty());
+    let place_name_region = non_empty(place_name_region);
+    let place_name = non_empty(place_name);
+    let place_name_sub = non_empty(place_name_sub);
+
+    let mut name = String::new();
+    if let Some(region) = &place_name_region {
+        name.push_str(region);
+    }
+
+    if let Some(pn) = &place_name {
+        if !name.is_empty() {
+            name.push_str(" - ");
+        }
+
+        name.push_str(pn);
+    }
+
+    if let Some(sub) = &place_name_sub
+        && place_name_sub != place_name
+    {
+        let empty = name.is_empty();
+        if !empty {
+            name.push_str(" (");
+        }
+
+        name.push_str(sub);
+
+        if !empty {
+            name.push(')');
+        }
+    }
+
+    name
+}
+
+#[cfg(test)]
+mod test {
+    use super::map_name;
+    use crate::{analysers::analyse_maps, test::Fixture};
+
+    #[test]
+    fn map() {
+        let mut fixture = Fixture::default();
+        fixture
+            .row("PlaceName", 1, &[(0, "La Noscea".into())])
+            .row("PlaceName", 2, &[(0, "Limsa Lominsa".into())])
+            .row("PlaceName", 3, &[(0, "Upper Decks".into())])
+            .row(
+                "Map",
+                1,
+                &[
+                    (6, "s1t1/00".into()),
+                    (10, 1_u16.into()),
+                    (11, 2_u16.into()),
+                    (12, 3_u16.into()),
+                ],
+            )
+            .row("Map", 2, &[(10, 1_u16.into())]);
+
+        let output = fixture.run(analyse_maps);
+        assert_eq!(
+            vec!["La Noscea - Limsa Lominsa (Upper Decks)"],
+            output.names(&output.affects.maps["s1t1/00"]),
+        );
+        // maps without an id are skipped
+        assert_eq!(1, output.affects.maps.len());
+    }
+
+    #[test]
+    fn names() {
+        let name = |region: &str, place: &str, sub: &str| {
+            map_name(Some(region.into()), Some(place.into()), Some(sub.into()))
+        };
+
+        assert_eq!(
+            "La Noscea - Limsa Lominsa",
+            name("La Noscea", "Limsa Lominsa", "")
+        );
+        assert_eq!("The Goblet", name("", "", "The Goblet"));
+        assert_eq!("Thanalan - Ul'dah", name("Thanalan", "Ul'dah", "Ul'dah"));
+        assert_eq!("Coerthas (Ishgard)", name("Coerthas", "", "Ishgard"));
+    }
 }
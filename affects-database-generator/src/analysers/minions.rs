@@ -1,26 +1,27 @@
 use affects_common::ItemKind;
+use path_parser::builder::MonsterModel;
 
 use crate::{
-    analysers::GeneratorContext,
-    formats::imc::{ImcFile, RawImcFile},
-    schema::{Companion, MetadataProvider, ModelChara, ModelCharaKind},
+    analysers::{GeneratorContext, localised_row},
+    report::Anomaly,
+    schema::{Companion, MetadataProvider, ModelCharaKind},
 };
 
-pub fn analyse_minions(ctx: &mut GeneratorContext) {
+pub fn analyse_minions(ctx: &mut GeneratorContext) -> anyhow::Result<()> {
     let minions = ctx
         .excel
-        .sheet(MetadataProvider::<Companion>::for_sheet())
-        .unwrap();
-    let model_charas = ctx
-        .excel
-        .sheet(MetadataProvider::<ModelChara>::for_sheet())
-        .unwrap();
+        .sheet(MetadataProvider::<Companion>::for_sheet())?;
+    let model_charas = ctx.cache.model_charas(ctx.excel)?;
 
     for minion in minions {
-        let minion = minion.unwrap();
+        let minion = minion?;
 
-        let model_chara = match model_charas.row(minion.model as u32) {
-            Ok(mc) if !mc.kind.is_other() => mc,
+        let model_chara = match model_charas.get(&(minion.model as u32)) {
+            Some(mc) if mc.kind == ModelCharaKind::Unknown => {
+                ctx.anomaly(Anomaly::UnknownModelCharaKind { row_id: mc.row_id });
+                continue;
+            }
+            Some(mc) if !mc.kind.is_other() => mc,
             _ => continue,
         };
 
@@ -29,27 +30,24 @@ pub fn analyse_minions(ctx: &mut GeneratorContext) {
             _ => continue,
         };
 
-        let mut variant_id = model_chara.variant;
+        let variant_id = model_chara.variant;
 
-        // need to use the imc file to map this variant_id to the
-        // correct variant_id used in the game path
+        // names are kept by imc variant, which should be in the imc file
         if model_chara.kind == ModelCharaKind::Monster {
-            let imc = ctx
-                .ironworks
-                .file::<RawImcFile>(&format!(
-                    "chara/monster/m{model:<04}/obj/body/b{base:<04}/b{base:<04}.imc",
-                    model = model_chara.model,
-                    base = model_chara.base,
-                ))
-                .ok()
-                .and_then(ImcFile::try_from_raw);
-            if let Some(imc) = imc {
-                let imc_variant = &imc.parts[0].variants[variant_id as usize - 1];
-                variant_id = imc_variant.material_id;
-            }
+            let imc_path = MonsterModel::new(model_chara.model, model_chara.base as u16)
+                .imc()
+                .to_string();
+            ctx.check_variant(&imc_path, 0, variant_id);
         }
 
-        let name_idx = ctx.get_name_idx(ItemKind::Minion, name);
+        let name_idx = ctx.get_name_idx(ItemKind::Minion, name)?;
+        ctx.localise_name(name_idx, |excel| {
+            localised_row::<Companion>(excel, minion.row_id)?
+                .singular
+                .format()
+                .ok()
+        });
+
         let map = match model_chara.kind {
             ModelCharaKind::Demihuman => &mut ctx.affects.demihumans,
             ModelCharaKind::Monster => &mut ctx.affects.monsters,
@@ -64,4 +62,97 @@ pub fn analyse_minions(ctx: &mut GeneratorContext) {
             .or_default()
             .insert((ItemKind::Minion, name_idx));
     }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{analysers::analyse_minions, report::Anomaly, test::Fixture};
+
+    #[test]
+    fn monster_and_demihuman() {
+        let mut fixture = Fixture::default();
+        fixture
+            .row(
+                "ModelChara",
+                10,
+                &[
+                    (0, 3_u8.into()),
+                    (1, 7_001_u16.into()),
+                    (2, 1_u8.into()),
+                    (3, 1_u8.into()),
+                ],
+            )
+            .row(
+                "ModelChara",
+                11,
+                &[
+                    (0, 2_u8.into()),
+                    (1, 1_002_u16.into()),
+                    (2, 1_u8.into()),
+                    (3, 2_u8.into()),
+                ],
+            )
+            .row(
+                "Companion",
+                1,
+                &[(0, "wayward hatchling".into()), (8, 10_u16.into())],
+            )
+            .row(
+                "Companion",
+                2,
+                &[(0, "wind-up airship".into()), (8, 11_u16.into())],
+            )
+            .imc(
+                "chara/monster/m7001/obj/body/b0001/b0001.imc",
+                &[&[(1, 0), (3, 0)]],
+            );
+
+        let output = fixture.run(analyse_minions);
+        let affects = &output.affects;
+        assert_eq!(
+            vec!["wayward hatchling"],
+            output.names(&affects.monsters[&7001][&1][&1]),
+        );
+        // demihuman variants are not looked up in an imc file
+        assert_eq!(
+            vec!["wind-up airship"],
+            output.names(&affects.demihumans[&1002][&1][&2]),
+        );
+        assert!(output.anomalies.is_empty());
+    }
+
+    #[test]
+    fn skips_other_models() {
+        let mut fixture = Fixture::default();
+        fixture
+            .row("ModelChara", 10, &[(0, 1_u8.into())])
+            .row("ModelChara", 11, &[(0, 9_u8.into())])
+            .row(
+                "Companion",
+                1,
+                &[(0, "wind-up cursor".into()), (8, 10_u16.into())],
+            )
+            .row(
+                "Companion",
+                2,
+                &[(0, "wind-up leader".into()), (8, 11_u16.into())],
+            )
+            // no model chara at all
+            .row(
+                "Companion",
+                3,
+                &[(0, "wind-up onion".into()), (8, 12_u16.into())],
+            );
+
+        let output = fixture.run(analyse_minions);
+        assert!(output.affects.names.is_empty());
+        assert!(output.affects.monsters.is_empty());
+        assert!(output.affects.demihumans.is_empty());
+        assert_eq!(
+            vec![Anomaly::UnknownModelCharaKind { row_id: 11 }],
+            output.anomalies,
+        );
+    }
 }
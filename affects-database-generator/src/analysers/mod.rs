@@ -1,12 +1,28 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, sync::Arc};
 
-use affects_common::{Affects, ItemKind};
+use ::imc::ImcFile;
+use affects_common::{
+    Affects, AttributeMask, ItemKind, Language, ModelTable, NameSet, SCHEMA_VERSION,
+};
+use anyhow::Context;
 use ironworks::{Ironworks, excel::Excel};
 
-use crate::containers::BNpcContainer;
+use crate::{
+    cache::{GeneratorCache, hash_bytes},
+    containers::BNpcContainer,
+    report::Anomaly,
+    schema::{
+        Action, ActionCastTimeline, ActionCastVfx, ActionTimeline, BNpcBase, BNpcName,
+        CharaMakeCustomize, CharaMakeType, Companion, ENpcBase, ENpcResident, Emote,
+        EquipSlotCategory, HairMakeType, Item, Map, MetadataExtractor, MetadataProvider,
+        ModelChara, Mount, NpcEquip, Orchestrion, OrchestrionPath, Ornament, PlaceName,
+        TextCommand, Tribe, Vfx,
+    },
+};
 
 mod actions;
 mod bnpcs;
+mod customisations;
 mod emotes;
 mod enpcs;
 pub mod imc;
@@ -15,33 +31,51 @@ mod maps;
 mod minions;
 mod mounts;
 mod ornaments;
+mod sounds;
+mod timelines;
+mod vfx;
 
 pub use self::{
-    actions::analyse_actions, bnpcs::analyse_bnpcs, emotes::analyse_emotes, enpcs::analyse_enpcs,
-    items::analyse_items, maps::analyse_maps, minions::analyse_minions, mounts::analyse_mounts,
-    ornaments::analyse_ornaments,
+    actions::analyse_actions, bnpcs::analyse_bnpcs, customisations::analyse_customisations,
+    emotes::analyse_emotes, enpcs::analyse_enpcs, items::analyse_items, maps::analyse_maps,
+    minions::analyse_minions, mounts::analyse_mounts, ornaments::analyse_ornaments,
+    sounds::analyse_sounds,
 };
 
 pub struct GeneratorContext<'a> {
     pub affects: &'a mut Affects,
     pub ironworks: &'a Ironworks,
+    pub cache: &'a GeneratorCache,
     pub excel: &'a Excel,
-    pub name_map: &'a mut BTreeMap<String, u16>,
+    /// excel readers for every language other than english
+    pub localised_excels: &'a [(Language, Excel)],
+    pub name_map: &'a mut BTreeMap<String, u32>,
     pub bnpcs: &'a BNpcContainer,
+    pub anomalies: &'a mut Vec<Anomaly>,
 }
 
 impl GeneratorContext<'_> {
-    pub fn get_name_idx<S: Into<String>>(&mut self, kind: ItemKind, name: S) -> u16 {
+    /// the index of `name` in this run. indices are handed out in the order
+    /// names are first seen, and are renumbered by
+    /// [`crate::names::NameIndices::assign`] once every analyser has run.
+    /// `kind` is added to the kinds of the name, even if it was seen before
+    pub fn get_name_idx<S: Into<String>>(
+        &mut self,
+        kind: ItemKind,
+        name: S,
+    ) -> anyhow::Result<u32> {
         let name = name.into();
-        if let Some(&idx) = self.name_map.get(&name) {
-            return idx;
-        }
-
-        self.affects.names.push(name.clone());
-        let name_idx = self.affects.names.len() - 1;
-        let name_idx = u16::try_from(name_idx).expect("name idx exceeded 16 bits");
+        let name_idx = match self.name_map.get(&name) {
+            Some(&idx) => idx,
+            None => {
+                let idx = u32::try_from(self.affects.names.len())
+                    .with_context(|| format!("too many names to give {name} an index"))?;
+                self.affects.names.push(name.clone());
+                self.name_map.insert(name, idx);
 
-        self.name_map.insert(name, name_idx);
+                idx
+            }
+        };
 
         self.affects
             .name_kinds
@@ -49,6 +83,578 @@ impl GeneratorContext<'_> {
             .or_default()
             .insert(kind);
 
-        name_idx
+        Ok(name_idx)
+    }
+
+    /// stores the translations of the name at `name_idx`. `localise` builds
+    /// the name again using the given language's sheets. like the english
+    /// names, the first translation stored for an index is kept
+    pub fn localise_name(&mut self, name_idx: u32, localise: impl Fn(&Excel) -> Option<String>) {
+        let idx = name_idx as usize;
+        for (language, excel) in self.localised_excels {
+            let names = self.affects.localised_names.entry(*language).or_default();
+            if names.get(idx).is_some_and(|name| !name.is_empty()) {
+                continue;
+            }
+
+            let name = match localise(excel) {
+                Some(name) if !name.is_empty() => name,
+                _ => continue,
+            };
+
+            if names.len() <= idx {
+                names.resize(idx + 1, String::new());
+            }
+
+            names[idx] = name;
+        }
+    }
+
+    pub fn anomaly(&mut self, anomaly: Anomaly) {
+        self.anomalies.push(anomaly);
+    }
+
+    /// reads the imc file at `path`, recording an anomaly if it is missing or
+    /// cannot be parsed
+    pub fn imc(&mut self, path: &str) -> Option<Arc<ImcFile>> {
+        match self.cache.imc(self.ironworks, path) {
+            Ok(imc) => Some(imc),
+            Err(error) => {
+                self.anomaly(error.anomaly(path.into()));
+                None
+            }
+        }
+    }
+
+    /// checks that the imc file at `path` has `variant` in the part at
+    /// `part`, recording an anomaly if it does not
+    pub fn check_variant(&mut self, path: &str, part: usize, variant: u8) {
+        let imc = match self.imc(path) {
+            Some(imc) => imc,
+            None => return,
+        };
+
+        if imc.variant(part, variant.into()).is_none() {
+            self.anomaly(Anomaly::VariantOutOfRange {
+                path: path.into(),
+                part,
+                variant,
+            });
+        }
+    }
+
+    /// adds a database produced by a single analyser, giving its names
+    /// indices in this one
+    pub fn merge(&mut self, fragment: &Affects) -> anyhow::Result<()> {
+        let remap = fragment
+            .names
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| -> anyhow::Result<_> {
+                let kinds = fragment
+                    .name_kinds
+                    .get(&(idx as u32))
+                    .filter(|kinds| !kinds.is_empty())
+                    .with_context(|| format!("fragment name {name} has no kind"))?;
+                let mut name_idx = 0;
+                for &kind in kinds {
+                    name_idx = self.get_name_idx(kind, name)?;
+                }
+
+                for (language, names) in &fragment.localised_names {
+                    match names.get(idx) {
+                        Some(name) if !name.is_empty() => {
+                            let name = name.clone();
+                            let names = self.affects.localised_names.entry(*language).or_default();
+                            let target = name_idx as usize;
+                            if names.len() <= target {
+                                names.resize(target + 1, String::new());
+                            }
+
+                            if names[target].is_empty() {
+                                names[target] = name;
+                            }
+                        }
+                        _ => continue,
+                    }
+                }
+
+                Ok(name_idx)
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+
+        merge_tables(self.affects, fragment, &remap);
+
+        Ok(())
+    }
+}
+
+/// adds every table of `fragment` to `affects`. `remap` maps each name index
+/// in `fragment` to its index in `affects`
+pub fn merge_tables(affects: &mut Affects, fragment: &Affects, remap: &[u32]) {
+    for (slot, models) in &fragment.equipment {
+        let into = affects.equipment.entry(*slot).or_default();
+        for (model, variants) in models {
+            let into = into.entry(*model).or_default();
+            merge_keyed(into, variants, remap);
+        }
+    }
+
+    merge_models(&mut affects.weapons, &fragment.weapons, remap);
+    merge_models(&mut affects.monsters, &fragment.monsters, remap);
+    merge_models(&mut affects.demihumans, &fragment.demihumans, remap);
+    merge_keyed(&mut affects.emotes, &fragment.emotes, remap);
+    merge_keyed(&mut affects.actions, &fragment.actions, remap);
+    merge_keyed(&mut affects.maps, &fragment.maps, remap);
+
+    // vfx
+    for (model, effects) in &fragment.vfx.equipment {
+        let into = affects.vfx.equipment.entry(*model).or_default();
+        for (effect, used_by) in effects {
+            into.entry(*effect).or_default().extend(used_by);
+        }
+    }
+
+    for (into, from) in [
+        (&mut affects.vfx.monsters, &fragment.vfx.monsters),
+        (&mut affects.vfx.demihumans, &fragment.vfx.demihumans),
+        (&mut affects.vfx.weapons, &fragment.vfx.weapons),
+    ] {
+        for (model, bases) in from {
+            let into = into.entry(*model).or_default();
+            for (base, effects) in bases {
+                let into = into.entry(*base).or_default();
+                for (effect, variants) in effects {
+                    into.entry(*effect).or_default().extend(variants);
+                }
+            }
+        }
+    }
+
+    merge_keyed(&mut affects.vfx.effects, &fragment.vfx.effects, remap);
+
+    // sounds
+    merge_keyed(&mut affects.sounds.bgm, &fragment.sounds.bgm, remap);
+    merge_keyed(
+        &mut affects.sounds.timelines,
+        &fragment.sounds.timelines,
+        remap,
+    );
+
+    // attributes
+    merge_variants(
+        &mut affects.attributes.equipment,
+        &fragment.attributes.equipment,
+    );
+    merge_variants(
+        &mut affects.attributes.weapons,
+        &fragment.attributes.weapons,
+    );
+    merge_variants(
+        &mut affects.attributes.monsters,
+        &fragment.attributes.monsters,
+    );
+
+    // materials
+    for (into, from) in [
+        (&mut affects.materials.decals, &fragment.materials.decals),
+        (
+            &mut affects.materials.animations,
+            &fragment.materials.animations,
+        ),
+    ] {
+        for (id, users) in from {
+            into.entry(*id).or_default().extend(users);
+        }
+    }
+
+    merge_variants(
+        &mut affects.materials.equipment,
+        &fragment.materials.equipment,
+    );
+    merge_variants(&mut affects.materials.weapons, &fragment.materials.weapons);
+    merge_variants(
+        &mut affects.materials.monsters,
+        &fragment.materials.monsters,
+    );
+
+    // customisations
+    for (model, body_types) in &fragment.customisations.models {
+        let into = affects.customisations.models.entry(*model).or_default();
+        for (body_type, ids) in body_types {
+            merge_keyed(into.entry(body_type.clone()).or_default(), ids, remap);
+        }
+    }
+
+    merge_keyed(
+        &mut affects.customisations.eyes,
+        &fragment.customisations.eyes,
+        remap,
+    );
+    merge_keyed(
+        &mut affects.customisations.face_paints,
+        &fragment.customisations.face_paints,
+        remap,
+    );
+
+    // items
+    for (item_id, name_idx) in &fragment.item_ids {
+        affects
+            .item_ids
+            .entry(*item_id)
+            .or_insert(remap[*name_idx as usize]);
+    }
+
+    for (name_idx, items) in &fragment.items {
+        let into = affects.items.entry(remap[*name_idx as usize]).or_default();
+        for item in items {
+            if !into.iter().any(|other| other.item_id == item.item_id) {
+                into.push(item.clone());
+            }
+        }
+    }
+}
+
+fn merge_names(into: &mut NameSet, from: &NameSet, remap: &[u32]) {
+    into.extend(
+        from.iter()
+            .map(|&(kind, name_idx)| (kind, remap[name_idx as usize])),
+    );
+}
+
+fn merge_keyed<K: Ord + Clone>(
+    into: &mut BTreeMap<K, NameSet>,
+    from: &BTreeMap<K, NameSet>,
+    remap: &[u32],
+) {
+    for (key, names) in from {
+        merge_names(into.entry(key.clone()).or_default(), names, remap);
+    }
+}
+
+fn merge_models<S: Ord + Copy>(into: &mut ModelTable<S>, from: &ModelTable<S>, remap: &[u32]) {
+    for (model, secondaries) in from {
+        let into = into.entry(*model).or_default();
+        for (secondary, variants) in secondaries {
+            merge_keyed(into.entry(*secondary).or_default(), variants, remap);
+        }
+    }
+}
+
+/// adds a table of something read from each imc variant, like its
+/// attributes. every fragment reads the same imc files, so a variant in both
+/// tables is the same in each
+fn merge_variants<M: Ord + Copy, S: Ord + Copy, V: Copy>(
+    into: &mut BTreeMap<M, BTreeMap<S, BTreeMap<u8, V>>>,
+    from: &BTreeMap<M, BTreeMap<S, BTreeMap<u8, V>>>,
+) {
+    for (model, secondaries) in from {
+        let into = into.entry(*model).or_default();
+        for (secondary, variants) in secondaries {
+            into.entry(*secondary).or_default().extend(variants);
+        }
+    }
+}
+
+type SheetHash = fn(&GeneratorCache, &Excel) -> anyhow::Result<u64>;
+
+/// an analyser that adds to the database from the game's sheets. analysers
+/// run into a database of their own, which is then merged into the main one,
+/// so they can run in parallel and their output can be reused by incremental
+/// runs
+pub struct Analyser {
+    pub name: &'static str,
+    pub run: fn(&mut GeneratorContext) -> anyhow::Result<()>,
+    /// the sheets the analyser reads. other game files, like imc files and
+    /// timelines, are covered by the game version
+    pub sheets: &'static [SheetHash],
+    /// whether the analyser reads the battle npc data
+    pub bnpcs: bool,
+}
+
+impl Analyser {
+    /// a hash of everything the analyser reads and of the generator reading
+    /// it. the game files besides sheets are too many to hash, so they are
+    /// only known to be unchanged while `game_version` is. without a game
+    /// version there is no hash, and the analyser always runs
+    pub fn inputs(
+        &self,
+        ctx: &GeneratorContext,
+        game_version: Option<&str>,
+    ) -> anyhow::Result<Option<u64>> {
+        let game_version = match game_version {
+            Some(game_version) => game_version,
+            None => return Ok(None),
+        };
+
+        let mut hashes = vec![
+            hash_bytes(env!("CARGO_PKG_VERSION").as_bytes()),
+            hash_bytes(&SCHEMA_VERSION.to_le_bytes()),
+            hash_bytes(game_version.as_bytes()),
+        ];
+        for hash in self.sheets {
+            hashes.push(hash(ctx.cache, ctx.excel)?);
+        }
+        if self.bnpcs {
+            hashes.push(hash_bytes(&serde_json::to_vec(ctx.bnpcs)?));
+        }
+
+        let bytes = hashes
+            .iter()
+            .flat_map(|hash| hash.to_le_bytes())
+            .collect::<Vec<_>>();
+        Ok(Some(hash_bytes(&bytes)))
+    }
+}
+
+/// the analysers that read sheets, in the order they run. the imc analysers
+/// are not included, as they read what these produce
+pub const ANALYSERS: &[Analyser] = &[
+    Analyser {
+        name: "Items",
+        run: analyse_items,
+        sheets: &[
+            GeneratorCache::sheet_hash::<Item>,
+            GeneratorCache::sheet_hash::<EquipSlotCategory>,
+        ],
+        bnpcs: false,
+    },
+    Analyser {
+        name: "Emotes",
+        run: analyse_emotes,
+        sheets: &[
+            GeneratorCache::sheet_hash::<Emote>,
+            GeneratorCache::sheet_hash::<ActionTimeline>,
+            GeneratorCache::sheet_hash::<TextCommand>,
+        ],
+        bnpcs: false,
+    },
+    Analyser {
+        name: "Battle NPCs",
+        run: analyse_bnpcs,
+        sheets: &[
+            GeneratorCache::sheet_hash::<BNpcBase>,
+            GeneratorCache::sheet_hash::<BNpcName>,
+            GeneratorCache::sheet_hash::<Companion>,
+            GeneratorCache::sheet_hash::<ModelChara>,
+            GeneratorCache::sheet_hash::<NpcEquip>,
+        ],
+        bnpcs: true,
+    },
+    Analyser {
+        name: "Event NPCs",
+        run: analyse_enpcs,
+        sheets: &[
+            GeneratorCache::sheet_hash::<ENpcBase>,
+            GeneratorCache::sheet_hash::<ENpcResident>,
+            GeneratorCache::sheet_hash::<ModelChara>,
+        ],
+        bnpcs: false,
+    },
+    Analyser {
+        name: "Actions",
+        run: analyse_actions,
+        sheets: &[
+            GeneratorCache::sheet_hash::<Action>,
+            GeneratorCache::sheet_hash::<ActionCastTimeline>,
+            GeneratorCache::sheet_hash::<ActionCastVfx>,
+            GeneratorCache::sheet_hash::<ActionTimeline>,
+            GeneratorCache::sheet_hash::<Vfx>,
+        ],
+        bnpcs: false,
+    },
+    Analyser {
+        name: "Minions",
+        run: analyse_minions,
+        sheets: &[
+            GeneratorCache::sheet_hash::<Companion>,
+            GeneratorCache::sheet_hash::<ModelChara>,
+        ],
+        bnpcs: false,
+    },
+    Analyser {
+        name: "Mounts",
+        run: analyse_mounts,
+        sheets: &[
+            GeneratorCache::sheet_hash::<Mount>,
+            GeneratorCache::sheet_hash::<ModelChara>,
+            GeneratorCache::sheet_hash::<ActionTimeline>,
+        ],
+        bnpcs: false,
+    },
+    Analyser {
+        name: "Ornaments",
+        run: analyse_ornaments,
+        sheets: &[
+            GeneratorCache::sheet_hash::<Ornament>,
+            GeneratorCache::sheet_hash::<ModelChara>,
+        ],
+        bnpcs: false,
+    },
+    Analyser {
+        name: "Maps",
+        run: analyse_maps,
+        sheets: &[
+            GeneratorCache::sheet_hash::<Map>,
+            GeneratorCache::sheet_hash::<PlaceName>,
+        ],
+        bnpcs: false,
+    },
+    Analyser {
+        name: "Sounds",
+        run: analyse_sounds,
+        sheets: &[
+            GeneratorCache::sheet_hash::<Orchestrion>,
+            GeneratorCache::sheet_hash::<OrchestrionPath>,
+        ],
+        bnpcs: false,
+    },
+    Analyser {
+        name: "Customisations",
+        run: analyse_customisations,
+        sheets: &[
+            GeneratorCache::sheet_hash::<CharaMakeType>,
+            GeneratorCache::sheet_hash::<HairMakeType>,
+            GeneratorCache::sheet_hash::<CharaMakeCustomize>,
+            GeneratorCache::sheet_hash::<Tribe>,
+            GeneratorCache::sheet_hash::<Item>,
+        ],
+        bnpcs: false,
+    },
+];
+
+/// reads a single row of a sheet, for use when localising names
+pub fn localised_row<S: MetadataExtractor>(excel: &Excel, row_id: u32) -> Option<S> {
+    excel
+        .sheet(MetadataProvider::<S>::for_sheet())
+        .ok()?
+        .row(row_id)
+        .ok()
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use affects_common::{Affects, ItemKind, Language, SoundMaps};
+
+    use super::ANALYSERS;
+    use crate::{names::NameIndices, test::Fixture};
+
+    fn items_inputs(fixture: &Fixture, game_version: Option<&str>) -> Option<u64> {
+        let items = &ANALYSERS[0];
+        assert_eq!("Items", items.name);
+
+        fixture
+            .with_context(&mut Affects::default(), |ctx| {
+                items.inputs(ctx, game_version).unwrap()
+            })
+            .0
+    }
+
+    #[test]
+    fn inputs() {
+        let mut fixture = Fixture::default();
+        fixture.row("Item", 1, &[(9, "Iron Cuirass".into())]);
+
+        let inputs = items_inputs(&fixture, Some("2024.01.01"));
+        assert!(inputs.is_some());
+        assert_eq!(inputs, items_inputs(&fixture, Some("2024.01.01")));
+
+        // files other than sheets are only covered by the game version
+        assert_ne!(inputs, items_inputs(&fixture, Some("2024.02.01")));
+        assert_eq!(None, items_inputs(&fixture, None));
+
+        fixture.row("Item", 1, &[(9, "Steel Cuirass".into())]);
+        assert_ne!(inputs, items_inputs(&fixture, Some("2024.01.01")));
+    }
+
+    #[test]
+    fn merge_without_kind() {
+        let fragment = Affects {
+            names: vec!["Iron Cuirass".into()],
+            ..Default::default()
+        };
+
+        let mut affects = Affects::default();
+        let (merged, _) = Fixture::default().with_context(&mut affects, |ctx| ctx.merge(&fragment));
+        let err = merged.unwrap_err();
+        assert!(err.to_string().contains("Iron Cuirass"), "{err}");
+    }
+
+    /// the fragments merged in the given order, with their names numbered
+    /// as they are at the end of a run
+    fn merged(fragments: &[&Affects]) -> Affects {
+        let mut affects = Affects::default();
+        let (result, _) = Fixture::default().with_context(&mut affects, |ctx| {
+            fragments
+                .iter()
+                .try_for_each(|fragment| ctx.merge(fragment))
+        });
+        result.unwrap();
+        NameIndices::default().assign(&mut affects);
+
+        affects
+    }
+
+    #[test]
+    fn merge_order() {
+        let maps = Affects {
+            names: vec!["Limsa Lominsa".into(), "Gridania".into()],
+            localised_names: BTreeMap::from([(
+                Language::German,
+                vec![String::new(), "Gridania (de)".into()],
+            )]),
+            name_kinds: BTreeMap::from([
+                (0, BTreeSet::from([ItemKind::Map])),
+                (1, BTreeSet::from([ItemKind::Map])),
+            ]),
+            maps: BTreeMap::from([
+                ("s1t1".into(), BTreeSet::from([(ItemKind::Map, 0)])),
+                ("f1f1".into(), BTreeSet::from([(ItemKind::Map, 1)])),
+            ]),
+            ..Default::default()
+        };
+        let sounds = Affects {
+            names: vec!["Gridania".into(), "Answers".into()],
+            name_kinds: BTreeMap::from([
+                (0, BTreeSet::from([ItemKind::Sound])),
+                (1, BTreeSet::from([ItemKind::Sound])),
+            ]),
+            sounds: SoundMaps {
+                bgm: BTreeMap::from([
+                    (
+                        "music/gridania.scd".into(),
+                        BTreeSet::from([(ItemKind::Sound, 0)]),
+                    ),
+                    (
+                        "music/answers.scd".into(),
+                        BTreeSet::from([(ItemKind::Sound, 1)]),
+                    ),
+                ]),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+
+        let affects = merged(&[&maps, &sounds]);
+        assert_eq!(
+            serde_json::to_string(&affects).unwrap(),
+            serde_json::to_string(&merged(&[&sounds, &maps])).unwrap()
+        );
+
+        // new names are numbered in sorted order
+        assert_eq!(vec!["Answers", "Gridania", "Limsa Lominsa"], affects.names);
+        assert_eq!(Some("Gridania (de)"), affects.name(1, Language::German));
+        assert_eq!(BTreeSet::from([(ItemKind::Map, 1)]), affects.maps["f1f1"]);
+        // names found by several analysers have the kinds from all of them
+        assert_eq!(
+            BTreeSet::from([ItemKind::Map, ItemKind::Sound]),
+            affects.name_kinds[&1]
+        );
+        assert_eq!(
+            BTreeSet::from([(ItemKind::Sound, 1)]),
+            affects.sounds.bgm["music/gridania.scd"]
+        );
     }
 }
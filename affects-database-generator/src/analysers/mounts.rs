@@ -1,26 +1,33 @@
+use std::collections::BTreeMap;
+
 use affects_common::ItemKind;
+use path_parser::builder::MonsterModel;
 
 use crate::{
-    analysers::GeneratorContext,
-    formats::imc::{ImcFile, RawImcFile},
-    schema::{MetadataProvider, ModelChara, ModelCharaKind, Mount},
+    analysers::{
+        GeneratorContext, localised_row,
+        timelines::{TimelineCache, add_timeline_files},
+    },
+    report::Anomaly,
+    schema::{MetadataProvider, ModelCharaKind, Mount},
 };
 
-pub fn analyse_mounts(ctx: &mut GeneratorContext) {
-    let mounts = ctx
-        .excel
-        .sheet(MetadataProvider::<Mount>::for_sheet())
-        .unwrap();
-    let model_charas = ctx
-        .excel
-        .sheet(MetadataProvider::<ModelChara>::for_sheet())
-        .unwrap();
+pub fn analyse_mounts(ctx: &mut GeneratorContext) -> anyhow::Result<()> {
+    let mounts = ctx.excel.sheet(MetadataProvider::<Mount>::for_sheet())?;
+    let model_charas = ctx.cache.model_charas(ctx.excel)?;
+    let model_timelines = model_timelines(ctx)?;
+
+    let mut timelines = TimelineCache::default();
 
     for mount in mounts {
-        let mount = mount.unwrap();
+        let mount = mount?;
 
-        let model_chara = match model_charas.row(mount.model_chara as u32) {
-            Ok(mc) if !mc.kind.is_other() => mc,
+        let model_chara = match model_charas.get(&(mount.model_chara as u32)) {
+            Some(mc) if mc.kind == ModelCharaKind::Unknown => {
+                ctx.anomaly(Anomaly::UnknownModelCharaKind { row_id: mc.row_id });
+                continue;
+            }
+            Some(mc) if !mc.kind.is_other() => mc,
             _ => continue,
         };
 
@@ -29,27 +36,38 @@ pub fn analyse_mounts(ctx: &mut GeneratorContext) {
             _ => continue,
         };
 
-        let mut variant_id = model_chara.variant;
+        let variant_id = model_chara.variant;
 
-        // need to use the imc file to map this variant_id to the
-        // correct variant_id used in the game path
+        // names are kept by imc variant, which should be in the imc file
         if model_chara.kind == ModelCharaKind::Monster {
-            let imc = ctx
-                .ironworks
-                .file::<RawImcFile>(&format!(
-                    "chara/monster/m{model:<04}/obj/body/b{base:<04}/b{base:<04}.imc",
-                    model = model_chara.model,
-                    base = model_chara.base,
-                ))
+            let imc_path = MonsterModel::new(model_chara.model, model_chara.base as u16)
+                .imc()
+                .to_string();
+            ctx.check_variant(&imc_path, 0, variant_id);
+        }
+
+        let name_idx = ctx.get_name_idx(ItemKind::Mount, name)?;
+        ctx.localise_name(name_idx, |excel| {
+            localised_row::<Mount>(excel, mount.row_id)?
+                .singular
+                .format()
                 .ok()
-                .and_then(ImcFile::try_from_raw);
-            if let Some(imc) = imc {
-                let imc_variant = &imc.parts[0].variants[variant_id as usize - 1];
-                variant_id = imc_variant.material_id;
+        });
+
+        // the effects the mount plays while ridden, which are only linked
+        // to it through the timelines of its model
+        if model_chara.kind == ModelCharaKind::Monster {
+            let keys = model_timelines
+                .get(&model_chara.model)
+                .map(Vec::as_slice)
+                .unwrap_or_default();
+            for key in keys {
+                if let Some(tmb) = timelines.get(ctx.ironworks, key) {
+                    add_timeline_files(ctx, tmb, ItemKind::Mount, name_idx);
+                }
             }
         }
 
-        let name_idx = ctx.get_name_idx(ItemKind::Mount, name);
         let map = match model_chara.kind {
             ModelCharaKind::Demihuman => &mut ctx.affects.demihumans,
             ModelCharaKind::Monster => &mut ctx.affects.monsters,
@@ -64,4 +82,181 @@ pub fn analyse_mounts(ctx: &mut GeneratorContext) {
             .or_default()
             .insert((ItemKind::Mount, name_idx));
     }
+
+    Ok(())
+}
+
+/// monster model => the keys of the timelines played while riding it, like
+/// `mount_sp/m0001/mon_sp001` for m0001
+fn model_timelines(ctx: &GeneratorContext) -> anyhow::Result<BTreeMap<u16, Vec<String>>> {
+    let action_timelines = ctx.cache.action_timelines(ctx.excel)?;
+
+    let mut model_timelines = BTreeMap::<u16, Vec<String>>::new();
+    for timeline in action_timelines.values() {
+        let key = match timeline.key.format() {
+            Ok(key) => key,
+            Err(_) => continue,
+        };
+
+        let model = key
+            .strip_prefix("mount_sp/m")
+            .and_then(|rest| rest.split_once('/'))
+            .and_then(|(model, _)| model.parse().ok());
+        if let Some(model) = model {
+            model_timelines.entry(model).or_default().push(key);
+        }
+    }
+
+    Ok(model_timelines)
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{analysers::analyse_mounts, report::Anomaly, test::Fixture};
+
+    #[test]
+    fn monster_and_demihuman() {
+        let mut fixture = Fixture::default();
+        fixture
+            .row(
+                "ModelChara",
+                10,
+                &[
+                    (0, 3_u8.into()),
+                    (1, 2_001_u16.into()),
+                    (2, 1_u8.into()),
+                    (3, 1_u8.into()),
+                ],
+            )
+            .row(
+                "ModelChara",
+                11,
+                &[
+                    (0, 2_u8.into()),
+                    (1, 1_006_u16.into()),
+                    (2, 1_u8.into()),
+                    (3, 3_u8.into()),
+                ],
+            )
+            .row(
+                "Mount",
+                1,
+                &[(0, "company chocobo".into()), (8, 10_i32.into())],
+            )
+            .row(
+                "Mount",
+                2,
+                &[(0, "magitek armor".into()), (8, 11_i32.into())],
+            )
+            .imc(
+                "chara/monster/m2001/obj/body/b0001/b0001.imc",
+                &[&[(1, 0), (2, 0)]],
+            );
+
+        let output = fixture.run(analyse_mounts);
+        let affects = &output.affects;
+        assert_eq!(
+            vec!["company chocobo"],
+            output.names(&affects.monsters[&2001][&1][&1]),
+        );
+        // demihuman variants are not looked up in an imc file
+        assert_eq!(
+            vec!["magitek armor"],
+            output.names(&affects.demihumans[&1006][&1][&3]),
+        );
+        assert!(output.anomalies.is_empty());
+    }
+
+    #[test]
+    fn ridden_effects() {
+        let mut fixture = Fixture::default();
+        fixture
+            .row(
+                "ModelChara",
+                10,
+                &[(0, 3_u8.into()), (1, 2_001_u16.into()), (2, 1_u8.into())],
+            )
+            .row(
+                "Mount",
+                1,
+                &[(0, "company chocobo".into()), (8, 10_i32.into())],
+            )
+            .row(
+                "ActionTimeline",
+                1,
+                &[(6, "mount_sp/m2001/mon_sp001".into())],
+            )
+            .row(
+                "ActionTimeline",
+                2,
+                &[(6, "mount_sp/m2002/mon_sp001".into())],
+            )
+            .imc("chara/monster/m2001/obj/body/b0001/b0001.imc", &[&[(1, 0)]])
+            .tmb(
+                "mount_sp/m2001/mon_sp001",
+                &["vfx/mount/eff/chocobo_run.avfx"],
+            )
+            .tmb(
+                "mount_sp/m2002/mon_sp001",
+                &["vfx/mount/eff/magitek_run.avfx"],
+            )
+            .avfx(
+                "vfx/mount/eff/chocobo_run.avfx",
+                &["vfx/mount/texture/chocobo_dust.atex"],
+            );
+
+        let output = fixture.run(analyse_mounts);
+        let effects = &output.affects.vfx.effects;
+        assert_eq!(
+            vec!["company chocobo"],
+            output.names(&effects["chocobo_run"]),
+        );
+        // textures go to whatever plays the effects drawing them
+        assert_eq!(
+            vec!["company chocobo"],
+            output.names(&effects["chocobo_dust"]),
+        );
+        // timelines of models no mount uses are not read
+        assert_eq!(2, effects.len());
+        assert!(output.anomalies.is_empty());
+    }
+
+    #[test]
+    fn reports_variants_missing_from_imc() {
+        let mut fixture = Fixture::default();
+        fixture
+            .row(
+                "ModelChara",
+                10,
+                &[
+                    (0, 3_u8.into()),
+                    (1, 2_001_u16.into()),
+                    (2, 1_u8.into()),
+                    (3, 4_u8.into()),
+                ],
+            )
+            .row(
+                "Mount",
+                1,
+                &[(0, "company chocobo".into()), (8, 10_i32.into())],
+            )
+            .imc(
+                "chara/monster/m2001/obj/body/b0001/b0001.imc",
+                &[&[(1, 0), (2, 0)]],
+            );
+
+        let output = fixture.run(analyse_mounts);
+        assert_eq!(
+            vec!["company chocobo"],
+            output.names(&output.affects.monsters[&2001][&1][&4]),
+        );
+        assert_eq!(
+            vec![Anomaly::VariantOutOfRange {
+                path: "chara/monster/m2001/obj/body/b0001/b0001.imc".into(),
+                part: 0,
+                variant: 4,
+            }],
+            output.anomalies,
+        );
+    }
 }
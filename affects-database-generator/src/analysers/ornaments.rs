@@ -1,26 +1,25 @@
 use affects_common::ItemKind;
+use path_parser::builder::MonsterModel;
 
 use crate::{
-    analysers::GeneratorContext,
-    formats::imc::{ImcFile, RawImcFile},
-    schema::{MetadataProvider, ModelChara, ModelCharaKind, Ornament},
+    analysers::{GeneratorContext, localised_row},
+    report::Anomaly,
+    schema::{MetadataProvider, ModelCharaKind, Ornament},
 };
 
-pub fn analyse_ornaments(ctx: &mut GeneratorContext) {
-    let ornaments = ctx
-        .excel
-        .sheet(MetadataProvider::<Ornament>::for_sheet())
-        .unwrap();
-    let model_charas = ctx
-        .excel
-        .sheet(MetadataProvider::<ModelChara>::for_sheet())
-        .unwrap();
+pub fn analyse_ornaments(ctx: &mut GeneratorContext) -> anyhow::Result<()> {
+    let ornaments = ctx.excel.sheet(MetadataProvider::<Ornament>::for_sheet())?;
+    let model_charas = ctx.cache.model_charas(ctx.excel)?;
 
     for ornament in ornaments {
-        let ornament = ornament.unwrap();
+        let ornament = ornament?;
 
-        let model_chara = match model_charas.row(ornament.model as u32) {
-            Ok(mc) if !mc.kind.is_other() => mc,
+        let model_chara = match model_charas.get(&(ornament.model as u32)) {
+            Some(mc) if mc.kind == ModelCharaKind::Unknown => {
+                ctx.anomaly(Anomaly::UnknownModelCharaKind { row_id: mc.row_id });
+                continue;
+            }
+            Some(mc) if !mc.kind.is_other() => mc,
             _ => continue,
         };
 
@@ -29,27 +28,24 @@ pub fn analyse_ornaments(ctx: &mut GeneratorContext) {
             _ => continue,
         };
 
-        let mut variant_id = model_chara.variant;
+        let variant_id = model_chara.variant;
 
-        // need to use the imc file to map this variant_id to the
-        // correct variant_id used in the game path
+        // names are kept by imc variant, which should be in the imc file
         if model_chara.kind == ModelCharaKind::Monster {
-            let imc = ctx
-                .ironworks
-                .file::<RawImcFile>(&format!(
-                    "chara/monster/m{model:<04}/obj/body/b{base:<04}/b{base:<04}.imc",
-                    model = model_chara.model,
-                    base = model_chara.base,
-                ))
-                .ok()
-                .and_then(ImcFile::try_from_raw);
-            if let Some(imc) = imc {
-                let imc_variant = &imc.parts[0].variants[variant_id as usize - 1];
-                variant_id = imc_variant.material_id;
-            }
+            let imc_path = MonsterModel::new(model_chara.model, model_chara.base as u16)
+                .imc()
+                .to_string();
+            ctx.check_variant(&imc_path, 0, variant_id);
         }
 
-        let name_idx = ctx.get_name_idx(ItemKind::FashionAccessory, name);
+        let name_idx = ctx.get_name_idx(ItemKind::FashionAccessory, name)?;
+        ctx.localise_name(name_idx, |excel| {
+            localised_row::<Ornament>(excel, ornament.row_id)?
+                .singular
+                .format()
+                .ok()
+        });
+
         let map = match model_chara.kind {
             ModelCharaKind::Demihuman => &mut ctx.affects.demihumans,
             ModelCharaKind::Monster => &mut ctx.affects.monsters,
@@ -64,4 +60,47 @@ pub fn analyse_ornaments(ctx: &mut GeneratorContext) {
             .or_default()
             .insert((ItemKind::FashionAccessory, name_idx));
     }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{analysers::analyse_ornaments, test::Fixture};
+
+    #[test]
+    fn ornament() {
+        let mut fixture = Fixture::default();
+        fixture
+            .row(
+                "ModelChara",
+                10,
+                &[
+                    (0, 3_u8.into()),
+                    (1, 8_001_u16.into()),
+                    (2, 1_u8.into()),
+                    (3, 1_u8.into()),
+                ],
+            )
+            .row(
+                "ModelChara",
+                11,
+                &[(0, 1_u8.into()), (1, 8_002_u16.into()), (2, 1_u8.into())],
+            )
+            .row("Ornament", 1, &[(0, 10_u16.into()), (8, "parasol".into())])
+            .row("Ornament", 2, &[(0, 11_u16.into()), (8, "wings".into())])
+            .imc(
+                "chara/monster/m8001/obj/body/b0001/b0001.imc",
+                &[&[(1, 0), (1, 0)]],
+            );
+
+        let output = fixture.run(analyse_ornaments);
+        assert_eq!(
+            vec!["parasol"],
+            output.names(&output.affects.monsters[&8001][&1][&1]),
+        );
+        // only monster and demihuman models are recorded
+        assert_eq!(vec!["parasol"], output.affects.names);
+        assert!(output.anomalies.is_empty());
+    }
 }
@@ -0,0 +1,88 @@
+use affects_common::ItemKind;
+
+use crate::{
+    analysers::{GeneratorContext, localised_row},
+    schema::{MetadataProvider, Orchestrion, OrchestrionPath},
+};
+
+pub fn analyse_sounds(ctx: &mut GeneratorContext) -> anyhow::Result<()> {
+    let orchestrions = ctx
+        .excel
+        .sheet(MetadataProvider::<Orchestrion>::for_sheet())?;
+    let orchestrion_paths = ctx
+        .excel
+        .sheet(MetadataProvider::<OrchestrionPath>::for_sheet())?;
+
+    for orchestrion in orchestrions {
+        let orchestrion = orchestrion?;
+
+        let name = match orchestrion.name.format() {
+            Ok(name) if !name.is_empty() => name,
+            _ => continue,
+        };
+
+        // the path sheet shares row ids with the orchestrion sheet
+        let path = match orchestrion_paths
+            .row(orchestrion.row_id)
+            .ok()
+            .and_then(|path| path.file.format().ok())
+        {
+            Some(path) if !path.is_empty() => path,
+            _ => continue,
+        };
+
+        let name_idx = ctx.get_name_idx(ItemKind::Sound, name)?;
+        ctx.localise_name(name_idx, |excel| {
+            localised_row::<Orchestrion>(excel, orchestrion.row_id)?
+                .name
+                .format()
+                .ok()
+        });
+
+        ctx.affects
+            .sounds
+            .bgm
+            .entry(path.to_lowercase())
+            .or_default()
+            .insert((ItemKind::Sound, name_idx));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use affects_common::ItemKind;
+
+    use crate::{analysers::analyse_sounds, test::Fixture};
+
+    #[test]
+    fn orchestrion_rolls() {
+        let mut fixture = Fixture::default();
+        fixture
+            .row("Orchestrion", 1, &[(0, "Answers".into())])
+            .row(
+                "OrchestrionPath",
+                1,
+                &[(0, "music/ffxiv/BGM_Ex1_Boss_01.scd".into())],
+            )
+            // a roll without a path
+            .row("Orchestrion", 2, &[(0, "Dragonsong".into())])
+            // a path without a name
+            .row("Orchestrion", 3, &[])
+            .row(
+                "OrchestrionPath",
+                3,
+                &[(0, "music/ffxiv/BGM_Ex2_Boss_01.scd".into())],
+            );
+
+        let output = fixture.run(analyse_sounds);
+        let bgm = &output.affects.sounds.bgm;
+        assert_eq!(1, bgm.len());
+        // paths are matched in lowercase
+        let names = &bgm["music/ffxiv/bgm_ex1_boss_01.scd"];
+        assert_eq!(vec!["Answers"], output.names(names));
+        assert_eq!(ItemKind::Sound, names.first().unwrap().0);
+        assert_eq!(vec!["Answers"], output.affects.names);
+    }
+}
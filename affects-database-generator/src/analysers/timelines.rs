@@ -0,0 +1,64 @@
+use std::collections::BTreeMap;
+
+use affects_common::ItemKind;
+use ironworks::Ironworks;
+use path_parser::{GamePath, path::chara::CharacterPath};
+
+use crate::{
+    analysers::{
+        GeneratorContext,
+        vfx::{add_vfx, vfx_stem},
+    },
+    formats::tmb::TmbFile,
+};
+
+/// timeline files by key, so that every .tmb file is only read once per
+/// analyser
+#[derive(Default)]
+pub struct TimelineCache {
+    files: BTreeMap<String, Option<TmbFile>>,
+}
+
+impl TimelineCache {
+    pub fn get(&mut self, ironworks: &Ironworks, key: &str) -> Option<&TmbFile> {
+        self.files
+            .entry(key.to_string())
+            .or_insert_with(|| {
+                let path = GamePath::Character(CharacterPath::Tmb(key.into())).to_string();
+                ironworks.file::<TmbFile>(&path).ok()
+            })
+            .as_ref()
+    }
+}
+
+/// links the effects and sounds a timeline plays, and the textures of those
+/// effects, to the name using it
+pub fn add_timeline_files(
+    ctx: &mut GeneratorContext,
+    tmb: &TmbFile,
+    kind: ItemKind,
+    name_idx: u32,
+) {
+    for path in tmb.vfx_paths() {
+        // textures are only referenced by the effects drawing them
+        let textures = if path.ends_with(".avfx") {
+            ctx.cache.effect_textures(ctx.ironworks, path)
+        } else {
+            Vec::new()
+        };
+
+        let paths = std::iter::once(path).chain(textures.iter().map(String::as_str));
+        for stem in paths.filter_map(vfx_stem) {
+            add_vfx(ctx, stem, kind, name_idx);
+        }
+    }
+
+    for path in tmb.sound_paths() {
+        ctx.affects
+            .sounds
+            .timelines
+            .entry(path.to_lowercase())
+            .or_default()
+            .insert((kind, name_idx));
+    }
+}
@@ -0,0 +1,24 @@
+use affects_common::ItemKind;
+
+use crate::analysers::GeneratorContext;
+
+/// turns either a full vfx path or a location from the VFX sheet into the
+/// file stem the calculator looks effects up by
+pub fn vfx_stem(location: &str) -> Option<String> {
+    let file = location.rsplit('/').next()?;
+    let stem = file.split('.').next()?;
+    if stem.is_empty() {
+        return None;
+    }
+
+    Some(stem.to_string())
+}
+
+pub fn add_vfx(ctx: &mut GeneratorContext, stem: String, kind: ItemKind, name_idx: u32) {
+    ctx.affects
+        .vfx
+        .effects
+        .entry(stem)
+        .or_default()
+        .insert((kind, name_idx));
+}
@@ -0,0 +1,96 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
+
+use crate::{
+    bnpc_sources::BNpcSource,
+    containers::{BNpcContainer, BNpcMapEntry},
+};
+
+/// a csv file with a base id and a name id on each line, such as an export of
+/// a datamining sheet. a header line is skipped if there is one
+pub struct CsvFile {
+    path: PathBuf,
+}
+
+impl CsvFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl BNpcSource for CsvFile {
+    fn describe(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn load(&self) -> anyhow::Result<BNpcContainer> {
+        let data = std::fs::read_to_string(&self.path)
+            .with_context(|| format!("could not open {}", self.path.display()))?;
+
+        let mut bnpc = Vec::new();
+        for (line_idx, line) in data.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+
+            let mut fields = line.split(',').map(|field| field.trim().trim_matches('"'));
+            let (base, name) = match (fields.next(), fields.next()) {
+                (Some(base), Some(name)) => (base, name),
+                _ => anyhow::bail!("line {} does not have two fields", line_idx + 1),
+            };
+
+            match (base.parse(), name.parse()) {
+                (Ok(bnpc_base), Ok(bnpc_name)) => bnpc.push(BNpcMapEntry {
+                    bnpc_base,
+                    bnpc_name,
+                }),
+                // the header
+                _ if line_idx == 0 => continue,
+                _ => anyhow::bail!("line {} does not have two ids", line_idx + 1),
+            }
+        }
+
+        Ok(BNpcContainer { bnpc })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bnpc_sources::{BNpcSource, CsvFile},
+        test::{bnpc_pairs, temp_path},
+    };
+
+    fn load(name: &str, data: &str) -> anyhow::Result<Vec<(u32, u32)>> {
+        let path = temp_path(name);
+        std::fs::write(&path, data).unwrap();
+        let bnpcs = CsvFile::new(&path).load();
+        std::fs::remove_file(&path).unwrap();
+
+        Ok(bnpc_pairs(&bnpcs?))
+    }
+
+    #[test]
+    fn header_and_quoted_fields() {
+        let data = "\"BNpcBase\",\"BNpcName\"\n\"1\",\"2\"\n 3 , 4 \n\n5,6,extra\n";
+        assert_eq!(
+            vec![(1, 2), (3, 4), (5, 6)],
+            load("header.csv", data).unwrap(),
+        );
+        assert_eq!(vec![(7, 8)], load("no-header.csv", "7,8").unwrap());
+    }
+
+    #[test]
+    fn malformed_lines() {
+        let error = load("one-field.csv", "1,2\n3\n").unwrap_err();
+        assert_eq!("line 2 does not have two fields", error.to_string());
+
+        // only the first line can be a header
+        let error = load("not-ids.csv", "1,2\nbase,name\n").unwrap_err();
+        assert_eq!("line 2 does not have two ids", error.to_string());
+
+        assert!(CsvFile::new(temp_path("missing.csv")).load().is_err());
+    }
+}
@@ -0,0 +1,35 @@
+use crate::{
+    bnpc_sources::BNpcSource,
+    containers::{BNpcContainer, GraphqlContainer},
+};
+
+/// downloads the mapping teamcraft collects from players
+pub struct GraphqlDownload {
+    pub url: String,
+}
+
+impl Default for GraphqlDownload {
+    fn default() -> Self {
+        Self {
+            url: "https://gubal.ffxivteamcraft.com/graphql".into(),
+        }
+    }
+}
+
+impl BNpcSource for GraphqlDownload {
+    fn describe(&self) -> String {
+        self.url.clone()
+    }
+
+    fn load(&self) -> anyhow::Result<BNpcContainer> {
+        let bnpcs = ureq::post(&self.url)
+            .send_json(serde_json::json!({
+                "query": "query { bnpc { bnpcBase, bnpcName } }",
+            }))?
+            .body_mut()
+            .read_json::<GraphqlContainer<BNpcContainer>>()?
+            .data;
+
+        Ok(bnpcs)
+    }
+}
@@ -0,0 +1,82 @@
+use std::{
+    fs::File,
+    io::BufReader,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use serde::Deserialize;
+
+use crate::{
+    bnpc_sources::BNpcSource,
+    containers::{BNpcContainer, GraphqlContainer},
+};
+
+/// a json file holding the mapping, either as saved by [`super::PatchCache`]
+/// and older versions of the generator, or as the raw response from the
+/// teamcraft graphql api
+pub struct JsonFile {
+    path: PathBuf,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum JsonFormat {
+    Graphql(GraphqlContainer<BNpcContainer>),
+    Plain(BNpcContainer),
+}
+
+impl JsonFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl BNpcSource for JsonFile {
+    fn describe(&self) -> String {
+        self.path.display().to_string()
+    }
+
+    fn load(&self) -> anyhow::Result<BNpcContainer> {
+        let file = File::open(&self.path)
+            .with_context(|| format!("could not open {}", self.path.display()))?;
+        let bnpcs = match serde_json::from_reader(BufReader::new(file))? {
+            JsonFormat::Graphql(container) => container.data,
+            JsonFormat::Plain(bnpcs) => bnpcs,
+        };
+
+        Ok(bnpcs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bnpc_sources::{BNpcSource, JsonFile},
+        test::{bnpc_pairs, temp_path},
+    };
+
+    fn load(name: &str, data: &str) -> anyhow::Result<Vec<(u32, u32)>> {
+        let path = temp_path(name);
+        std::fs::write(&path, data).unwrap();
+        let bnpcs = JsonFile::new(&path).load();
+        std::fs::remove_file(&path).unwrap();
+
+        Ok(bnpc_pairs(&bnpcs?))
+    }
+
+    #[test]
+    fn graphql_and_plain() {
+        let graphql = r#"{"data":{"bnpc":[{"bnpcBase":1,"bnpcName":2}]}}"#;
+        assert_eq!(vec![(1, 2)], load("graphql.json", graphql).unwrap());
+
+        let plain = r#"{"bnpc":[{"bnpcBase":3,"bnpcName":4},{"bnpcBase":5,"bnpcName":6}]}"#;
+        assert_eq!(vec![(3, 4), (5, 6)], load("plain.json", plain).unwrap());
+
+        assert!(load("neither.json", r#"{"bnpcs":[]}"#).is_err());
+    }
+}
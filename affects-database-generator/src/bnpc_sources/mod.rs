@@ -0,0 +1,135 @@
+use std::{collections::BTreeSet, path::Path};
+
+use ironworks::excel::Excel;
+
+use crate::{
+    containers::BNpcContainer,
+    report::Anomaly,
+    schema::{BNpcBase, BNpcName, MetadataProvider},
+};
+
+mod csv_file;
+mod graphql;
+mod json_file;
+mod patch_cache;
+
+pub use self::{
+    csv_file::CsvFile, graphql::GraphqlDownload, json_file::JsonFile, patch_cache::PatchCache,
+};
+
+/// where the battle npc base => name mapping comes from. the game data does
+/// not link the two, so the mapping is collected from outside it
+pub trait BNpcSource {
+    /// a short description for progress output
+    fn describe(&self) -> String;
+
+    fn load(&self) -> anyhow::Result<BNpcContainer>;
+}
+
+/// picks a source for `--bnpc-path`: `download` fetches the mapping from
+/// teamcraft, `.csv` files are read as [`CsvFile`]s and anything else as a
+/// [`JsonFile`]
+pub fn source_for(bnpc_path: &str) -> Box<dyn BNpcSource> {
+    if bnpc_path == "download" {
+        return Box::new(GraphqlDownload::default());
+    }
+
+    let path = Path::new(bnpc_path);
+    let is_csv = path
+        .extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+    if is_csv {
+        Box::new(CsvFile::new(path))
+    } else {
+        Box::new(JsonFile::new(path))
+    }
+}
+
+/// checks that every row the mapping refers to exists in the game data. rows
+/// that do not are harmless, as the analysers only look up rows that exist,
+/// but usually mean the mapping is for a different patch
+pub fn validate(bnpcs: &BNpcContainer, excel: &Excel) -> anyhow::Result<Vec<Anomaly>> {
+    let bases = excel
+        .sheet(MetadataProvider::<BNpcBase>::for_sheet())?
+        .into_iter()
+        .map(|base| base.map(|base| base.row_id))
+        .collect::<Result<BTreeSet<_>, _>>()?;
+    let names = excel
+        .sheet(MetadataProvider::<BNpcName>::for_sheet())?
+        .into_iter()
+        .map(|name| name.map(|name| name.row_id))
+        .collect::<Result<BTreeSet<_>, _>>()?;
+
+    let missing_bases = bnpcs
+        .bnpc
+        .iter()
+        .map(|entry| entry.bnpc_base)
+        .filter(|base| !bases.contains(base))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|row_id| Anomaly::MissingRow {
+            sheet: "BNpcBase".into(),
+            row_id,
+        });
+    let missing_names = bnpcs
+        .bnpc
+        .iter()
+        .map(|entry| entry.bnpc_name)
+        .filter(|name| !names.contains(name))
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .map(|row_id| Anomaly::MissingRow {
+            sheet: "BNpcName".into(),
+            row_id,
+        });
+
+    Ok(missing_bases.chain(missing_names).collect())
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bnpc_sources::validate,
+        containers::{BNpcContainer, BNpcMapEntry},
+        report::Anomaly,
+        test::Fixture,
+    };
+
+    #[test]
+    fn missing_rows() {
+        let mut fixture = Fixture::default();
+        fixture
+            .row("BNpcBase", 1, &[])
+            .row("BNpcName", 2, &[(0, "ahriman".into())]);
+
+        let bnpcs = BNpcContainer {
+            bnpc: [(1, 2), (3, 2), (1, 4), (3, 5), (3, 2)]
+                .into_iter()
+                .map(|(bnpc_base, bnpc_name)| BNpcMapEntry {
+                    bnpc_base,
+                    bnpc_name,
+                })
+                .collect(),
+        };
+
+        let anomalies = validate(&bnpcs, &fixture.game_data().excel).unwrap();
+        // each missing row is reported once
+        assert_eq!(
+            vec![
+                Anomaly::MissingRow {
+                    sheet: "BNpcBase".into(),
+                    row_id: 3,
+                },
+                Anomaly::MissingRow {
+                    sheet: "BNpcName".into(),
+                    row_id: 4,
+                },
+                Anomaly::MissingRow {
+                    sheet: "BNpcName".into(),
+                    row_id: 5,
+                },
+            ],
+            anomalies,
+        );
+    }
+}
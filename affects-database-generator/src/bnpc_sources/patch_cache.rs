@@ -0,0 +1,113 @@
+use std::{fs::File, io::BufWriter, path::Path};
+
+use crate::{
+    bnpc_sources::{BNpcSource, JsonFile},
+    containers::BNpcContainer,
+};
+
+/// keeps a copy of another source's mapping for each game version, so a
+/// database can be generated again for the same patch without going back to
+/// the source
+pub struct PatchCache {
+    inner: Box<dyn BNpcSource>,
+    /// `None` if the game version could not be read, in which case nothing is
+    /// cached
+    file: Option<JsonFile>,
+}
+
+impl PatchCache {
+    pub fn new(inner: Box<dyn BNpcSource>, dir: &Path, game_version: Option<&str>) -> Self {
+        let file = game_version.map(|version| JsonFile::new(dir.join(format!("{version}.json"))));
+        Self { inner, file }
+    }
+
+    fn path(&self) -> Option<&Path> {
+        self.file.as_ref().map(JsonFile::path)
+    }
+}
+
+impl BNpcSource for PatchCache {
+    fn describe(&self) -> String {
+        match &self.file {
+            Some(file) if file.path().exists() => file.describe(),
+            _ => self.inner.describe(),
+        }
+    }
+
+    fn load(&self) -> anyhow::Result<BNpcContainer> {
+        if let Some(file) = &self.file
+            && file.path().exists()
+        {
+            return file.load();
+        }
+
+        let bnpcs = self.inner.load()?;
+        if let Some(path) = self.path() {
+            if let Some(dir) = path.parent() {
+                std::fs::create_dir_all(dir)?;
+            }
+
+            let file = BufWriter::new(File::create(path)?);
+            serde_json::to_writer_pretty(file, &bnpcs)?;
+        }
+
+        Ok(bnpcs)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        bnpc_sources::{BNpcSource, PatchCache},
+        containers::{BNpcContainer, BNpcMapEntry},
+        test::{bnpc_pairs, temp_path},
+    };
+
+    /// a source that can only be loaded from once
+    struct Once(Option<(u32, u32)>);
+
+    impl BNpcSource for Once {
+        fn describe(&self) -> String {
+            "once".into()
+        }
+
+        fn load(&self) -> anyhow::Result<BNpcContainer> {
+            let (bnpc_base, bnpc_name) = match self.0 {
+                Some(entry) => entry,
+                None => anyhow::bail!("already loaded"),
+            };
+
+            Ok(BNpcContainer {
+                bnpc: vec![BNpcMapEntry {
+                    bnpc_base,
+                    bnpc_name,
+                }],
+            })
+        }
+    }
+
+    #[test]
+    fn writes_then_reuses() {
+        let dir = temp_path("patch-cache");
+        let version = Some("2024.01.01.0000.0000");
+        let path = dir.join("2024.01.01.0000.0000.json");
+
+        let cache = PatchCache::new(Box::new(Once(Some((1, 2)))), &dir, version);
+        assert_eq!("once", cache.describe());
+        assert_eq!(vec![(1, 2)], bnpc_pairs(&cache.load().unwrap()));
+        assert!(path.exists());
+
+        // the same version is read back from the file
+        let cache = PatchCache::new(Box::new(Once(None)), &dir, version);
+        assert_eq!(path.display().to_string(), cache.describe());
+        let loaded = cache.load();
+
+        // without a version nothing is written or read
+        let uncached = PatchCache::new(Box::new(Once(None)), &dir, None);
+        let uncached = uncached.load();
+
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert_eq!(vec![(1, 2)], bnpc_pairs(&loaded.unwrap()));
+        assert!(uncached.is_err());
+    }
+}
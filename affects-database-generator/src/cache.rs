@@ -0,0 +1,322 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    fs::File,
+    io::{BufReader, BufWriter},
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+use affects_common::Affects;
+use imc::ImcFile;
+use ironworks::{Ironworks, excel::Excel};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    formats::{avfx::AvfxFile, raw::RawFile},
+    report::Anomaly,
+    schema::{ActionTimeline, Item, MetadataExtractor, MetadataProvider, ModelChara},
+};
+
+/// fnv-1a. unlike the std hasher its output is the same between builds, so
+/// the hashes can be kept on disk
+pub struct StableHasher(u64);
+
+impl Default for StableHasher {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl std::hash::Hasher for StableHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+}
+
+pub fn hash_bytes(bytes: &[u8]) -> u64 {
+    use std::hash::Hasher;
+
+    let mut hasher = StableHasher::default();
+    hasher.write(bytes);
+    hasher.finish()
+}
+
+/// files and sheets that more than one analyser reads, so each is only read
+/// once per run. shared between the analyser threads
+#[derive(Default)]
+pub struct GeneratorCache {
+    /// imc path => hash of its contents, or `None` if it does not exist
+    imc_paths: Mutex<HashMap<String, Option<u64>>>,
+    /// content hash => parsed imc file. many models share identical imc files
+    imcs: Mutex<HashMap<u64, Arc<ImcFile>>>,
+    /// content hash => why the imc file could not be parsed
+    invalid_imcs: Mutex<HashMap<u64, String>>,
+    /// effect path => the paths of the textures it draws
+    effect_textures: Mutex<HashMap<String, Vec<String>>>,
+    model_charas: Mutex<Option<Arc<BTreeMap<u32, ModelChara>>>>,
+    items: Mutex<Option<Arc<BTreeMap<u32, Item<'static>>>>>,
+    action_timelines: Mutex<Option<Arc<BTreeMap<u32, ActionTimeline<'static>>>>>,
+    /// sheet name => hash of its rows
+    sheet_hashes: Mutex<HashMap<String, u64>>,
+}
+
+/// why an imc file could not be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ImcError {
+    Missing,
+    Invalid(String),
+}
+
+impl ImcError {
+    /// the anomaly to record for the imc file at `path`
+    pub fn anomaly(self, path: String) -> Anomaly {
+        match self {
+            Self::Missing => Anomaly::MissingImc { path },
+            Self::Invalid(error) => Anomaly::InvalidImc { path, error },
+        }
+    }
+}
+
+impl GeneratorCache {
+    pub fn imc(&self, ironworks: &Ironworks, path: &str) -> Result<Arc<ImcFile>, ImcError> {
+        let known = self.imc_paths.lock().unwrap().get(path).copied();
+        if let Some(hash) = known {
+            return match hash {
+                Some(hash) => self.parsed_imc(hash),
+                None => Err(ImcError::Missing),
+            };
+        }
+
+        // read without holding the locks. two threads may read the same file,
+        // but they end up storing the same thing
+        let data = ironworks.file::<RawFile>(path).ok();
+        let hash = data.as_ref().map(|data| hash_bytes(&data.0));
+        self.imc_paths
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), hash);
+
+        let (data, hash) = data.zip(hash).ok_or(ImcError::Missing)?;
+        match self.parsed_imc(hash) {
+            Err(ImcError::Missing) => {}
+            parsed => return parsed,
+        }
+
+        match ImcFile::from_bytes(&data.0) {
+            Ok(imc) => {
+                let imc = Arc::new(imc);
+                self.imcs.lock().unwrap().insert(hash, Arc::clone(&imc));
+                Ok(imc)
+            }
+            Err(error) => {
+                let error = error.to_string();
+                self.invalid_imcs
+                    .lock()
+                    .unwrap()
+                    .insert(hash, error.clone());
+                Err(ImcError::Invalid(error))
+            }
+        }
+    }
+
+    /// the imc file with the contents hashed to `hash`, or why it could not
+    /// be parsed. files that were not parsed yet are missing
+    fn parsed_imc(&self, hash: u64) -> Result<Arc<ImcFile>, ImcError> {
+        if let Some(imc) = self.imcs.lock().unwrap().get(&hash) {
+            return Ok(Arc::clone(imc));
+        }
+
+        match self.invalid_imcs.lock().unwrap().get(&hash) {
+            Some(error) => Err(ImcError::Invalid(error.clone())),
+            None => Err(ImcError::Missing),
+        }
+    }
+
+    /// the paths of the textures the effect at `path` draws. effects that
+    /// cannot be read draw none
+    pub fn effect_textures(&self, ironworks: &Ironworks, path: &str) -> Vec<String> {
+        if let Some(textures) = self.effect_textures.lock().unwrap().get(path) {
+            return textures.clone();
+        }
+
+        // read without holding the lock, as imc files are
+        let textures = ironworks
+            .file::<AvfxFile>(path)
+            .map(|avfx| avfx.texture_paths)
+            .unwrap_or_default();
+        self.effect_textures
+            .lock()
+            .unwrap()
+            .insert(path.to_string(), textures.clone());
+
+        textures
+    }
+
+    /// every row of the ModelChara sheet by row id
+    pub fn model_charas(&self, excel: &Excel) -> anyhow::Result<Arc<BTreeMap<u32, ModelChara>>> {
+        read_rows(&self.model_charas, excel, |mc| mc.row_id)
+    }
+
+    /// every row of the Item sheet by row id
+    pub fn items(&self, excel: &Excel) -> anyhow::Result<Arc<BTreeMap<u32, Item<'static>>>> {
+        read_rows(&self.items, excel, |item| item.row_id)
+    }
+
+    /// every row of the ActionTimeline sheet by row id
+    pub fn action_timelines(
+        &self,
+        excel: &Excel,
+    ) -> anyhow::Result<Arc<BTreeMap<u32, ActionTimeline<'static>>>> {
+        read_rows(&self.action_timelines, excel, |tl| tl.row_id)
+    }
+
+    /// a hash of every row of a sheet, used to tell whether it changed since
+    /// the previous run
+    pub fn sheet_hash<S: MetadataExtractor + Debug>(&self, excel: &Excel) -> anyhow::Result<u64> {
+        let name = S::name();
+        if let Some(&hash) = self.sheet_hashes.lock().unwrap().get(&name) {
+            return Ok(hash);
+        }
+
+        let mut rows = String::new();
+        for row in excel.sheet(MetadataProvider::<S>::for_sheet())? {
+            rows.push_str(&format!("{:?}\n", row?));
+        }
+
+        let hash = hash_bytes(rows.as_bytes());
+        self.sheet_hashes.lock().unwrap().insert(name, hash);
+
+        Ok(hash)
+    }
+
+    fn load_imcs(&self, stored: StoredImcs) {
+        self.imc_paths.lock().unwrap().extend(stored.paths);
+        self.imcs.lock().unwrap().extend(
+            stored
+                .files
+                .into_iter()
+                .map(|(hash, imc)| (hash, Arc::new(imc))),
+        );
+        self.invalid_imcs.lock().unwrap().extend(stored.invalid);
+    }
+
+    fn stored_imcs(&self) -> StoredImcs {
+        StoredImcs {
+            paths: self
+                .imc_paths
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(path, &hash)| (path.clone(), hash))
+                .collect(),
+            files: self
+                .imcs
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(&hash, imc)| (hash, ImcFile::clone(imc)))
+                .collect(),
+            invalid: self
+                .invalid_imcs
+                .lock()
+                .unwrap()
+                .iter()
+                .map(|(&hash, error)| (hash, error.clone()))
+                .collect(),
+        }
+    }
+}
+
+/// every row of the sheet `S` by row id, read into `cached` the first time
+/// it is asked for
+fn read_rows<S: MetadataExtractor>(
+    cached: &Mutex<Option<Arc<BTreeMap<u32, S>>>>,
+    excel: &Excel,
+    row_id: fn(&S) -> u32,
+) -> anyhow::Result<Arc<BTreeMap<u32, S>>> {
+    // held while reading, so threads asking at the same time wait for the
+    // first one rather than each reading the sheet
+    let mut cached = cached.lock().unwrap();
+    if let Some(rows) = &*cached {
+        return Ok(Arc::clone(rows));
+    }
+
+    let rows = excel
+        .sheet(MetadataProvider::<S>::for_sheet())?
+        .into_iter()
+        .map(|row| row.map(|row| (row_id(&row), row)))
+        .collect::<Result<BTreeMap<_, _>, _>>()?;
+    let rows = Arc::new(rows);
+    *cached = Some(Arc::clone(&rows));
+
+    Ok(rows)
+}
+
+/// what is kept on disk between runs for `--cache`
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct StoredCache {
+    /// the game version the imc files were read from. the stored imc files
+    /// are only reused for the same version
+    pub game_version: Option<String>,
+    imcs: StoredImcs,
+    /// analyser name => what it added to the database last time
+    pub fragments: BTreeMap<String, Fragment>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StoredImcs {
+    paths: BTreeMap<String, Option<u64>>,
+    files: BTreeMap<u64, ImcFile>,
+    /// content hash => why the file could not be parsed
+    #[serde(default)]
+    invalid: BTreeMap<u64, String>,
+}
+
+/// the output of a single analyser, kept so it can be reused while none of
+/// its inputs change
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Fragment {
+    /// see [`crate::analysers::Analyser::inputs`]
+    pub inputs: u64,
+    pub affects: Affects,
+    #[serde(default)]
+    pub anomalies: Vec<Anomaly>,
+}
+
+impl StoredCache {
+    /// a missing or unreadable cache is treated as empty
+    pub fn load(path: &Path) -> Self {
+        File::open(path)
+            .ok()
+            .and_then(|file| serde_json::from_reader(BufReader::new(file)).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer(file, self)?;
+
+        Ok(())
+    }
+
+    /// seeds `cache` with the imc files read last time, if they were read
+    /// from the same game version
+    pub fn restore_imcs(&mut self, cache: &GeneratorCache, game_version: Option<&str>) {
+        if game_version.is_some() && self.game_version.as_deref() == game_version {
+            cache.load_imcs(std::mem::take(&mut self.imcs));
+        }
+    }
+
+    pub fn store_imcs(&mut self, cache: &GeneratorCache, game_version: Option<String>) {
+        self.game_version = game_version;
+        self.imcs = cache.stored_imcs();
+    }
+}
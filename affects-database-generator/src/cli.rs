@@ -1,16 +1,52 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use clap::{Parser, ValueEnum};
 
 #[derive(Parser, Debug)]
 #[command(version)]
 pub struct CliArguments {
     #[arg(short, long)]
     pub game_path: PathBuf,
+    /// the battle npc base => name mapping: `download` to fetch it from
+    /// teamcraft, or a json or csv file
     #[arg(short, long)]
     pub bnpc_path: String,
+    /// a directory to keep the bnpc mapping for each game version in. once
+    /// a version's mapping is kept, it is used instead of `--bnpc-path`
+    #[arg(long)]
+    pub bnpc_cache: Option<PathBuf>,
     #[arg(short, long, default_value_t = false)]
     pub pretty: bool,
     #[arg(short, long)]
     pub output: PathBuf,
+    #[arg(short, long, value_enum, default_value_t = OutputFormat::Json)]
+    pub format: OutputFormat,
+    /// where to keep the index of every name between runs, so names keep
+    /// their index across databases. without this, names are numbered in
+    /// sorted order
+    #[arg(long)]
+    pub name_indices: Option<PathBuf>,
+    /// fail instead of writing the database if any anomalies are found. the
+    /// report is written either way
+    #[arg(long, default_value_t = false)]
+    pub strict: bool,
+    /// where to keep analyser output and imc files between runs
+    #[arg(long)]
+    pub cache: Option<PathBuf>,
+    /// reuse the output of analysers whose input sheets are unchanged since
+    /// the run that wrote `--cache`, by the same generator on the same game
+    /// version. nothing is reused when the game version cannot be read
+    #[arg(long, requires = "cache", default_value_t = false)]
+    pub incremental: bool,
+    /// an analyser to run even if its output could be reused, such as
+    /// "Battle NPCs"
+    #[arg(long)]
+    pub rerun: Vec<String>,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Json,
+    /// see `affects_common::AffectsView`. `--pretty` has no effect
+    Binary,
 }
@@ -1,4 +1,4 @@
 mod bnpcs;
 mod database;
 
-pub use self::bnpcs::{BNpcContainer, GraphqlContainer};
+pub use self::bnpcs::{BNpcContainer, BNpcMapEntry, GraphqlContainer};
@@ -0,0 +1,34 @@
+use std::io::Read;
+
+use ironworks::file::File;
+
+/// the parts of an effect (.avfx) file that are of interest. only the paths of
+/// the textures it draws are read, which are kept as strings in its texture
+/// blocks
+#[derive(Debug)]
+pub struct AvfxFile {
+    pub texture_paths: Vec<String>,
+}
+
+impl File for AvfxFile {
+    fn read(mut stream: impl ironworks::FileStream) -> std::result::Result<Self, ironworks::Error> {
+        let mut data = Vec::new();
+        stream
+            .read_to_end(&mut data)
+            .map_err(|e| ironworks::Error::Resource(Box::new(e)))?;
+
+        // the blocks around the paths are not parsed, so the paths are found
+        // by their prefix and extension
+        let texture_paths = data
+            .split(|&b| b == 0)
+            .filter_map(|s| {
+                let start = s.windows(4).position(|w| w == b"vfx/")?;
+                std::str::from_utf8(&s[start..]).ok()
+            })
+            .filter(|s| s.ends_with(".atex"))
+            .map(ToString::to_string)
+            .collect();
+
+        Ok(Self { texture_paths })
+    }
+}
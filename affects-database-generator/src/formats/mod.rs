@@ -1 +1,4 @@
-pub mod imc;
+pub mod avfx;
+pub mod mtrl;
+pub mod raw;
+pub mod tmb;
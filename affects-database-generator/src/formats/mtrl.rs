@@ -0,0 +1,38 @@
+use std::io::Read;
+
+use ironworks::file::File;
+
+/// the parts of a material (.mtrl) file that are of interest. only the
+/// string table is inspected, which is where the paths of the textures the
+/// material uses are kept
+#[derive(Debug)]
+pub struct MtrlFile {
+    pub strings: Vec<String>,
+}
+
+impl MtrlFile {
+    pub fn texture_paths(&self) -> impl Iterator<Item = &str> {
+        self.strings
+            .iter()
+            .map(String::as_str)
+            .filter(|s| s.contains('/') && s.ends_with(".tex"))
+    }
+}
+
+impl File for MtrlFile {
+    fn read(mut stream: impl ironworks::FileStream) -> std::result::Result<Self, ironworks::Error> {
+        let mut data = Vec::new();
+        stream
+            .read_to_end(&mut data)
+            .map_err(|e| ironworks::Error::Resource(Box::new(e)))?;
+
+        let strings = data
+            .split(|&b| b == 0)
+            .filter(|s| !s.is_empty())
+            .filter_map(|s| std::str::from_utf8(s).ok())
+            .map(ToString::to_string)
+            .collect();
+
+        Ok(Self { strings })
+    }
+}
@@ -0,0 +1,19 @@
+use std::io::Read;
+
+use ironworks::file::File;
+
+/// the unparsed contents of a file, for when they need to be hashed as well
+/// as parsed
+#[derive(Debug)]
+pub struct RawFile(pub Vec<u8>);
+
+impl File for RawFile {
+    fn read(mut stream: impl ironworks::FileStream) -> std::result::Result<Self, ironworks::Error> {
+        let mut data = Vec::new();
+        stream
+            .read_to_end(&mut data)
+            .map_err(|e| ironworks::Error::Resource(Box::new(e)))?;
+
+        Ok(Self(data))
+    }
+}
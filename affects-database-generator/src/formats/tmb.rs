@@ -0,0 +1,45 @@
+use std::io::Read;
+
+use ironworks::file::File;
+
+/// the parts of a timeline (.tmb) file that are of interest. only the string
+/// table is inspected, which is where the paths of any effects and sounds
+/// played by the timeline are kept
+#[derive(Debug)]
+pub struct TmbFile {
+    pub strings: Vec<String>,
+}
+
+impl TmbFile {
+    pub fn vfx_paths(&self) -> impl Iterator<Item = &str> {
+        self.strings
+            .iter()
+            .map(String::as_str)
+            .filter(|s| s.starts_with("vfx/") && (s.ends_with(".avfx") || s.ends_with(".atex")))
+    }
+
+    pub fn sound_paths(&self) -> impl Iterator<Item = &str> {
+        self.strings
+            .iter()
+            .map(String::as_str)
+            .filter(|s| s.ends_with(".scd"))
+    }
+}
+
+impl File for TmbFile {
+    fn read(mut stream: impl ironworks::FileStream) -> std::result::Result<Self, ironworks::Error> {
+        let mut data = Vec::new();
+        stream
+            .read_to_end(&mut data)
+            .map_err(|e| ironworks::Error::Resource(Box::new(e)))?;
+
+        let strings = data
+            .split(|&b| b == 0)
+            .filter(|s| !s.is_empty())
+            .filter_map(|s| std::str::from_utf8(s).ok())
+            .map(ToString::to_string)
+            .collect();
+
+        Ok(Self { strings })
+    }
+}
@@ -0,0 +1,48 @@
+use std::{path::Path, sync::Arc};
+
+use ironworks::{
+    Ironworks,
+    excel::{Excel, Language},
+    sqpack::{Install, SqPack},
+};
+
+/// the game files the analysers read from, along with an excel reader for
+/// every language names are translated into
+pub struct GameData {
+    pub ironworks: Arc<Ironworks>,
+    pub excel: Excel,
+    /// readers for the other languages the global client ships, which are
+    /// only used to translate names
+    pub localised_excels: [(affects_common::Language, Excel); 3],
+}
+
+impl GameData {
+    /// reads from the game install at `path`
+    pub fn install(path: &Path) -> Self {
+        Self::new(Ironworks::new().with_resource(SqPack::new(Install::at(path))))
+    }
+
+    /// reads from whichever resources `ironworks` was given, such as the
+    /// in-memory fixtures the analyser tests use
+    pub fn new(ironworks: Ironworks) -> Self {
+        let ironworks = Arc::new(ironworks);
+        let excel = Excel::new(Arc::clone(&ironworks)).with_default_language(Language::English);
+        let localised_excels = [
+            (affects_common::Language::Japanese, Language::Japanese),
+            (affects_common::Language::German, Language::German),
+            (affects_common::Language::French, Language::French),
+        ]
+        .map(|(language, excel_language)| {
+            (
+                language,
+                Excel::new(Arc::clone(&ironworks)).with_default_language(excel_language),
+            )
+        });
+
+        Self {
+            ironworks,
+            excel,
+            localised_excels,
+        }
+    }
+}
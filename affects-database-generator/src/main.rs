@@ -1,141 +1,311 @@
 use std::{
     collections::BTreeMap,
     fs::File,
-    io::{BufReader, BufWriter, Write},
-    sync::Arc,
-    time::Instant,
+    io::{BufWriter, Write},
+    path::Path,
+    time::{Duration, Instant, SystemTime},
 };
 
-use affects_common::Affects;
+use affects_common::{Affects, Metadata, SCHEMA_VERSION};
+use anyhow::Context;
 use clap::Parser;
-use ironworks::{
-    Ironworks,
-    excel::{Excel, Language},
-    sqpack::{Install, SqPack},
-};
 
 use crate::{
-    analysers::GeneratorContext,
-    cli::CliArguments,
-    containers::{BNpcContainer, GraphqlContainer},
+    analysers::{ANALYSERS, Analyser, GeneratorContext},
+    bnpc_sources::PatchCache,
+    cache::{Fragment, GeneratorCache, StoredCache},
+    cli::{CliArguments, OutputFormat},
+    game::GameData,
+    names::NameIndices,
+    report::{Anomaly, Report},
 };
 
 mod analysers;
+mod bnpc_sources;
+mod cache;
 mod cli;
 mod containers;
 mod formats;
+mod game;
+mod names;
+mod report;
 mod schema;
+#[cfg(test)]
+mod test;
 
-fn time(name: &str, mut f: impl FnMut()) {
+fn time<T>(name: &str, f: impl FnOnce() -> T) -> T {
     print!("{name}...");
     std::io::stdout().flush().ok();
     let start = Instant::now();
-    f();
+    let result = f();
     println!(" {}ms", start.elapsed().as_millis());
+
+    result
 }
 
-fn main() {
+fn main() -> anyhow::Result<()> {
     let args = CliArguments::parse();
 
+    let game_version = read_game_version(&args.game_path);
+
     // handle bnpcs
-    let bnpcs = if args.bnpc_path == "download" {
-        println!("downloading bnpc data...");
-        let bnpcs = ureq::post("https://gubal.ffxivteamcraft.com/graphql")
-            .send_json(serde_json::json!({
-                "query": "query { bnpc { bnpcBase, bnpcName } }",
-            }))
-            .unwrap()
-            .body_mut()
-            .read_json::<GraphqlContainer<BNpcContainer>>()
-            .unwrap()
-            .data;
-
-        let mut bnpcs_file = BufWriter::new(File::create("bnpcs.json").unwrap());
-        serde_json::to_writer_pretty(&mut bnpcs_file, &bnpcs).unwrap();
-
-        bnpcs
-    } else {
-        let bnpcs_file = BufReader::new(File::open(&args.bnpc_path).unwrap());
-        serde_json::from_reader::<_, BNpcContainer>(bnpcs_file).unwrap()
-    };
+    let mut bnpc_source = bnpc_sources::source_for(&args.bnpc_path);
+    if let Some(dir) = &args.bnpc_cache {
+        bnpc_source = Box::new(PatchCache::new(bnpc_source, dir, game_version.as_deref()));
+    }
+
+    println!("reading bnpc data from {}...", bnpc_source.describe());
+    let bnpcs = bnpc_source.load().context("could not read bnpc data")?;
+    if args.bnpc_path == "download" {
+        let mut bnpcs_file = BufWriter::new(File::create("bnpcs.json")?);
+        serde_json::to_writer_pretty(&mut bnpcs_file, &bnpcs)?;
+    }
 
     // initialise ironworks
-    let ironworks =
-        Arc::new(Ironworks::new().with_resource(SqPack::new(Install::at(&args.game_path))));
-    let excel = Excel::new(Arc::clone(&ironworks)).with_default_language(Language::English);
+    let game = GameData::install(&args.game_path);
 
     // main object
     let mut affects = Affects::default();
-    let mut name_map: BTreeMap<String, u16> = Default::default();
+    let mut name_map: BTreeMap<String, u32> = Default::default();
+    let mut report = Report::default();
+    let mut imc_anomalies = Vec::new();
+
+    let bnpc_anomalies = time("Checking bnpc data", || {
+        bnpc_sources::validate(&bnpcs, &game.excel)
+    })?;
+    report.add("BNpc data", bnpc_anomalies);
+
+    let cache = GeneratorCache::default();
+    let mut stored = args
+        .cache
+        .as_deref()
+        .map(StoredCache::load)
+        .unwrap_or_default();
+    if args.incremental {
+        stored.restore_imcs(&cache, game_version.as_deref());
+    }
 
     let mut ctx = GeneratorContext {
         affects: &mut affects,
-        excel: &excel,
-        ironworks: &ironworks,
+        excel: &game.excel,
+        localised_excels: &game.localised_excels,
+        ironworks: &game.ironworks,
+        cache: &cache,
         name_map: &mut name_map,
         bnpcs: &bnpcs,
+        anomalies: &mut imc_anomalies,
     };
 
     let overall = Instant::now();
 
-    time("Items", || {
-        crate::analysers::analyse_items(&mut ctx);
-    });
+    // the sheet analysers are independent of each other, so each runs on its
+    // own thread. their output is merged in the order they are listed, which
+    // keeps the name indices the same however the threads are scheduled
+    let outputs = std::thread::scope(|scope| {
+        let ctx = &ctx;
+        let args = &args;
+        let game_version = game_version.as_deref();
+        let handles = ANALYSERS
+            .iter()
+            .map(|analyser| {
+                let previous = stored.fragments.remove(analyser.name);
+                scope.spawn(move || run_analyser(analyser, ctx, previous, args, game_version))
+            })
+            .collect::<Vec<_>>();
 
-    time("Emotes", || {
-        crate::analysers::analyse_emotes(&mut ctx);
+        handles
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+            })
+            .collect::<Vec<_>>()
     });
 
-    time("Battle NPCs", || {
-        crate::analysers::analyse_bnpcs(&mut ctx);
-    });
+    for (analyser, output) in ANALYSERS.iter().zip(outputs) {
+        let output = output.with_context(|| format!("{} analyser failed", analyser.name))?;
+        let cached = if output.cached { " (cached)" } else { "" };
+        println!(
+            "{}... {}ms{cached}",
+            analyser.name,
+            output.elapsed.as_millis()
+        );
 
-    time("Event NPCs", || {
-        crate::analysers::analyse_enpcs(&mut ctx);
-    });
-
-    time("Actions", || {
-        crate::analysers::analyse_actions(&mut ctx);
-    });
+        ctx.merge(&output.affects)?;
+        report.add(analyser.name, output.anomalies.clone());
+        if let Some(inputs) = output.inputs {
+            let fragment = Fragment {
+                inputs,
+                affects: output.affects,
+                anomalies: output.anomalies,
+            };
+            stored.fragments.insert(analyser.name.to_string(), fragment);
+        }
+    }
 
-    time("Minions", || {
-        crate::analysers::analyse_minions(&mut ctx);
-    });
+    time("Equipment IMC", || {
+        crate::analysers::imc::analyse_equipment_imcs(&mut ctx)
+    })?;
 
-    time("Mounts", || {
-        crate::analysers::analyse_mounts(&mut ctx);
-    });
+    time("Weapon IMC", || {
+        crate::analysers::imc::analyse_weapon_imcs(&mut ctx)
+    })?;
 
-    time("Ornaments", || {
-        crate::analysers::analyse_ornaments(&mut ctx);
-    });
+    time("Monster IMC", || {
+        crate::analysers::imc::analyse_monster_imcs(&mut ctx)
+    })?;
 
-    time("Maps", || {
-        crate::analysers::analyse_maps(&mut ctx);
-    });
+    time("Name indices", || {
+        let mut name_indices = match &args.name_indices {
+            Some(path) => NameIndices::load(path)?,
+            None => NameIndices::default(),
+        };
+        name_indices.assign(ctx.affects);
 
-    time("Equipment IMC", || {
-        crate::analysers::imc::analyse_equipment_imcs(&mut ctx);
-    });
+        if let Some(path) = &args.name_indices {
+            name_indices.save(path)?;
+        }
 
-    time("Weapon IMC", || {
-        crate::analysers::imc::analyse_weapon_imcs(&mut ctx);
-    });
+        anyhow::Ok(())
+    })?;
 
-    time("Monster IMC", || {
-        crate::analysers::imc::analyse_monster_imcs(&mut ctx);
-    });
+    report.add("IMC", imc_anomalies);
 
     println!("=== {}ms overall ===", overall.elapsed().as_millis());
 
-    time("Saving", || {
-        let mut affects_file = BufWriter::new(File::create(&args.output).unwrap());
-        if args.pretty {
-            serde_json::to_writer_pretty(&mut affects_file, &affects).unwrap();
-        } else {
-            serde_json::to_writer(&mut affects_file, &affects).unwrap();
+    affects.metadata = Metadata {
+        schema_version: SCHEMA_VERSION,
+        game_version: game_version.clone(),
+        generated_at: SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|since| since.as_secs())
+            .unwrap_or_default(),
+        generator_version: env!("CARGO_PKG_VERSION").into(),
+    };
+
+    // the report goes next to the database, such as affects.report.json
+    let report_path = args.output.with_extension("report.json");
+    report.save(&report_path)?;
+    if !report.is_empty() {
+        println!(
+            "{} anomalies found, see {}",
+            report.len(),
+            report_path.display()
+        );
+
+        if args.strict {
+            anyhow::bail!("anomalies found with --strict");
         }
-    });
+    }
+
+    time("Saving", || match args.format {
+        OutputFormat::Json => {
+            let mut affects_file = BufWriter::new(File::create(&args.output)?);
+            if args.pretty {
+                serde_json::to_writer_pretty(&mut affects_file, &affects)?;
+            } else {
+                serde_json::to_writer(&mut affects_file, &affects)?;
+            }
+
+            anyhow::Ok(())
+        }
+        OutputFormat::Binary => {
+            std::fs::write(&args.output, affects.to_binary()?)?;
+
+            Ok(())
+        }
+    })?;
+
+    if let Some(cache_path) = &args.cache {
+        time("Saving cache", || {
+            stored.store_imcs(&cache, game_version.clone());
+            stored.save(cache_path)
+        })?;
+    }
 
     println!("Done.");
+
+    Ok(())
+}
+
+struct AnalyserOutput {
+    affects: Affects,
+    /// see [`Analyser::inputs`]. only worked out when there is a cache to
+    /// store the output in, and the game version is known
+    inputs: Option<u64>,
+    anomalies: Vec<Anomaly>,
+    cached: bool,
+    elapsed: Duration,
+}
+
+/// runs an analyser into a database of its own. with `--incremental`, the
+/// output of the previous run is used instead if the analyser's inputs are
+/// unchanged
+fn run_analyser(
+    analyser: &Analyser,
+    ctx: &GeneratorContext,
+    previous: Option<Fragment>,
+    args: &CliArguments,
+    game_version: Option<&str>,
+) -> anyhow::Result<AnalyserOutput> {
+    let start = Instant::now();
+    let inputs = if args.cache.is_some() {
+        analyser.inputs(ctx, game_version)?
+    } else {
+        None
+    };
+    let rerun = args
+        .rerun
+        .iter()
+        .any(|name| name.eq_ignore_ascii_case(analyser.name));
+
+    if args.incremental
+        && !rerun
+        && let Some(inputs) = inputs
+        && let Some(previous) = previous
+        && previous.inputs == inputs
+    {
+        return Ok(AnalyserOutput {
+            affects: previous.affects,
+            inputs: Some(inputs),
+            anomalies: previous.anomalies,
+            cached: true,
+            elapsed: start.elapsed(),
+        });
+    }
+
+    let mut affects = Affects::default();
+    let mut name_map = BTreeMap::new();
+    let mut anomalies = Vec::new();
+    (analyser.run)(&mut GeneratorContext {
+        affects: &mut affects,
+        ironworks: ctx.ironworks,
+        cache: ctx.cache,
+        excel: ctx.excel,
+        localised_excels: ctx.localised_excels,
+        name_map: &mut name_map,
+        bnpcs: ctx.bnpcs,
+        anomalies: &mut anomalies,
+    })?;
+
+    Ok(AnalyserOutput {
+        affects,
+        inputs,
+        anomalies,
+        cached: false,
+        elapsed: start.elapsed(),
+    })
+}
+
+/// reads `ffxivgame.ver`, accepting either the install root or its `game`
+/// directory
+fn read_game_version(game_path: &Path) -> Option<String> {
+    [
+        game_path.join("game").join("ffxivgame.ver"),
+        game_path.join("ffxivgame.ver"),
+    ]
+    .iter()
+    .find_map(|path| std::fs::read_to_string(path).ok())
+    .map(|version| version.trim().to_string())
 }
@@ -0,0 +1,190 @@
+use std::{
+    collections::BTreeMap,
+    fs::File,
+    io::{BufReader, BufWriter, ErrorKind},
+    path::Path,
+};
+
+use affects_common::Affects;
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+
+use crate::analysers::merge_tables;
+
+/// english name => index. kept between runs so a name keeps its index in
+/// every database generated after it first appeared
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct NameIndices(BTreeMap<String, u32>);
+
+impl NameIndices {
+    /// a missing file is treated as empty. any other error opening it is
+    /// returned, rather than numbering every name from scratch
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        match File::open(path) {
+            Ok(file) => Ok(serde_json::from_reader(BufReader::new(file))?),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(Self::default()),
+            Err(error) => Err(error).with_context(|| format!("could not open {}", path.display())),
+        }
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(file, self)?;
+
+        Ok(())
+    }
+
+    /// renumbers the names in `affects`. names that already have an index
+    /// keep it, and new names are sorted and numbered after the highest
+    /// index, so the result does not depend on the order the analysers added
+    /// them in. indices of names that are no longer in the game are left
+    /// empty rather than handed out again
+    pub fn assign(&mut self, affects: &mut Affects) {
+        let mut new_names = affects
+            .names
+            .iter()
+            .filter(|name| !self.0.contains_key(*name))
+            .collect::<Vec<_>>();
+        new_names.sort();
+
+        let mut next = self.0.values().max().map_or(0, |&idx| idx + 1);
+        for name in new_names {
+            self.0.insert(name.clone(), next);
+            next += 1;
+        }
+
+        let remap = affects
+            .names
+            .iter()
+            .map(|name| self.0[name])
+            .collect::<Vec<_>>();
+        let len = remap.iter().max().map_or(0, |&idx| idx as usize + 1);
+
+        let mut renumbered = Affects {
+            metadata: std::mem::take(&mut affects.metadata),
+            names: vec![String::new(); len],
+            ..Default::default()
+        };
+
+        for (idx, name) in affects.names.iter().enumerate() {
+            renumbered.names[remap[idx] as usize] = name.clone();
+        }
+
+        for (language, names) in &affects.localised_names {
+            let mut localised = vec![String::new(); len];
+            for (idx, name) in names.iter().enumerate() {
+                localised[remap[idx] as usize] = name.clone();
+            }
+
+            renumbered.localised_names.insert(*language, localised);
+        }
+
+        for (idx, kinds) in &affects.name_kinds {
+            renumbered
+                .name_kinds
+                .insert(remap[*idx as usize], kinds.clone());
+        }
+
+        merge_tables(&mut renumbered, affects, &remap);
+        *affects = renumbered;
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use affects_common::{Affects, ItemKind};
+
+    use super::NameIndices;
+    use crate::test::temp_path;
+
+    /// a database with a map for each of `names`, in the order given
+    fn database(names: &[&str]) -> Affects {
+        Affects {
+            names: names.iter().map(|name| name.to_string()).collect(),
+            name_kinds: (0..names.len() as u32)
+                .map(|idx| (idx, BTreeSet::from([ItemKind::Map])))
+                .collect(),
+            maps: names
+                .iter()
+                .enumerate()
+                .map(|(idx, name)| {
+                    (
+                        name.to_lowercase(),
+                        BTreeSet::from([(ItemKind::Map, idx as u32)]),
+                    )
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn map_idx(affects: &Affects, name: &str) -> u32 {
+        affects.maps[&name.to_lowercase()].first().unwrap().1
+    }
+
+    #[test]
+    fn keeps_previous_indices() {
+        let mut indices = NameIndices(BTreeMap::from([
+            ("Gridania".into(), 0),
+            ("Mor Dhona".into(), 1),
+        ]));
+
+        let mut affects = database(&["Limsa Lominsa", "Gridania", "Answers"]);
+        indices.assign(&mut affects);
+
+        // names that are gone keep their index, which is left empty
+        assert_eq!(
+            vec!["Gridania", "", "Answers", "Limsa Lominsa"],
+            affects.names
+        );
+        for name in ["Gridania", "Answers", "Limsa Lominsa"] {
+            assert_eq!(name, affects.names[map_idx(&affects, name) as usize]);
+        }
+        assert_eq!(
+            BTreeSet::from([0, 2, 3]),
+            affects.name_kinds.keys().copied().collect::<BTreeSet<_>>()
+        );
+
+        // a later run finding the names in another order gives the same
+        // indices, and new names go after them
+        let mut later = database(&["Ul'dah", "Answers", "Gridania", "Limsa Lominsa"]);
+        indices.assign(&mut later);
+        assert_eq!(
+            vec!["Gridania", "", "Answers", "Limsa Lominsa", "Ul'dah"],
+            later.names
+        );
+        assert_eq!(4, map_idx(&later, "Ul'dah"));
+        assert_eq!(2, map_idx(&later, "Answers"));
+    }
+
+    #[test]
+    fn save_and_load() {
+        let path = temp_path("name-indices.json");
+        let mut indices = NameIndices::default();
+        indices.assign(&mut database(&["Gridania", "Answers"]));
+        indices.save(&path).unwrap();
+
+        let loaded = NameIndices::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(indices.0, loaded.0);
+
+        // a missing file has no indices yet
+        assert!(NameIndices::load(&path).unwrap().0.is_empty());
+    }
+
+    #[test]
+    fn load_errors() {
+        // a file in place of the directory the indices should be in
+        let file = temp_path("name-indices-parent");
+        std::fs::write(&file, "").unwrap();
+        let unopenable = NameIndices::load(&file.join("indices.json"));
+        let invalid = NameIndices::load(&file);
+        std::fs::remove_file(&file).unwrap();
+
+        assert!(unopenable.is_err());
+        assert!(invalid.is_err());
+    }
+}
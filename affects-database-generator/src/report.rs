@@ -0,0 +1,62 @@
+use std::{collections::BTreeMap, fs::File, io::BufWriter, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+/// something in the game data an analyser could not make sense of. the
+/// analyser skips or works around it and carries on, so these are collected
+/// for review rather than stopping the run
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Anomaly {
+    /// an imc file that does not exist
+    MissingImc { path: String },
+    /// an imc file that exists but could not be parsed
+    InvalidImc { path: String, error: String },
+    /// a part or variant an imc file does not have
+    VariantOutOfRange {
+        path: String,
+        part: usize,
+        variant: u8,
+    },
+    /// an imc file with more parts than the model has slots
+    TooManyParts { path: String, parts: usize },
+    /// a ModelChara row with a type that is not known
+    UnknownModelCharaKind { row_id: u32 },
+    /// a row referencing a row of another sheet that does not exist
+    MissingRow { sheet: String, row_id: u32 },
+    /// a CharaMakeType row for a race, tribe or gender without a model
+    UnknownCharaMakeType { row_id: u32 },
+}
+
+/// the anomalies found while generating a database, written next to it
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Report {
+    /// analyser name => what it found, in the order it found it
+    pub anomalies: BTreeMap<String, Vec<Anomaly>>,
+}
+
+impl Report {
+    pub fn add(&mut self, analyser: &str, anomalies: Vec<Anomaly>) {
+        if !anomalies.is_empty() {
+            self.anomalies
+                .entry(analyser.to_string())
+                .or_default()
+                .extend(anomalies);
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.anomalies.values().map(Vec::len).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn save(&self, path: &Path) -> anyhow::Result<()> {
+        let file = BufWriter::new(File::create(path)?);
+        serde_json::to_writer_pretty(file, self)?;
+
+        Ok(())
+    }
+}
@@ -4,11 +4,12 @@ use crate::schema::MetadataExtractor;
 
 #[derive(Debug)]
 pub struct Action<'a> {
+    pub row_id: u32,
     pub name: SeString<'a>,
     pub animation_start: u8,
     pub animation_end: i16,
     pub animation_hit: u16,
-    // pub vfx: u16,
+    pub vfx: u8,
 }
 
 impl MetadataExtractor for Action<'_> {
@@ -23,9 +24,10 @@ impl MetadataExtractor for Action<'_> {
             row,
             [name, 0, into_string],
             [animation_start, 5, into_u8],
-            // [vfx, 6, into_u16],
+            [vfx, 6, into_u8],
             [animation_end, 7, into_i16],
             [animation_hit, 8, into_u16],
+            row_id: row.row_id(),
         );
 
         Ok(item)
@@ -0,0 +1,20 @@
+use crate::schema::MetadataExtractor;
+
+#[derive(Debug)]
+pub struct ActionCastVfx {
+    pub vfx: u16,
+}
+
+impl MetadataExtractor for ActionCastVfx {
+    type Error = super::Error;
+
+    fn name() -> String {
+        "ActionCastVFX".into()
+    }
+
+    fn populate_row(row: ironworks::excel::Row) -> Result<Self, Self::Error> {
+        let item = crate::populate!(row, [vfx, 0, into_u16],);
+
+        Ok(item)
+    }
+}
@@ -4,6 +4,7 @@ use crate::schema::MetadataExtractor;
 
 #[derive(Debug)]
 pub struct ActionTimeline<'a> {
+    pub row_id: u32,
     pub key: SeString<'a>,
 }
 
@@ -15,7 +16,7 @@ impl MetadataExtractor for ActionTimeline<'_> {
     }
 
     fn populate_row(row: ironworks::excel::Row) -> Result<Self, Self::Error> {
-        let item = crate::populate!(row, [key, 6, into_string],);
+        let item = crate::populate!(row, [key, 6, into_string], row_id: row.row_id(),);
 
         Ok(item)
     }
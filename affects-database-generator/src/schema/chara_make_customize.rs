@@ -0,0 +1,29 @@
+use crate::schema::MetadataExtractor;
+
+#[derive(Debug)]
+pub struct CharaMakeCustomize {
+    pub row_id: u32,
+    /// the id of the hair model or face paint decal
+    pub feature_id: u8,
+    /// the item unlocking the customisation, or 0 if it is always available
+    pub hint_item: u32,
+}
+
+impl MetadataExtractor for CharaMakeCustomize {
+    type Error = super::Error;
+
+    fn name() -> String {
+        "CharaMakeCustomize".into()
+    }
+
+    fn populate_row(row: ironworks::excel::Row) -> Result<Self, Self::Error> {
+        let item = crate::populate!(
+            row,
+            [feature_id, 0, into_u8],
+            [hint_item, 5, into_u32],
+            row_id: row.row_id(),
+        );
+
+        Ok(item)
+    }
+}
@@ -0,0 +1,29 @@
+use crate::schema::MetadataExtractor;
+
+#[derive(Debug)]
+pub struct CharaMakeType {
+    pub row_id: u32,
+    pub race: i32,
+    pub tribe: i32,
+    pub gender: i8,
+}
+
+impl MetadataExtractor for CharaMakeType {
+    type Error = super::Error;
+
+    fn name() -> String {
+        "CharaMakeType".into()
+    }
+
+    fn populate_row(row: ironworks::excel::Row) -> Result<Self, Self::Error> {
+        let item = crate::populate!(
+            row,
+            [race, 0, into_i32],
+            [tribe, 1, into_i32],
+            [gender, 2, into_i8],
+            row_id: row.row_id(),
+        );
+
+        Ok(item)
+    }
+}
@@ -4,6 +4,7 @@ use crate::schema::MetadataExtractor;
 
 #[derive(Debug)]
 pub struct Companion<'a> {
+    pub row_id: u32,
     pub singular: SeString<'a>,
     pub model: u16,
 }
@@ -16,7 +17,12 @@ impl MetadataExtractor for Companion<'_> {
     }
 
     fn populate_row(row: ironworks::excel::Row) -> Result<Self, Self::Error> {
-        let item = crate::populate!(row, [singular, 0, into_string], [model, 8, into_u16],);
+        let item = crate::populate!(
+            row,
+            [singular, 0, into_string],
+            [model, 8, into_u16],
+            row_id: row.row_id(),
+        );
 
         Ok(item)
     }
@@ -5,6 +5,7 @@ use crate::schema::MetadataExtractor;
 
 #[derive(Debug)]
 pub struct Emote<'a> {
+    pub row_id: u32,
     pub name: SeString<'a>,
     pub action_timelines: Vec<u16>,
     pub text_command: i32,
@@ -32,6 +33,7 @@ impl MetadataExtractor for Emote<'_> {
             [name, 0, into_string],
             [text_command, 19, into_i32],
             action_timelines: timelines,
+            row_id: row.row_id(),
         );
 
         Ok(item)
@@ -1,7 +1,7 @@
 use std::error::Error;
 
 pub trait MetadataExtractor {
-    type Error: Error;
+    type Error: Error + Send + Sync + 'static;
 
     fn name() -> String;
     fn populate_row(row: ironworks::excel::Row) -> Result<Self, Self::Error>
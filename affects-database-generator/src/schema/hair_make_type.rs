@@ -0,0 +1,36 @@
+use crate::schema::MetadataExtractor;
+
+/// the hairstyles and face paints of a race, tribe and gender. rows have the
+/// same ids as the CharaMakeType rows they are for
+#[derive(Debug)]
+pub struct HairMakeType {
+    pub row_id: u32,
+    /// the first of the CharaMakeCustomize rows for hairstyles
+    pub hair_start_index: u32,
+    /// the first of the CharaMakeCustomize rows for face paints
+    pub face_paint_start_index: u32,
+}
+
+impl HairMakeType {
+    pub const FACE_PAINTS: u32 = 50;
+    pub const HAIRSTYLES: u32 = 100;
+}
+
+impl MetadataExtractor for HairMakeType {
+    type Error = super::Error;
+
+    fn name() -> String {
+        "HairMakeType".into()
+    }
+
+    fn populate_row(row: ironworks::excel::Row) -> Result<Self, Self::Error> {
+        let item = crate::populate!(
+            row,
+            [hair_start_index, 0, into_u32],
+            [face_paint_start_index, 1, into_u32],
+            row_id: row.row_id(),
+        );
+
+        Ok(item)
+    }
+}
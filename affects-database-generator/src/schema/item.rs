@@ -8,6 +8,7 @@ pub struct Item<'a> {
     // pub singular: SeString<'a>,
     // pub plural: SeString<'a>,
     pub name: SeString<'a>,
+    pub icon: u16,
     pub item_ui_category: u8,
     pub equip_slot_category: u8,
     pub model_main: u64,
@@ -27,6 +28,7 @@ impl MetadataExtractor for Item<'_> {
             // [singular, 0, into_string],
             // [plural, 2, into_string],
             [name, 9, into_string],
+            [icon, 10, into_u16],
             [item_ui_category, 15, into_u8],
             [equip_slot_category, 17, into_u8],
             [model_main, 47, into_u64],
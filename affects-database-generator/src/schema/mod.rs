@@ -1,45 +1,61 @@
 mod action;
 mod action_cast_timeline;
+mod action_cast_vfx;
 mod action_timeline;
 mod bnpc_base;
 mod bnpc_name;
+mod chara_make_customize;
+mod chara_make_type;
 mod companion;
 mod emote;
 mod enpc_base;
 mod enpc_resident;
 mod equip_slot_category;
 mod extractor;
+mod hair_make_type;
 mod item;
 mod map;
 mod model_chara;
 mod mount;
 mod npc_equip;
+mod orchestrion;
+mod orchestrion_path;
 mod ornament;
 mod place_name;
 mod provider;
 mod text_command;
+mod tribe;
+mod vfx;
 
 pub use self::{
     action::Action,
     action_cast_timeline::ActionCastTimeline,
+    action_cast_vfx::ActionCastVfx,
     action_timeline::ActionTimeline,
     bnpc_base::BNpcBase,
     bnpc_name::BNpcName,
+    chara_make_customize::CharaMakeCustomize,
+    chara_make_type::CharaMakeType,
     companion::Companion,
     emote::Emote,
     enpc_base::ENpcBase,
     enpc_resident::ENpcResident,
     equip_slot_category::EquipSlotCategory,
     extractor::MetadataExtractor,
+    hair_make_type::HairMakeType,
     item::Item,
     map::Map,
     model_chara::{ModelChara, ModelCharaKind},
     mount::Mount,
     npc_equip::NpcEquip,
+    orchestrion::Orchestrion,
+    orchestrion_path::OrchestrionPath,
     ornament::Ornament,
     place_name::PlaceName,
     provider::MetadataProvider,
     text_command::TextCommand,
+    tribe::Tribe,
+    vfx::Vfx,
 };
 
 #[derive(Debug, thiserror::Error)]
@@ -47,12 +47,15 @@ impl MetadataExtractor for ModelChara {
 pub enum ModelCharaKind {
     Monster,
     Demihuman,
+    /// humans and weapons, which have no model paths of their own
     Other,
+    /// a type added to the game since this was written
+    Unknown,
 }
 
 impl ModelCharaKind {
     pub fn is_other(self) -> bool {
-        matches!(self, Self::Other)
+        matches!(self, Self::Other | Self::Unknown)
     }
 }
 
@@ -61,7 +64,8 @@ impl From<u8> for ModelCharaKind {
         match value {
             2 => Self::Demihuman,
             3 => Self::Monster,
-            _ => Self::Other,
+            0 | 1 | 4 => Self::Other,
+            _ => Self::Unknown,
         }
     }
 }
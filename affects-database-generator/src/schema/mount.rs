@@ -4,6 +4,7 @@ use crate::schema::MetadataExtractor;
 
 #[derive(Debug)]
 pub struct Mount<'a> {
+    pub row_id: u32,
     pub singular: SeString<'a>,
     pub model_chara: i32,
 }
@@ -16,7 +17,12 @@ impl MetadataExtractor for Mount<'_> {
     }
 
     fn populate_row(row: ironworks::excel::Row) -> Result<Self, Self::Error> {
-        let item = crate::populate!(row, [singular, 0, into_string], [model_chara, 8, into_i32],);
+        let item = crate::populate!(
+            row,
+            [singular, 0, into_string],
+            [model_chara, 8, into_i32],
+            row_id: row.row_id(),
+        );
 
         Ok(item)
     }
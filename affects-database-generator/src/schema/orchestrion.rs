@@ -0,0 +1,27 @@
+use ironworks::sestring::SeString;
+
+use crate::schema::MetadataExtractor;
+
+#[derive(Debug)]
+pub struct Orchestrion<'a> {
+    pub row_id: u32,
+    pub name: SeString<'a>,
+}
+
+impl MetadataExtractor for Orchestrion<'_> {
+    type Error = super::Error;
+
+    fn name() -> String {
+        "Orchestrion".into()
+    }
+
+    fn populate_row(row: ironworks::excel::Row) -> Result<Self, Self::Error> {
+        let item = crate::populate!(
+            row,
+            [name, 0, into_string],
+            row_id: row.row_id(),
+        );
+
+        Ok(item)
+    }
+}
@@ -0,0 +1,22 @@
+use ironworks::sestring::SeString;
+
+use crate::schema::MetadataExtractor;
+
+#[derive(Debug)]
+pub struct OrchestrionPath<'a> {
+    pub file: SeString<'a>,
+}
+
+impl MetadataExtractor for OrchestrionPath<'_> {
+    type Error = super::Error;
+
+    fn name() -> String {
+        "OrchestrionPath".into()
+    }
+
+    fn populate_row(row: ironworks::excel::Row) -> Result<Self, Self::Error> {
+        let item = crate::populate!(row, [file, 0, into_string],);
+
+        Ok(item)
+    }
+}
@@ -4,6 +4,7 @@ use crate::schema::MetadataExtractor;
 
 #[derive(Debug)]
 pub struct Ornament<'a> {
+    pub row_id: u32,
     pub model: u16,
     pub singular: SeString<'a>,
 }
@@ -16,7 +17,12 @@ impl MetadataExtractor for Ornament<'_> {
     }
 
     fn populate_row(row: ironworks::excel::Row) -> Result<Self, Self::Error> {
-        let item = crate::populate!(row, [model, 0, into_u16], [singular, 8, into_string],);
+        let item = crate::populate!(
+            row,
+            [model, 0, into_u16],
+            [singular, 8, into_string],
+            row_id: row.row_id(),
+        );
 
         Ok(item)
     }
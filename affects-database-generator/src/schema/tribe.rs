@@ -0,0 +1,29 @@
+use ironworks::sestring::SeString;
+
+use crate::schema::MetadataExtractor;
+
+#[derive(Debug)]
+pub struct Tribe<'a> {
+    pub row_id: u32,
+    pub masculine: SeString<'a>,
+    pub feminine: SeString<'a>,
+}
+
+impl MetadataExtractor for Tribe<'_> {
+    type Error = super::Error;
+
+    fn name() -> String {
+        "Tribe".into()
+    }
+
+    fn populate_row(row: ironworks::excel::Row) -> Result<Self, Self::Error> {
+        let item = crate::populate!(
+            row,
+            [masculine, 0, into_string],
+            [feminine, 1, into_string],
+            row_id: row.row_id(),
+        );
+
+        Ok(item)
+    }
+}
@@ -0,0 +1,22 @@
+use ironworks::sestring::SeString;
+
+use crate::schema::MetadataExtractor;
+
+#[derive(Debug)]
+pub struct Vfx<'a> {
+    pub location: SeString<'a>,
+}
+
+impl MetadataExtractor for Vfx<'_> {
+    type Error = super::Error;
+
+    fn name() -> String {
+        "VFX".into()
+    }
+
+    fn populate_row(row: ironworks::excel::Row) -> Result<Self, Self::Error> {
+        let item = crate::populate!(row, [location, 0, into_string],);
+
+        Ok(item)
+    }
+}
@@ -0,0 +1,593 @@
+//! in-memory game data for the analyser tests. sheets are written out as
+//! real excel files, so the analysers read them the same way they read an
+//! install
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    io::Cursor,
+    path::PathBuf,
+};
+
+use affects_common::{Affects, NameSet};
+use imc::{ImageChangeData, ImageChangeParts, ImcFile, PartMask};
+use ironworks::{ErrorValue, Ironworks, Resource};
+
+use crate::{
+    analysers::GeneratorContext,
+    cache::GeneratorCache,
+    containers::{BNpcContainer, BNpcMapEntry},
+    game::GameData,
+    report::Anomaly,
+};
+
+/// the columns each sheet the analysers read has. columns not listed here
+/// are written as u8s
+const SHEETS: &[(&str, &[(usize, Kind)])] = &[
+    (
+        "Action",
+        &[
+            (0, Kind::String),
+            (5, Kind::U8),
+            (6, Kind::U8),
+            (7, Kind::I16),
+            (8, Kind::U16),
+        ],
+    ),
+    ("ActionCastTimeline", &[(0, Kind::U16), (1, Kind::U16)]),
+    ("ActionCastVFX", &[(0, Kind::U16)]),
+    ("ActionTimeline", &[(6, Kind::String)]),
+    ("BNpcBase", &[(5, Kind::U16), (7, Kind::U16)]),
+    ("BNpcName", &[(0, Kind::String)]),
+    ("CharaMakeCustomize", &[(0, Kind::U8), (5, Kind::U32)]),
+    (
+        "CharaMakeType",
+        &[(0, Kind::I32), (1, Kind::I32), (2, Kind::I8)],
+    ),
+    ("Companion", &[(0, Kind::String), (8, Kind::U16)]),
+    (
+        "Emote",
+        &[
+            (0, Kind::String),
+            (1, Kind::U16),
+            (2, Kind::U16),
+            (3, Kind::U16),
+            (4, Kind::U16),
+            (5, Kind::U16),
+            (6, Kind::U16),
+            (7, Kind::U16),
+            (19, Kind::I32),
+        ],
+    ),
+    (
+        "ENpcBase",
+        &[
+            (35, Kind::U16),
+            (71, Kind::U32),
+            (76, Kind::U32),
+            (79, Kind::U32),
+            (82, Kind::U32),
+            (85, Kind::U32),
+            (88, Kind::U32),
+            (91, Kind::U32),
+            (94, Kind::U32),
+            (97, Kind::U32),
+            (100, Kind::U32),
+        ],
+    ),
+    ("ENpcResident", &[(0, Kind::String), (2, Kind::String)]),
+    (
+        "EquipSlotCategory",
+        &[
+            (0, Kind::I8),
+            (1, Kind::I8),
+            (2, Kind::I8),
+            (3, Kind::I8),
+            (4, Kind::I8),
+            (5, Kind::I8),
+            (6, Kind::I8),
+            (7, Kind::I8),
+            (8, Kind::I8),
+            (9, Kind::I8),
+            (10, Kind::I8),
+            (11, Kind::I8),
+            (12, Kind::I8),
+            (13, Kind::I8),
+        ],
+    ),
+    ("HairMakeType", &[(0, Kind::U32), (1, Kind::U32)]),
+    (
+        "Item",
+        &[
+            (9, Kind::String),
+            (10, Kind::U16),
+            (15, Kind::U8),
+            (17, Kind::U8),
+            (47, Kind::U64),
+            (48, Kind::U64),
+        ],
+    ),
+    (
+        "Map",
+        &[
+            (6, Kind::String),
+            (10, Kind::U16),
+            (11, Kind::U16),
+            (12, Kind::U16),
+        ],
+    ),
+    (
+        "ModelChara",
+        &[(0, Kind::U8), (1, Kind::U16), (2, Kind::U8), (3, Kind::U8)],
+    ),
+    ("Mount", &[(0, Kind::String), (8, Kind::I32)]),
+    (
+        "NpcEquip",
+        &[
+            (6, Kind::U32),
+            (11, Kind::U32),
+            (14, Kind::U32),
+            (17, Kind::U32),
+            (20, Kind::U32),
+            (23, Kind::U32),
+            (26, Kind::U32),
+            (29, Kind::U32),
+            (32, Kind::U32),
+            (35, Kind::U32),
+        ],
+    ),
+    ("Orchestrion", &[(0, Kind::String)]),
+    ("OrchestrionPath", &[(0, Kind::String)]),
+    ("Ornament", &[(0, Kind::U16), (8, Kind::String)]),
+    ("PlaceName", &[(0, Kind::String)]),
+    ("TextCommand", &[(5, Kind::String)]),
+    ("Tribe", &[(0, Kind::String), (1, Kind::String)]),
+    ("VFX", &[(0, Kind::String)]),
+];
+
+/// excel column types, by the id used for them in .exh files
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    String = 0x0,
+    I8 = 0x2,
+    U8 = 0x3,
+    I16 = 0x4,
+    U16 = 0x5,
+    I32 = 0x6,
+    U32 = 0x7,
+    U64 = 0xB,
+}
+
+impl Kind {
+    fn size(self) -> usize {
+        match self {
+            Self::I8 | Self::U8 => 1,
+            Self::I16 | Self::U16 => 2,
+            Self::String | Self::I32 | Self::U32 => 4,
+            Self::U64 => 8,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    String(String),
+    I8(i8),
+    U8(u8),
+    I16(i16),
+    U16(u16),
+    I32(i32),
+    U32(u32),
+    U64(u64),
+}
+
+impl Value {
+    fn kind(&self) -> Kind {
+        match self {
+            Self::String(_) => Kind::String,
+            Self::I8(_) => Kind::I8,
+            Self::U8(_) => Kind::U8,
+            Self::I16(_) => Kind::I16,
+            Self::U16(_) => Kind::U16,
+            Self::I32(_) => Kind::I32,
+            Self::U32(_) => Kind::U32,
+            Self::U64(_) => Kind::U64,
+        }
+    }
+
+    /// the big endian bytes stored in the fixed part of a row. strings are
+    /// stored separately
+    fn bytes(&self) -> Vec<u8> {
+        match self {
+            Self::String(_) => Vec::new(),
+            Self::I8(v) => v.to_be_bytes().to_vec(),
+            Self::U8(v) => v.to_be_bytes().to_vec(),
+            Self::I16(v) => v.to_be_bytes().to_vec(),
+            Self::U16(v) => v.to_be_bytes().to_vec(),
+            Self::I32(v) => v.to_be_bytes().to_vec(),
+            Self::U32(v) => v.to_be_bytes().to_vec(),
+            Self::U64(v) => v.to_be_bytes().to_vec(),
+        }
+    }
+}
+
+macro_rules! value_from {
+    ($($ty: ty => $variant: ident),* $(,)?) => {
+        $(
+            impl From<$ty> for Value {
+                fn from(value: $ty) -> Self {
+                    Self::$variant(value.into())
+                }
+            }
+        )*
+    };
+}
+
+value_from!(
+    &str => String,
+    i8 => I8,
+    u8 => U8,
+    i16 => I16,
+    u16 => U16,
+    i32 => I32,
+    u32 => U32,
+    u64 => U64,
+);
+
+/// a resource serving files from memory. paths are case insensitive, like
+/// they are in sqpacks
+#[derive(Debug, Default)]
+struct MemoryResource {
+    files: HashMap<String, Vec<u8>>,
+}
+
+impl Resource for MemoryResource {
+    type File = Cursor<Vec<u8>>;
+
+    fn version(&self, _path: &str) -> Result<String, ironworks::Error> {
+        Ok("fixture".into())
+    }
+
+    fn file(&self, path: &str) -> Result<Self::File, ironworks::Error> {
+        self.files
+            .get(&path.to_lowercase())
+            .cloned()
+            .map(Cursor::new)
+            .ok_or_else(|| ironworks::Error::NotFound(ErrorValue::Path(path.into())))
+    }
+}
+
+/// a set of sheet rows and files to run analysers against. every sheet in
+/// [`SHEETS`] exists, and is empty unless rows are added to it
+#[derive(Debug, Default)]
+pub struct Fixture {
+    rows: BTreeMap<&'static str, BTreeMap<u32, Vec<(usize, Value)>>>,
+    files: HashMap<String, Vec<u8>>,
+    bnpcs: Vec<(u32, u32)>,
+}
+
+impl Fixture {
+    /// adds a row to `sheet`. columns that are not given are zero or empty
+    pub fn row(
+        &mut self,
+        sheet: &'static str,
+        row_id: u32,
+        fields: &[(usize, Value)],
+    ) -> &mut Self {
+        self.rows
+            .entry(sheet)
+            .or_default()
+            .insert(row_id, fields.to_vec());
+        self
+    }
+
+    pub fn file(&mut self, path: &str, data: impl Into<Vec<u8>>) -> &mut Self {
+        self.files.insert(path.to_lowercase(), data.into());
+        self
+    }
+
+    /// adds an imc file with a part for each of `parts`, each giving the
+    /// (material, vfx) of the default variant and then every other variant
+    pub fn imc(&mut self, path: &str, parts: &[&[(u8, u8)]]) -> &mut Self {
+        let imc = ImcFile {
+            part_mask: PartMask::first(parts.len()),
+            parts: parts
+                .iter()
+                .map(|variants| ImageChangeParts {
+                    default_variant: entry(variants[0]),
+                    variants: variants[1..].iter().copied().map(entry).collect(),
+                })
+                .collect(),
+        };
+
+        self.imc_file(path, &imc)
+    }
+
+    /// adds an imc file for entries that need more than a material and vfx
+    pub fn imc_file(&mut self, path: &str, imc: &ImcFile) -> &mut Self {
+        let data = imc.to_bytes().expect("could not write imc");
+        self.file(path, data)
+    }
+
+    /// adds a timeline file playing the given effects and sounds
+    pub fn tmb(&mut self, key: &str, paths: &[&str]) -> &mut Self {
+        let mut data = b"TMLB".to_vec();
+        for path in paths {
+            data.push(0);
+            data.extend_from_slice(path.as_bytes());
+        }
+
+        data.push(0);
+        self.file(&format!("chara/action/{key}.tmb"), data)
+    }
+
+    /// adds an effect file drawing the given textures
+    pub fn avfx(&mut self, path: &str, textures: &[&str]) -> &mut Self {
+        let mut data = b"XFVA".to_vec();
+        data.extend_from_slice(&0_u32.to_le_bytes());
+        for texture in textures {
+            data.extend_from_slice(b"htaP");
+            data.extend_from_slice(&(texture.len() as u32 + 1).to_le_bytes());
+            data.extend_from_slice(texture.as_bytes());
+            data.push(0);
+        }
+
+        self.file(path, data)
+    }
+
+    /// links a battle npc base to a name, like the bnpc mapping does
+    pub fn bnpc(&mut self, bnpc_base: u32, bnpc_name: u32) -> &mut Self {
+        self.bnpcs.push((bnpc_base, bnpc_name));
+        self
+    }
+
+    pub fn game_data(&self) -> GameData {
+        let mut resource = MemoryResource {
+            files: self.files.clone(),
+        };
+
+        let mut list = String::from("EXLT,2\r\n");
+        for (id, (sheet, columns)) in SHEETS.iter().enumerate() {
+            list.push_str(&format!("{sheet},{id}\r\n"));
+
+            let rows = self.rows.get(sheet).cloned().unwrap_or_default();
+            let (header, pages) = write_sheet(sheet, columns, &rows);
+            resource
+                .files
+                .insert(format!("exd/{sheet}.exh").to_lowercase(), header);
+            for (start, data) in pages {
+                resource
+                    .files
+                    .insert(format!("exd/{sheet}_{start}.exd").to_lowercase(), data);
+            }
+        }
+
+        for sheet in self.rows.keys() {
+            assert!(
+                SHEETS.iter().any(|(name, _)| name == sheet),
+                "{sheet} is not a fixture sheet",
+            );
+        }
+
+        resource
+            .files
+            .insert("exd/root.exl".into(), list.into_bytes());
+
+        GameData::new(Ironworks::new().with_resource(resource))
+    }
+
+    pub fn run(&self, analyse: fn(&mut GeneratorContext) -> anyhow::Result<()>) -> Output {
+        self.run_on(Affects::default(), analyse)
+    }
+
+    /// runs `analyse` on an existing database, for the analysers that look
+    /// at what the others found
+    pub fn run_on(
+        &self,
+        mut affects: Affects,
+        analyse: fn(&mut GeneratorContext) -> anyhow::Result<()>,
+    ) -> Output {
+        let (result, anomalies) = self.with_context(&mut affects, analyse);
+        result.expect("analyser failed");
+
+        Output { affects, anomalies }
+    }
+
+    /// calls `f` with a context reading the fixture into `affects`, giving
+    /// back what it returned and the anomalies it recorded
+    pub fn with_context<T>(
+        &self,
+        affects: &mut Affects,
+        f: impl FnOnce(&mut GeneratorContext) -> T,
+    ) -> (T, Vec<Anomaly>) {
+        let game = self.game_data();
+        let cache = GeneratorCache::default();
+        let bnpcs = BNpcContainer {
+            bnpc: self
+                .bnpcs
+                .iter()
+                .map(|&(bnpc_base, bnpc_name)| BNpcMapEntry {
+                    bnpc_base,
+                    bnpc_name,
+                })
+                .collect(),
+        };
+        let mut name_map = affects
+            .names
+            .iter()
+            .enumerate()
+            .map(|(idx, name)| (name.clone(), idx as u32))
+            .collect();
+        let mut anomalies = Vec::new();
+
+        let result = f(&mut GeneratorContext {
+            affects,
+            ironworks: &game.ironworks,
+            cache: &cache,
+            excel: &game.excel,
+            localised_excels: &game.localised_excels,
+            name_map: &mut name_map,
+            bnpcs: &bnpcs,
+            anomalies: &mut anomalies,
+        });
+
+        (result, anomalies)
+    }
+}
+
+/// what an analyser produced
+#[derive(Debug)]
+pub struct Output {
+    pub affects: Affects,
+    pub anomalies: Vec<Anomaly>,
+}
+
+impl Output {
+    /// the english names in `names`, in index order
+    pub fn names(&self, names: &NameSet) -> Vec<&str> {
+        names
+            .iter()
+            .map(|(_, idx)| self.affects.names[*idx as usize].as_str())
+            .collect()
+    }
+}
+
+/// a path in the temp directory for a test to write `name` to. the process
+/// id keeps runs from tripping over each other, and tests over each other
+/// as long as their names differ
+pub fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("{}-{name}", std::process::id()))
+}
+
+/// the base and name ids of each entry of a battle npc mapping
+pub fn bnpc_pairs(bnpcs: &BNpcContainer) -> Vec<(u32, u32)> {
+    bnpcs
+        .bnpc
+        .iter()
+        .map(|entry| (entry.bnpc_base, entry.bnpc_name))
+        .collect()
+}
+
+/// an imc entry with only a material and vfx
+fn entry((material_id, vfx_id): (u8, u8)) -> ImageChangeData {
+    ImageChangeData {
+        material_id,
+        vfx_id,
+        ..Default::default()
+    }
+}
+
+/// writes the .exh file for a sheet, and an .exd file for each of its rows.
+/// giving every row a page of its own means row ids do not need to be
+/// contiguous
+fn write_sheet(
+    sheet: &str,
+    columns: &[(usize, Kind)],
+    rows: &BTreeMap<u32, Vec<(usize, Value)>>,
+) -> (Vec<u8>, Vec<(u32, Vec<u8>)>) {
+    let column_count = columns.iter().map(|&(idx, _)| idx + 1).max().unwrap_or(0);
+    let mut kinds = vec![Kind::U8; column_count];
+    for &(idx, kind) in columns {
+        kinds[idx] = kind;
+    }
+
+    let mut offsets = Vec::with_capacity(kinds.len());
+    let mut row_size = 0;
+    for kind in &kinds {
+        offsets.push(row_size);
+        row_size += kind.size();
+    }
+
+    // header
+    let mut header = b"EXHF".to_vec();
+    header.extend_from_slice(&3u16.to_be_bytes());
+    header.extend_from_slice(&(row_size as u16).to_be_bytes());
+    header.extend_from_slice(&(kinds.len() as u16).to_be_bytes());
+    header.extend_from_slice(&(rows.len() as u16).to_be_bytes());
+    // one language, none
+    header.extend_from_slice(&1u16.to_be_bytes());
+    header.extend_from_slice(&[0, 0, 0]);
+    // default sheet kind, without subrows
+    header.push(1);
+    header.extend_from_slice(&[0, 0]);
+    header.extend_from_slice(&(rows.len() as u32).to_be_bytes());
+    header.extend_from_slice(&[0; 8]);
+    for (kind, offset) in kinds.iter().zip(&offsets) {
+        header.extend_from_slice(&(*kind as u16).to_be_bytes());
+        header.extend_from_slice(&(*offset as u16).to_be_bytes());
+    }
+
+    for &row_id in rows.keys() {
+        header.extend_from_slice(&row_id.to_be_bytes());
+        header.extend_from_slice(&1u32.to_be_bytes());
+    }
+
+    header.extend_from_slice(&[0, 0]);
+
+    // pages
+    let mut pages = Vec::with_capacity(rows.len());
+    for (&row_id, fields) in rows {
+        for (idx, _) in fields {
+            assert!(*idx < kinds.len(), "{sheet} has no column {idx}");
+        }
+
+        let mut fixed = vec![0; row_size];
+        let mut strings = Vec::new();
+        for (idx, kind) in kinds.iter().enumerate() {
+            let value = fields
+                .iter()
+                .rev()
+                .find(|(field, _)| *field == idx)
+                .map(|(_, value)| value);
+            if let Some(value) = value {
+                assert_eq!(
+                    value.kind(),
+                    *kind,
+                    "wrong type for column {idx} of {sheet}",
+                );
+            }
+
+            let bytes = match (kind, value) {
+                (Kind::String, value) => {
+                    let string_offset = strings.len() as u32;
+                    if let Some(Value::String(string)) = value {
+                        strings.extend_from_slice(string.as_bytes());
+                    }
+                    strings.push(0);
+
+                    string_offset.to_be_bytes().to_vec()
+                }
+                (_, Some(value)) => value.bytes(),
+                (_, None) => continue,
+            };
+
+            let offset = offsets[idx];
+            fixed[offset..offset + bytes.len()].copy_from_slice(&bytes);
+        }
+
+        // rows are padded to four bytes
+        while (fixed.len() + strings.len()) % 4 != 0 {
+            strings.push(0);
+        }
+
+        let data_size = (fixed.len() + strings.len()) as u32;
+
+        let mut page = b"EXDF".to_vec();
+        page.extend_from_slice(&2u16.to_be_bytes());
+        page.extend_from_slice(&[0, 0]);
+        // index size
+        page.extend_from_slice(&8u32.to_be_bytes());
+        page.extend_from_slice(&(data_size + 6).to_be_bytes());
+        page.extend_from_slice(&[0; 16]);
+        // the row starts straight after the header and index
+        page.extend_from_slice(&row_id.to_be_bytes());
+        page.extend_from_slice(&40u32.to_be_bytes());
+        page.extend_from_slice(&data_size.to_be_bytes());
+        page.extend_from_slice(&1u16.to_be_bytes());
+        page.extend_from_slice(&fixed);
+        page.extend_from_slice(&strings);
+
+        pages.push((row_id, page));
+    }
+
+    (header, pages)
+}
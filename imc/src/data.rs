@@ -0,0 +1,145 @@
+use binrw::binrw;
+
+use crate::Error;
+
+/// the entry for one variant of one part of a model
+#[binrw]
+#[brw(little)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImageChangeData {
+    pub material_id: u8,
+    pub decal_id: u8,
+    /// the attribute mask in the low 10 bits and the sound id in the high 6
+    pub attribute_and_sound: u16,
+    pub vfx_id: u8,
+    /// the material animation id in the low 4 bits. the high 4 bits are
+    /// unknown, and are kept as they are
+    pub material_animation_id_mask: u8,
+}
+
+impl ImageChangeData {
+    pub const MAX_ATTRIBUTE_MASK: u16 = 0x3FF;
+    pub const MAX_MATERIAL_ANIMATION_ID: u8 = 0xF;
+    pub const MAX_SOUND_ID: u8 = 0x3F;
+
+    /// which of the model's attributes (atr_a to atr_j) are shown, one bit
+    /// each
+    pub fn attribute_mask(&self) -> u16 {
+        self.attribute_and_sound & Self::MAX_ATTRIBUTE_MASK
+    }
+
+    /// whether attribute `attribute` is shown, where 0 is atr_a
+    pub fn has_attribute(&self, attribute: u8) -> bool {
+        attribute < 10 && self.attribute_mask() & (1 << attribute) != 0
+    }
+
+    pub fn material_animation_id(&self) -> u8 {
+        self.material_animation_id_mask & Self::MAX_MATERIAL_ANIMATION_ID
+    }
+
+    pub fn set_attribute_mask(&mut self, mask: u16) -> Result<(), Error> {
+        if mask > Self::MAX_ATTRIBUTE_MASK {
+            return Err(Error::OutOfRange {
+                field: "attribute mask",
+                value: mask,
+                max: Self::MAX_ATTRIBUTE_MASK,
+            });
+        }
+
+        self.attribute_and_sound = (self.attribute_and_sound & !Self::MAX_ATTRIBUTE_MASK) | mask;
+        Ok(())
+    }
+
+    pub fn set_material_animation_id(&mut self, id: u8) -> Result<(), Error> {
+        if id > Self::MAX_MATERIAL_ANIMATION_ID {
+            return Err(Error::OutOfRange {
+                field: "material animation id",
+                value: id.into(),
+                max: Self::MAX_MATERIAL_ANIMATION_ID.into(),
+            });
+        }
+
+        self.material_animation_id_mask =
+            (self.material_animation_id_mask & !Self::MAX_MATERIAL_ANIMATION_ID) | id;
+        Ok(())
+    }
+
+    pub fn set_sound_id(&mut self, id: u8) -> Result<(), Error> {
+        if id > Self::MAX_SOUND_ID {
+            return Err(Error::OutOfRange {
+                field: "sound id",
+                value: id.into(),
+                max: Self::MAX_SOUND_ID.into(),
+            });
+        }
+
+        self.attribute_and_sound = self.attribute_mask() | (u16::from(id) << 10);
+        Ok(())
+    }
+
+    pub fn sound_id(&self) -> u8 {
+        (self.attribute_and_sound >> 10) as u8
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ImageChangeData;
+    use crate::Error;
+
+    #[test]
+    fn accessors() {
+        let data = ImageChangeData {
+            attribute_and_sound: (3 << 10) | 0b101,
+            material_animation_id_mask: 0x52,
+            ..Default::default()
+        };
+
+        assert_eq!(0b101, data.attribute_mask());
+        assert!(data.has_attribute(0));
+        assert!(!data.has_attribute(1));
+        assert!(data.has_attribute(2));
+        assert!(!data.has_attribute(10));
+        assert_eq!(3, data.sound_id());
+        assert_eq!(2, data.material_animation_id());
+    }
+
+    #[test]
+    fn setters_keep_other_fields() {
+        let mut data = ImageChangeData {
+            attribute_and_sound: (3 << 10) | 0b101,
+            material_animation_id_mask: 0x52,
+            ..Default::default()
+        };
+
+        data.set_attribute_mask(0b11_0000_0000).unwrap();
+        assert_eq!(0b11_0000_0000, data.attribute_mask());
+        assert_eq!(3, data.sound_id());
+
+        data.set_sound_id(63).unwrap();
+        assert_eq!(0b11_0000_0000, data.attribute_mask());
+        assert_eq!(63, data.sound_id());
+
+        data.set_material_animation_id(7).unwrap();
+        assert_eq!(0x57, data.material_animation_id_mask);
+    }
+
+    #[test]
+    fn setters_reject_out_of_range() {
+        let mut data = ImageChangeData::default();
+        assert!(matches!(
+            data.set_attribute_mask(0x400),
+            Err(Error::OutOfRange { value: 0x400, .. })
+        ));
+        assert!(matches!(
+            data.set_sound_id(64),
+            Err(Error::OutOfRange { value: 64, .. })
+        ));
+        assert!(matches!(
+            data.set_material_animation_id(16),
+            Err(Error::OutOfRange { value: 16, .. })
+        ));
+        assert_eq!(ImageChangeData::default(), data);
+    }
+}
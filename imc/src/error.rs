@@ -0,0 +1,21 @@
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("could not read or write imc file: {0}")]
+    Binrw(#[from] binrw::Error),
+    #[error("part mask has {expected} parts but the file has {actual}")]
+    PartCount { expected: usize, actual: usize },
+    #[error("part {part} has {actual} variants but the first part has {expected}")]
+    VariantCount {
+        part: usize,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("{0} variants do not fit in an imc file")]
+    TooManyVariants(usize),
+    #[error("{field} of {value} is out of range (the most it can be is {max})")]
+    OutOfRange {
+        field: &'static str,
+        value: u16,
+        max: u16,
+    },
+}
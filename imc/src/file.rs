@@ -0,0 +1,326 @@
+use std::io::{Cursor, Read, Seek, Write};
+
+use binrw::{BinRead, BinWrite, binrw};
+
+use crate::{Error, ImageChangeData, PartMask};
+
+/// an imc file as it is stored
+#[binrw]
+#[brw(little)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawImcFile {
+    /// the number of variants, not counting the defaults
+    pub count: u16,
+    pub part_mask: PartMask,
+    #[br(count = part_mask.count())]
+    pub default_variants: Vec<ImageChangeData>,
+    /// every part of the first variant, then every part of the second, and
+    /// so on
+    #[br(count = part_mask.count() * count as usize)]
+    pub variants: Vec<ImageChangeData>,
+}
+
+/// an imc file, with its entries grouped by part
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImcFile {
+    pub part_mask: PartMask,
+    /// a part for each bit set in `part_mask`, lowest first
+    pub parts: Vec<ImageChangeParts>,
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImageChangeParts {
+    pub default_variant: ImageChangeData,
+    /// variants 1 and up
+    pub variants: Vec<ImageChangeData>,
+}
+
+impl ImcFile {
+    /// a file with only a default variant for each part in `part_mask`
+    pub fn new(part_mask: PartMask) -> Self {
+        Self {
+            part_mask,
+            parts: vec![ImageChangeParts::default(); part_mask.count()],
+        }
+    }
+
+    pub fn read(reader: &mut (impl Read + Seek)) -> Result<Self, Error> {
+        RawImcFile::read(reader)?.try_into()
+    }
+
+    pub fn from_bytes(data: &[u8]) -> Result<Self, Error> {
+        Self::read(&mut Cursor::new(data))
+    }
+
+    pub fn write(&self, writer: &mut (impl Write + Seek)) -> Result<(), Error> {
+        RawImcFile::try_from(self)?.write(writer)?;
+        Ok(())
+    }
+
+    pub fn to_bytes(&self) -> Result<Vec<u8>, Error> {
+        let mut data = Cursor::new(Vec::new());
+        self.write(&mut data)?;
+        Ok(data.into_inner())
+    }
+
+    /// the number of variants each part has, not counting the default
+    pub fn count(&self) -> usize {
+        self.parts.first().map_or(0, |part| part.variants.len())
+    }
+
+    /// the entry for `variant` of the part at `part`. variant 0 is the part's
+    /// default
+    pub fn variant(&self, part: usize, variant: u16) -> Option<&ImageChangeData> {
+        let part = self.parts.get(part)?;
+        match variant {
+            0 => Some(&part.default_variant),
+            variant => part.variants.get(variant as usize - 1),
+        }
+    }
+
+    pub fn variant_mut(&mut self, part: usize, variant: u16) -> Option<&mut ImageChangeData> {
+        let part = self.parts.get_mut(part)?;
+        match variant {
+            0 => Some(&mut part.default_variant),
+            variant => part.variants.get_mut(variant as usize - 1),
+        }
+    }
+
+    /// adds a variant to every part, copied from the part's default, and
+    /// returns its id
+    pub fn add_variant(&mut self) -> Result<u16, Error> {
+        let count = self.count() + 1;
+        let id = u16::try_from(count).map_err(|_| Error::TooManyVariants(count))?;
+        for part in &mut self.parts {
+            part.variants.push(part.default_variant);
+        }
+
+        Ok(id)
+    }
+
+    /// checks that the file has a part for every bit of its part mask, and
+    /// that every part has the same number of variants
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.parts.len() != self.part_mask.count() {
+            return Err(Error::PartCount {
+                expected: self.part_mask.count(),
+                actual: self.parts.len(),
+            });
+        }
+
+        let count = self.count();
+        if count > u16::MAX as usize {
+            return Err(Error::TooManyVariants(count));
+        }
+
+        for (idx, part) in self.parts.iter().enumerate() {
+            if part.variants.len() != count {
+                return Err(Error::VariantCount {
+                    part: idx,
+                    expected: count,
+                    actual: part.variants.len(),
+                });
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl TryFrom<RawImcFile> for ImcFile {
+    type Error = Error;
+
+    fn try_from(value: RawImcFile) -> Result<Self, Self::Error> {
+        let part_count = value.part_mask.count();
+        if value.default_variants.len() != part_count {
+            return Err(Error::PartCount {
+                expected: part_count,
+                actual: value.default_variants.len(),
+            });
+        }
+
+        let count = value.count as usize;
+        if value.variants.len() != part_count * count {
+            return Err(Error::VariantCount {
+                part: 0,
+                expected: count,
+                actual: value.variants.len() / part_count.max(1),
+            });
+        }
+
+        let mut parts = value
+            .default_variants
+            .into_iter()
+            .map(|default_variant| ImageChangeParts {
+                default_variant,
+                variants: Vec::with_capacity(count),
+            })
+            .collect::<Vec<_>>();
+
+        for variant in value.variants.chunks(part_count.max(1)) {
+            for (part, data) in parts.iter_mut().zip(variant) {
+                part.variants.push(*data);
+            }
+        }
+
+        Ok(Self {
+            part_mask: value.part_mask,
+            parts,
+        })
+    }
+}
+
+impl TryFrom<&ImcFile> for RawImcFile {
+    type Error = Error;
+
+    fn try_from(value: &ImcFile) -> Result<Self, Self::Error> {
+        value.validate()?;
+
+        let count = value.count();
+        let mut variants = Vec::with_capacity(count * value.parts.len());
+        for variant in 0..count {
+            variants.extend(value.parts.iter().map(|part| part.variants[variant]));
+        }
+
+        Ok(Self {
+            count: count as u16,
+            part_mask: value.part_mask,
+            default_variants: value
+                .parts
+                .iter()
+                .map(|part| part.default_variant)
+                .collect(),
+            variants,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{ImageChangeParts, ImcFile, RawImcFile};
+    use crate::{Error, ImageChangeData, PartMask};
+
+    fn data(material_id: u8, vfx_id: u8) -> ImageChangeData {
+        ImageChangeData {
+            material_id,
+            vfx_id,
+            ..Default::default()
+        }
+    }
+
+    /// two parts with two variants each, as stored
+    const BYTES: &[u8] = &[
+        // count and part mask
+        2, 0, 0b11, 0, //
+        // defaults
+        1, 0, 0, 0, 0, 0, //
+        1, 0, 0, 0, 0, 0, //
+        // variant 1
+        2, 0, 0x05, 0x0C, 3, 0x21, //
+        4, 0, 0, 0, 0, 0, //
+        // variant 2
+        5, 1, 0, 0, 0, 0, //
+        6, 0, 0, 0, 7, 0, //
+    ];
+
+    #[test]
+    fn read() {
+        let imc = ImcFile::from_bytes(BYTES).unwrap();
+        assert_eq!(PartMask(0b11), imc.part_mask);
+        assert_eq!(2, imc.count());
+        assert_eq!(2, imc.parts.len());
+
+        assert_eq!(Some(&data(1, 0)), imc.variant(0, 0));
+        assert_eq!(Some(&data(4, 0)), imc.variant(1, 1));
+        assert_eq!(Some(&data(6, 7)), imc.variant(1, 2));
+        assert_eq!(None, imc.variant(1, 3));
+        assert_eq!(None, imc.variant(2, 0));
+
+        let first = imc.variant(0, 1).unwrap();
+        assert_eq!(2, first.material_id);
+        assert_eq!(3, first.vfx_id);
+        assert_eq!(0b101, first.attribute_mask());
+        assert_eq!(3, first.sound_id());
+        assert_eq!(1, first.material_animation_id());
+        assert_eq!(1, imc.variant(0, 2).unwrap().decal_id);
+    }
+
+    #[test]
+    fn roundtrip() {
+        let imc = ImcFile::from_bytes(BYTES).unwrap();
+        assert_eq!(BYTES, imc.to_bytes().unwrap());
+    }
+
+    #[test]
+    fn edit() {
+        let mut imc = ImcFile::new(PartMask::EQUIPMENT);
+        assert_eq!(5, imc.parts.len());
+        imc.variant_mut(1, 0).unwrap().material_id = 1;
+
+        assert_eq!(1, imc.add_variant().unwrap());
+        assert_eq!(2, imc.add_variant().unwrap());
+        let variant = imc.variant_mut(1, 2).unwrap();
+        assert_eq!(1, variant.material_id);
+        variant.vfx_id = 4;
+        variant.set_attribute_mask(0b11).unwrap();
+
+        let read = ImcFile::from_bytes(&imc.to_bytes().unwrap()).unwrap();
+        assert_eq!(imc, read);
+        assert_eq!(4, read.variant(1, 2).unwrap().vfx_id);
+        assert_eq!(0b11, read.variant(1, 2).unwrap().attribute_mask());
+    }
+
+    #[test]
+    fn validate() {
+        let mut imc = ImcFile::new(PartMask(0b11));
+        imc.add_variant().unwrap();
+        assert!(imc.validate().is_ok());
+
+        imc.parts[1].variants.clear();
+        assert!(matches!(
+            imc.to_bytes(),
+            Err(Error::VariantCount {
+                part: 1,
+                expected: 1,
+                actual: 0,
+            })
+        ));
+
+        imc.parts.push(ImageChangeParts::default());
+        assert!(matches!(
+            imc.validate(),
+            Err(Error::PartCount {
+                expected: 2,
+                actual: 3,
+            })
+        ));
+    }
+
+    #[test]
+    fn rejects_short_files() {
+        assert!(matches!(
+            ImcFile::from_bytes(&BYTES[..BYTES.len() - 1]),
+            Err(Error::Binrw(_))
+        ));
+    }
+
+    #[test]
+    fn raw_parts_must_match_mask() {
+        let raw = RawImcFile {
+            count: 0,
+            part_mask: PartMask(0b111),
+            default_variants: vec![data(1, 0)],
+            variants: Vec::new(),
+        };
+
+        assert!(matches!(
+            ImcFile::try_from(raw),
+            Err(Error::PartCount {
+                expected: 3,
+                actual: 1,
+            })
+        ));
+    }
+}
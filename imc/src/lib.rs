@@ -0,0 +1,15 @@
+//! reading and writing of imc files, which list the material, decal,
+//! attributes, sound, vfx and material animation each variant of a model
+//! uses
+
+mod data;
+mod error;
+mod file;
+mod part_mask;
+
+pub use self::{
+    data::ImageChangeData,
+    error::Error,
+    file::{ImageChangeParts, ImcFile, RawImcFile},
+    part_mask::PartMask,
+};
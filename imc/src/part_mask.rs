@@ -0,0 +1,77 @@
+use binrw::binrw;
+
+/// which parts of a model an imc file has entries for. equipment has a part
+/// for each of its five slots, while most other models only have one
+#[binrw]
+#[brw(little)]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct PartMask(pub u16);
+
+impl PartMask {
+    /// the mask used by equipment and accessory imc files
+    pub const EQUIPMENT: Self = Self(0b1_1111);
+    /// the mask used by imc files for models with a single part
+    pub const SINGLE: Self = Self(0b1);
+
+    /// a mask with the first `count` parts set
+    pub fn first(count: usize) -> Self {
+        match count {
+            16.. => Self(u16::MAX),
+            count => Self((1 << count) - 1),
+        }
+    }
+
+    pub fn count(self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    pub fn contains(self, part: u8) -> bool {
+        part < 16 && self.0 & (1 << part) != 0
+    }
+
+    /// the bits set in the mask, lowest first. the parts of an imc file are
+    /// stored in this order
+    pub fn parts(self) -> impl Iterator<Item = u8> {
+        (0..16).filter(move |&part| self.contains(part))
+    }
+
+    /// where the entries for `part` are among the parts of an imc file with
+    /// this mask
+    pub fn index_of(self, part: u8) -> Option<usize> {
+        self.parts().position(|other| other == part)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::PartMask;
+
+    #[test]
+    fn parts() {
+        assert_eq!(
+            vec![0, 1, 2, 3, 4],
+            PartMask::EQUIPMENT.parts().collect::<Vec<_>>()
+        );
+        assert_eq!(vec![1, 3], PartMask(0b1010).parts().collect::<Vec<_>>());
+        assert_eq!(2, PartMask(0b1010).count());
+    }
+
+    #[test]
+    fn index_of() {
+        let mask = PartMask(0b1010);
+        assert_eq!(Some(0), mask.index_of(1));
+        assert_eq!(Some(1), mask.index_of(3));
+        assert_eq!(None, mask.index_of(2));
+        assert_eq!(None, mask.index_of(16));
+    }
+
+    #[test]
+    fn first() {
+        assert_eq!(PartMask(0), PartMask::first(0));
+        assert_eq!(PartMask::SINGLE, PartMask::first(1));
+        assert_eq!(PartMask::EQUIPMENT, PartMask::first(5));
+        assert_eq!(PartMask(u16::MAX), PartMask::first(20));
+    }
+}
@@ -0,0 +1,439 @@
+use affects_common::EquipSlot;
+
+use crate::{
+    GamePath, GamePathBuf,
+    path::chara::{
+        AccessoryPath, BodyType, BodyTypeSlot, CharacterPath, DemihumanPath, EquipmentPath,
+        MonsterPath, WeaponPath,
+    },
+    types::ModelInfo,
+};
+
+// chara/equipment and chara/accessory
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct EquipmentModel {
+    pub slot: EquipSlot,
+    pub primary_id: u16,
+}
+
+impl EquipmentModel {
+    pub fn new(slot: EquipSlot, primary_id: u16) -> Self {
+        Self { slot, primary_id }
+    }
+
+    pub fn imc(&self) -> GamePathBuf {
+        if self.slot.is_accessory() {
+            GamePath::Accessory(AccessoryPath::Imc(self.primary_id))
+        } else {
+            GamePath::Equipment(EquipmentPath::Imc(self.primary_id))
+        }
+    }
+
+    pub fn mdl(&self, model_info: ModelInfo) -> GamePathBuf {
+        if self.slot.is_accessory() {
+            GamePath::Accessory(AccessoryPath::Mdl {
+                primary_id: self.primary_id,
+                info: model_info,
+                slot: self.slot,
+            })
+        } else {
+            GamePath::Equipment(EquipmentPath::Mdl {
+                id: self.primary_id,
+                info: model_info,
+                slot: self.slot,
+            })
+        }
+    }
+
+    pub fn mtrl(&self, model_info: ModelInfo, variant_id: u16) -> GamePathBuf {
+        if self.slot.is_accessory() {
+            GamePath::Accessory(AccessoryPath::Mtrl {
+                primary_id: self.primary_id,
+                variant_id,
+                model_info,
+                slot: self.slot,
+            })
+        } else {
+            GamePath::Equipment(EquipmentPath::Mtrl {
+                primary_id: self.primary_id,
+                variant_id,
+                model_info,
+                slot: self.slot,
+            })
+        }
+    }
+
+    pub fn tex(&self, model_info: ModelInfo, variant_id: u8) -> GamePathBuf {
+        if self.slot.is_accessory() {
+            GamePath::Accessory(AccessoryPath::Tex {
+                primary_id: self.primary_id,
+                variant_id,
+                model_info,
+                slot: self.slot,
+            })
+        } else {
+            GamePath::Equipment(EquipmentPath::Tex {
+                primary_id: self.primary_id,
+                variant_id,
+                model_info,
+                slot: self.slot,
+            })
+        }
+    }
+
+    /// accessories do not have vfx, so this is `None` for them
+    pub fn avfx(&self, effect_id: u16) -> Option<GamePathBuf> {
+        if self.slot.is_accessory() {
+            return None;
+        }
+
+        Some(GamePath::Equipment(EquipmentPath::Avfx {
+            primary_id: self.primary_id,
+            effect_id,
+        }))
+    }
+}
+
+// chara/weapon
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct WeaponModel {
+    pub primary_id: u16,
+    pub secondary_id: u16,
+}
+
+impl WeaponModel {
+    pub fn new(primary_id: u16, secondary_id: u16) -> Self {
+        Self {
+            primary_id,
+            secondary_id,
+        }
+    }
+
+    pub fn imc(&self) -> GamePathBuf {
+        GamePath::Weapon(WeaponPath::Imc {
+            primary_id: self.primary_id,
+            secondary_id: self.secondary_id,
+        })
+    }
+
+    pub fn mdl(&self) -> GamePathBuf {
+        GamePath::Weapon(WeaponPath::Mdl {
+            primary_id: self.primary_id,
+            secondary_id: self.secondary_id,
+        })
+    }
+
+    pub fn mtrl(&self, variant_id: u16) -> GamePathBuf {
+        GamePath::Weapon(WeaponPath::Mtrl {
+            primary_id: self.primary_id,
+            secondary_id: self.secondary_id,
+            variant_id,
+        })
+    }
+
+    pub fn tex(&self, variant_id: u8) -> GamePathBuf {
+        GamePath::Weapon(WeaponPath::Tex {
+            primary_id: self.primary_id,
+            secondary_id: self.secondary_id,
+            variant_id,
+        })
+    }
+
+    pub fn skeleton(&self) -> GamePathBuf {
+        GamePath::Weapon(WeaponPath::Skeleton {
+            primary_id: self.primary_id,
+            secondary_id: self.secondary_id,
+        })
+    }
+
+    pub fn avfx(&self, effect_id: u16) -> GamePathBuf {
+        GamePath::Weapon(WeaponPath::Avfx {
+            primary_id: self.primary_id,
+            secondary_id: self.secondary_id,
+            effect_id,
+        })
+    }
+}
+
+// chara/monster
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct MonsterModel {
+    pub primary_id: u16,
+    pub secondary_id: u16,
+}
+
+impl MonsterModel {
+    pub fn new(primary_id: u16, secondary_id: u16) -> Self {
+        Self {
+            primary_id,
+            secondary_id,
+        }
+    }
+
+    pub fn imc(&self) -> GamePathBuf {
+        GamePath::Monster(MonsterPath::Imc {
+            primary_id: self.primary_id,
+            secondary_id: self.secondary_id,
+        })
+    }
+
+    pub fn mdl(&self) -> GamePathBuf {
+        GamePath::Monster(MonsterPath::Mdl {
+            primary_id: self.primary_id,
+            secondary_id: self.secondary_id,
+        })
+    }
+
+    pub fn mtrl(&self, variant_id: u16) -> GamePathBuf {
+        GamePath::Monster(MonsterPath::Mtrl {
+            primary_id: self.primary_id,
+            secondary_id: self.secondary_id,
+            variant_id,
+        })
+    }
+
+    pub fn tex(&self, variant_id: u8) -> GamePathBuf {
+        GamePath::Monster(MonsterPath::Tex {
+            primary_id: self.primary_id,
+            secondary_id: self.secondary_id,
+            variant_id,
+        })
+    }
+
+    pub fn skeleton(&self) -> GamePathBuf {
+        GamePath::Monster(MonsterPath::Skeleton {
+            primary_id: self.primary_id,
+            secondary_id: self.secondary_id,
+        })
+    }
+
+    pub fn avfx(&self, effect_id: u16) -> GamePathBuf {
+        GamePath::Monster(MonsterPath::Avfx {
+            primary_id: self.primary_id,
+            secondary_id: self.secondary_id,
+            effect_id,
+        })
+    }
+}
+
+// chara/demihuman
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct DemihumanModel {
+    pub primary_id: u16,
+    pub secondary_id: u16,
+}
+
+impl DemihumanModel {
+    pub fn new(primary_id: u16, secondary_id: u16) -> Self {
+        Self {
+            primary_id,
+            secondary_id,
+        }
+    }
+
+    pub fn imc(&self) -> GamePathBuf {
+        GamePath::Demihuman(DemihumanPath::Imc {
+            primary_id: self.primary_id,
+            secondary_id: self.secondary_id,
+        })
+    }
+
+    pub fn mdl(&self, slot: EquipSlot) -> GamePathBuf {
+        GamePath::Demihuman(DemihumanPath::Mdl {
+            primary_id: self.primary_id,
+            secondary_id: self.secondary_id,
+            slot,
+        })
+    }
+
+    pub fn mtrl(&self, slot: EquipSlot, variant_id: u16) -> GamePathBuf {
+        GamePath::Demihuman(DemihumanPath::Mtrl {
+            primary_id: self.primary_id,
+            secondary_id: self.secondary_id,
+            variant_id,
+            slot,
+        })
+    }
+
+    pub fn tex(&self, slot: EquipSlot, variant_id: u8) -> GamePathBuf {
+        GamePath::Demihuman(DemihumanPath::Tex {
+            primary_id: self.primary_id,
+            secondary_id: self.secondary_id,
+            variant_id,
+            slot,
+        })
+    }
+
+    pub fn skeleton(&self) -> GamePathBuf {
+        GamePath::Demihuman(DemihumanPath::Skeleton {
+            primary_id: self.primary_id,
+            secondary_id: self.secondary_id,
+        })
+    }
+
+    pub fn avfx(&self, effect_id: u16) -> GamePathBuf {
+        GamePath::Demihuman(DemihumanPath::Avfx {
+            primary_id: self.primary_id,
+            secondary_id: self.secondary_id,
+            effect_id,
+        })
+    }
+}
+
+// chara/human
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct CharacterModel {
+    pub model_info: ModelInfo,
+    pub body_type: BodyType,
+    pub primary_id: u16,
+}
+
+impl CharacterModel {
+    pub fn new(model_info: ModelInfo, body_type: BodyType, primary_id: u16) -> Self {
+        Self {
+            model_info,
+            body_type,
+            primary_id,
+        }
+    }
+
+    pub fn mdl(&self, slot: Option<BodyTypeSlot>) -> GamePathBuf {
+        GamePath::Character(CharacterPath::Mdl {
+            primary_id: self.primary_id,
+            model_info: self.model_info,
+            body_type: self.body_type,
+            slot,
+        })
+    }
+
+    pub fn mtrl(&self, slot: Option<BodyTypeSlot>, variant_id: Option<u16>) -> GamePathBuf {
+        GamePath::Character(CharacterPath::Mtrl {
+            primary_id: self.primary_id,
+            variant_id,
+            model_info: self.model_info,
+            body_type: self.body_type,
+            slot,
+        })
+    }
+
+    pub fn tex(&self, slot: Option<BodyTypeSlot>, variant_id: Option<u8>) -> GamePathBuf {
+        GamePath::Character(CharacterPath::Tex {
+            primary_id: self.primary_id,
+            variant_id,
+            model_info: self.model_info,
+            body_type: self.body_type,
+            slot,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use affects_common::EquipSlot;
+
+    use super::{CharacterModel, DemihumanModel, EquipmentModel, MonsterModel, WeaponModel};
+    use crate::{
+        GamePathBuf,
+        path::chara::{BodyType, BodyTypeSlot},
+        types::{Gender, ModelInfo, ModelKind, Race},
+    };
+
+    const MIDLANDER_MALE: ModelInfo = ModelInfo {
+        race: Some(Race::Midlander),
+        gender: Gender::Male,
+        kind: ModelKind::Adult,
+    };
+
+    fn check(expected: &str, path: GamePathBuf) {
+        assert_eq!(expected, path.to_string());
+        assert_eq!(Ok(("", path)), crate::game_path(expected));
+    }
+
+    #[test]
+    fn equipment() {
+        let model = EquipmentModel::new(EquipSlot::Feet, 863);
+        check("chara/equipment/e0863/e0863.imc", model.imc());
+        check(
+            "chara/equipment/e0863/model/c0101e0863_sho.mdl",
+            model.mdl(MIDLANDER_MALE),
+        );
+        check(
+            "chara/equipment/e0863/material/v0006/mt_c0101e0863_sho_a.mtrl",
+            model.mtrl(MIDLANDER_MALE, 6),
+        );
+        check(
+            "chara/equipment/e0863/texture/v01_c0101e0863_sho_norm.tex",
+            model.tex(MIDLANDER_MALE, 1),
+        );
+        check(
+            "chara/equipment/e0863/vfx/eff/ve0002.avfx",
+            model.avfx(2).unwrap(),
+        );
+    }
+
+    #[test]
+    fn accessory() {
+        let model = EquipmentModel::new(EquipSlot::Ears, 52);
+        check("chara/accessory/a0052/a0052.imc", model.imc());
+        check(
+            "chara/accessory/a0052/model/c0101a0052_ear.mdl",
+            model.mdl(MIDLANDER_MALE),
+        );
+        assert_eq!(None, model.avfx(1));
+    }
+
+    #[test]
+    fn weapon() {
+        let model = WeaponModel::new(2021, 1);
+        check("chara/weapon/w2021/obj/body/b0001/b0001.imc", model.imc());
+        check(
+            "chara/weapon/w2021/obj/body/b0001/model/w2021b0001.mdl",
+            model.mdl(),
+        );
+        check(
+            "chara/weapon/w2021/obj/body/b0001/material/v0003/mt_w2021b0001_a.mtrl",
+            model.mtrl(3),
+        );
+    }
+
+    #[test]
+    fn monster() {
+        let model = MonsterModel::new(133, 2);
+        check("chara/monster/m0133/obj/body/b0002/b0002.imc", model.imc());
+        check(
+            "chara/monster/m0133/skeleton/base/b0002/skl_m0133b0002.sklb",
+            model.skeleton(),
+        );
+    }
+
+    #[test]
+    fn demihuman() {
+        let model = DemihumanModel::new(1, 27);
+        check(
+            "chara/demihuman/d0001/obj/equipment/e0027/e0027.imc",
+            model.imc(),
+        );
+        check(
+            "chara/demihuman/d0001/obj/equipment/e0027/model/d0001e0027_dwn.mdl",
+            model.mdl(EquipSlot::Legs),
+        );
+    }
+
+    #[test]
+    fn character() {
+        let model = CharacterModel::new(MIDLANDER_MALE, BodyType::Hair, 173);
+        check(
+            "chara/human/c0101/obj/hair/h0173/model/c0101h0173_hir.mdl",
+            model.mdl(Some(BodyTypeSlot::Hair)),
+        );
+        check(
+            "chara/human/c0101/obj/hair/h0173/material/v0002/mt_c0101h0173_hir_a.mtrl",
+            model.mtrl(Some(BodyTypeSlot::Hair), Some(2)),
+        );
+    }
+}
@@ -1,13 +1,161 @@
-#[derive(Debug, thiserror::Error)]
-pub enum Error<'a> {
-    #[cfg(not(test))]
+use std::any::Any;
+
+use nom::error::{ContextError, ErrorKind, FromExternalError, ParseError as NomParseError};
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum Error {
     #[error("error parsing path: {0}")]
-    Nom(nom::Err<nom::error::Error<&'a str>>),
-    #[cfg(test)]
-    #[error("error parsing path: {0}")]
-    Nom(nom::Err<nom_language::error::VerboseError<&'a str>>),
-    #[error("did not completely parse path")]
-    IncompleteParse,
+    Parse(ParseError),
+    #[error("did not completely parse path (stopped at byte {offset})")]
+    IncompleteParse { offset: usize },
     #[error("path id has mismatched file ids (expected {expected} but found {actual})")]
     MismatchedPathIds { expected: u32, actual: u32 },
 }
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// byte offset into the path where parsing failed
+    pub offset: usize,
+    /// the part of the path before `offset`
+    pub recognised: String,
+    /// what was being parsed when the failure happened, if known
+    pub context: Option<&'static str>,
+    /// tokens that would have been accepted at `offset`
+    pub expected: Vec<String>,
+    /// the underlying reason a matching part of the path was rejected
+    pub cause: Option<Box<Error>>,
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.recognised.is_empty() {
+            write!(f, "did not recognise path")?;
+        } else {
+            write!(f, "recognised `{}`", self.recognised)?;
+        }
+
+        let context = self.context.unwrap_or("the rest of the path");
+        match (&self.cause, self.expected.as_slice()) {
+            (Some(cause), _) => write!(f, " but {context} was invalid ({cause})")?,
+            (None, []) => write!(f, " but {context} could not be parsed")?,
+            (None, expected) => {
+                write!(f, " but expected ")?;
+                if expected.len() > 1 {
+                    write!(f, "one of ")?;
+                }
+
+                for (i, expected) in expected.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ", ")?;
+                    }
+
+                    write!(f, "`{expected}`")?;
+                }
+
+                if let Some(context) = self.context {
+                    write!(f, " in {context}")?;
+                }
+            }
+        }
+
+        write!(f, " at byte {}", self.offset)
+    }
+}
+
+// nom error returned by the parsers, converted into an Error once the whole
+// path is known
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct PathError<I> {
+    input: I,
+    context: Option<&'static str>,
+    expected: Vec<String>,
+    cause: Option<Box<Error>>,
+}
+
+impl<I> PathError<I> {
+    pub(crate) fn expecting(mut self, expected: impl Into<String>) -> Self {
+        if self.cause.is_none() {
+            self.expected = vec![expected.into()];
+        }
+
+        self
+    }
+}
+
+impl PathError<&str> {
+    /// the error for a failure while parsing `path`, which must be the input
+    /// the failed parser was given
+    pub fn into_error(self, path: &str) -> Error {
+        let offset = path.len() - self.input.len();
+        Error::Parse(ParseError {
+            offset,
+            recognised: path[..offset].to_string(),
+            context: self.context,
+            expected: self.expected,
+            cause: self.cause,
+        })
+    }
+}
+
+impl<'a> NomParseError<&'a str> for PathError<&'a str> {
+    fn from_error_kind(input: &'a str, _kind: ErrorKind) -> Self {
+        Self {
+            input,
+            context: None,
+            expected: Vec::new(),
+            cause: None,
+        }
+    }
+
+    fn append(_input: &'a str, _kind: ErrorKind, other: Self) -> Self {
+        other
+    }
+
+    fn or(mut self, other: Self) -> Self {
+        // keep whichever branch got furthest into the path. on a tie, an
+        // actual reason for rejecting the path beats a list of expectations
+        if other.input.len() < self.input.len() {
+            return other;
+        }
+
+        if other.input.len() > self.input.len() {
+            return self;
+        }
+
+        match (&self.cause, &other.cause) {
+            (Some(_), _) => self,
+            (None, Some(_)) => other,
+            (None, None) => {
+                for expected in other.expected {
+                    if !self.expected.contains(&expected) {
+                        self.expected.push(expected);
+                    }
+                }
+
+                if self.context != other.context {
+                    self.context = None;
+                }
+
+                self
+            }
+        }
+    }
+}
+
+impl<'a> ContextError<&'a str> for PathError<&'a str> {
+    fn add_context(_input: &'a str, ctx: &'static str, mut other: Self) -> Self {
+        other.context.get_or_insert(ctx);
+        other
+    }
+}
+
+impl<'a, E: Any> FromExternalError<&'a str, E> for PathError<&'a str> {
+    fn from_external_error(input: &'a str, kind: ErrorKind, e: E) -> Self {
+        let mut error = Self::from_error_kind(input, kind);
+        error.cause = (&e as &dyn Any)
+            .downcast_ref::<Error>()
+            .map(|cause| Box::new(cause.clone()));
+        error
+    }
+}
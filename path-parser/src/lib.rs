@@ -1,42 +1,47 @@
 #[macro_use]
 mod macros;
 
+pub mod builder;
 mod error;
 pub mod path;
 pub mod types;
 
-pub use self::error::Error;
+pub use self::error::{Error, ParseError, PathError};
 
 #[cfg(test)]
 mod test;
 
-use std::str::FromStr;
+use std::{borrow::Cow, str::FromStr};
 
 use affects_common::EquipSlot;
 use nom::{
     Parser,
     branch::alt,
-    bytes::complete::{tag, take, take_till},
+    bytes::complete::{take, take_till},
     combinator::map_res,
     sequence::preceded,
 };
 
 use crate::{
-    path::chara::{
-        AccessoryPath, CharacterPath, DemihumanPath, EquipmentPath, MonsterPath, WeaponPath,
+    path::{
+        bg::BgPath,
+        chara::{
+            AccessoryPath, CharacterPath, DemihumanPath, EquipmentPath, MonsterPath, WeaponPath,
+        },
+        sound::SoundPath,
+        vfx::VfxPath,
     },
     types::{Language, ModelInfo},
 };
 
-type Result<'a, T, E = Error<'a>> = std::result::Result<T, E>;
+type Result<T, E = Error> = std::result::Result<T, E>;
 
-#[cfg(test)]
-type IResult<I, O, E = nom_language::error::VerboseError<I>> = nom::IResult<I, O, E>;
+type IResult<I, O, E = PathError<I>> = nom::IResult<I, O, E>;
 
-#[cfg(not(test))]
-type IResult<I, O, E = nom::error::Error<I>> = nom::IResult<I, O, E>;
+pub type GamePathBuf = GamePath<'static>;
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum GamePath<'a> {
     Monster(MonsterPath),
     Weapon(WeaponPath),
@@ -44,6 +49,9 @@ pub enum GamePath<'a> {
     Equipment(EquipmentPath),
     Accessory(AccessoryPath),
     Character(CharacterPath<'a>),
+    Bg(BgPath<'a>),
+    Vfx(VfxPath<'a>),
+    Sound(SoundPath<'a>),
     Icon {
         group: u64,
         primary_id: u64,
@@ -52,40 +60,141 @@ pub enum GamePath<'a> {
         hires: bool,
     },
     Map {
-        primary_id: &'a str,
+        primary_id: Cow<'a, str>,
         variant: u8,
         suffix: Option<char>,
         extra: Option<char>,
     },
-    FontTexture(&'a str),
+    FontTexture(Cow<'a, str>),
     FontFile {
-        family: &'a str,
+        family: Cow<'a, str>,
         size: u8,
     },
 }
 
 impl<'a> GamePath<'a> {
-    pub fn parse(input: &'a str) -> Result<'a, Self> {
-        let (left, path) = game_path(input)
-            // FIXME: shouldn't need to clone here
-            .map_err(|e| {
-                #[cfg(not(test))]
-                {
-                    Error::Nom(e)
-                }
-
-                #[cfg(test)]
-                {
-                    panic!("do not use GamePath::parse in tests: {e}")
-                }
-            })?;
+    pub fn parse(input: &'a str) -> Result<Self> {
+        let (left, path) = game_path(input).map_err(|e| match e {
+            nom::Err::Error(e) | nom::Err::Failure(e) => e.into_error(input),
+            nom::Err::Incomplete(_) => Error::IncompleteParse {
+                offset: input.len(),
+            },
+        })?;
 
         if !left.is_empty() {
-            return Err(Error::IncompleteParse);
+            return Err(Error::IncompleteParse {
+                offset: input.len() - left.len(),
+            });
         }
 
         Ok(path)
     }
+
+    pub fn into_owned(self) -> GamePathBuf {
+        match self {
+            Self::Monster(path) => GamePath::Monster(path),
+            Self::Weapon(path) => GamePath::Weapon(path),
+            Self::Demihuman(path) => GamePath::Demihuman(path),
+            Self::Equipment(path) => GamePath::Equipment(path),
+            Self::Accessory(path) => GamePath::Accessory(path),
+            Self::Character(path) => GamePath::Character(path.into_owned()),
+            Self::Bg(path) => GamePath::Bg(path.into_owned()),
+            Self::Vfx(path) => GamePath::Vfx(path.into_owned()),
+            Self::Sound(path) => GamePath::Sound(path.into_owned()),
+            Self::Icon {
+                group,
+                primary_id,
+                language,
+                hq,
+                hires,
+            } => GamePath::Icon {
+                group,
+                primary_id,
+                language,
+                hq,
+                hires,
+            },
+            Self::Map {
+                primary_id,
+                variant,
+                suffix,
+                extra,
+            } => GamePath::Map {
+                primary_id: Cow::Owned(primary_id.into_owned()),
+                variant,
+                suffix,
+                extra,
+            },
+            Self::FontTexture(name) => GamePath::FontTexture(Cow::Owned(name.into_owned())),
+            Self::FontFile { family, size } => GamePath::FontFile {
+                family: Cow::Owned(family.into_owned()),
+                size,
+            },
+        }
+    }
+}
+
+// parts of a path that are not kept when parsing (material suffixes, texture
+// kinds, skeleton file types, etc.) are written out in their most common form
+impl std::fmt::Display for GamePath<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Monster(path) => write!(f, "{path}"),
+            Self::Weapon(path) => write!(f, "{path}"),
+            Self::Demihuman(path) => write!(f, "{path}"),
+            Self::Equipment(path) => write!(f, "{path}"),
+            Self::Accessory(path) => write!(f, "{path}"),
+            Self::Character(path) => write!(f, "{path}"),
+            Self::Bg(path) => write!(f, "{path}"),
+            Self::Vfx(path) => write!(f, "{path}"),
+            Self::Sound(path) => write!(f, "{path}"),
+            Self::Icon {
+                group,
+                primary_id,
+                language,
+                hq,
+                hires,
+            } => {
+                write!(f, "ui/icon/{group:<06}/")?;
+                if let Some(language) = language {
+                    write!(f, "{}/", language.as_str())?;
+                }
+
+                if *hq {
+                    write!(f, "hq/")?;
+                }
+
+                write!(f, "{primary_id:<06}")?;
+                if *hires {
+                    write!(f, "_hr1")?;
+                }
+
+                write!(f, ".tex")
+            }
+            Self::Map {
+                primary_id,
+                variant,
+                suffix,
+                extra,
+            } => {
+                write!(
+                    f,
+                    "ui/map/{primary_id}/{variant:<02}/{primary_id}{variant:<02}"
+                )?;
+                if let Some(suffix) = suffix {
+                    write!(f, "{suffix}")?;
+                }
+
+                if let Some(extra) = extra {
+                    write!(f, "_{extra}")?;
+                }
+
+                write!(f, ".tex")
+            }
+            Self::FontTexture(name) => write!(f, "common/font/{name}.tex"),
+            Self::FontFile { family, size } => write!(f, "common/font/{family}_{size}.fdt"),
+        }
+    }
 }
 
 // util
@@ -94,7 +203,16 @@ fn raw_part(input: &str) -> IResult<&str, &str> {
     take_till(|c| c == '/')(input)
 }
 
-fn simple_part_enum<T: FromStr>(input: &str) -> IResult<&str, T> {
+// same as nom's tag, but remembers what it was looking for
+fn tag<'a, 'b>(expected: &'a str) -> impl Fn(&'b str) -> IResult<&'b str, &'b str> {
+    move |input: &'b str| {
+        nom::bytes::complete::tag(expected)
+            .parse(input)
+            .map_err(|e| e.map(|e: PathError<&str>| e.expecting(expected)))
+    }
+}
+
+fn simple_part_enum<T: FromStr<Err: 'static>>(input: &str) -> IResult<&str, T> {
     map_res(raw_part, |part| T::from_str(part)).parse(input)
 }
 
@@ -102,11 +220,15 @@ fn path_id<'a, 'b>(before: &'a str) -> impl Fn(&'b str) -> IResult<&'b str, u16>
     move |input: &'b str| preceded(tag(before), n_digit_id::<u16>(4)).parse(input)
 }
 
-fn n_digit_id<T: FromStr>(n: usize) -> impl Fn(&str) -> IResult<&str, T> {
-    move |input: &str| map_res(take(n), |id: &str| id.parse::<T>()).parse(input)
+fn n_digit_id<T: FromStr<Err: 'static>>(n: usize) -> impl Fn(&str) -> IResult<&str, T> {
+    move |input: &str| {
+        map_res(take(n), |id: &str| id.parse::<T>())
+            .parse(input)
+            .map_err(|e| e.map(|e: PathError<&str>| e.expecting(format!("{n}-digit id"))))
+    }
 }
 
-fn check_repeat_id(expected_id: u16, repeat_id: u16) -> Result<'static, ()> {
+fn check_repeat_id(expected_id: u16, repeat_id: u16) -> Result<()> {
     if expected_id != repeat_id {
         return Err(Error::MismatchedPathIds {
             expected: expected_id as u32,
@@ -118,11 +240,23 @@ fn check_repeat_id(expected_id: u16, repeat_id: u16) -> Result<'static, ()> {
 }
 
 fn equip_slot(input: &str) -> IResult<&str, EquipSlot> {
-    map_res(take(3_usize), EquipSlot::from_str).parse(input)
+    map_res(take(3_usize), EquipSlot::from_str)
+        .parse(input)
+        .map_err(|e| e.map(|e: PathError<&str>| e.expecting("equipment slot")))
 }
 
 // main parser
 
+/// parses the start of a game path, returning what is left of `input`. use
+/// [`GamePath::parse`] to parse a whole path into an [`Error`] on failure
 pub fn game_path(input: &str) -> IResult<&str, GamePath<'_>> {
-    alt((path::common_path, path::chara_path, path::ui_path)).parse(input)
+    alt((
+        path::common_path,
+        path::chara_path,
+        path::bg_path,
+        path::vfx_path,
+        path::sound_path,
+        path::ui_path,
+    ))
+    .parse(input)
 }
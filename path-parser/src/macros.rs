@@ -2,7 +2,8 @@ macro_rules! enum_str {
     {$($(#[$meta: meta])* pub enum $name: ident { $($variant: ident => $str: expr),+ $(,)? })+} => {
         $(
             $(#[$meta])*
-            #[derive(Debug, PartialEq, Eq, Copy, Clone)]
+            #[derive(Debug, PartialEq, Eq, Copy, Clone, Hash)]
+            #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
             pub enum $name {
                 $(
                     $variant,
@@ -23,6 +24,16 @@ macro_rules! enum_str {
                     Ok(part)
                 }
             }
+
+            impl $name {
+                pub fn as_str(self) -> &'static str {
+                    match self {
+                        $(
+                            Self::$variant => $str,
+                        )+
+                    }
+                }
+            }
         )+
     }
 }
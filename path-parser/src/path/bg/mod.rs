@@ -0,0 +1,316 @@
+use std::borrow::Cow;
+
+use nom::{
+    Parser,
+    branch::alt,
+    bytes::complete::take_till,
+    combinator::map,
+    error::context,
+    sequence::{preceded, separated_pair, terminated},
+};
+
+use crate::{GamePath, IResult, raw_part, simple_part_enum, tag};
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum BgPath<'a> {
+    Territory {
+        expansion: Cow<'a, str>,
+        region: Cow<'a, str>,
+        kind: TerritoryKind,
+        zone: Cow<'a, str>,
+        part: BgPart,
+        file: Cow<'a, str>,
+        file_kind: BgFileKind,
+    },
+    Common {
+        category: Cow<'a, str>,
+        file: Cow<'a, str>,
+        file_kind: BgFileKind,
+    },
+}
+
+enum_str! {
+    pub enum TerritoryKind {
+        Field => "fld",
+        Town => "twn",
+        Dungeon => "dun",
+        Raid => "rad",
+        Indoor => "ind",
+        Jail => "jai",
+        Pvp => "pvp",
+        Event => "evt",
+        Housing => "hou",
+    }
+
+    pub enum BgPart {
+        Level => "level",
+        Plate => "bgplate",
+        Parts => "bgparts",
+        Texture => "texture",
+        Material => "material",
+        Collision => "collision",
+    }
+
+    pub enum BgFileKind {
+        Lgb => "lgb",
+        Sgb => "sgb",
+        Lvb => "lvb",
+        Mdl => "mdl",
+        Mtrl => "mtrl",
+        Tex => "tex",
+        Pcb => "pcb",
+        Tera => "tera",
+    }
+}
+
+impl std::fmt::Display for BgPart {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            Self::Level => "Level layout",
+            Self::Plate => "Terrain",
+            Self::Parts => "Props",
+            Self::Texture => "Textures",
+            Self::Material => "Materials",
+            Self::Collision => "Collision",
+        };
+
+        write!(f, "{s}")
+    }
+}
+
+impl BgPath<'_> {
+    pub fn is_housing(&self) -> bool {
+        match self {
+            Self::Territory { kind, .. } => *kind == TerritoryKind::Housing,
+            Self::Common { category, .. } => category == "hou",
+        }
+    }
+
+    pub fn into_owned(self) -> BgPath<'static> {
+        match self {
+            Self::Territory {
+                expansion,
+                region,
+                kind,
+                zone,
+                part,
+                file,
+                file_kind,
+            } => BgPath::Territory {
+                expansion: Cow::Owned(expansion.into_owned()),
+                region: Cow::Owned(region.into_owned()),
+                kind,
+                zone: Cow::Owned(zone.into_owned()),
+                part,
+                file: Cow::Owned(file.into_owned()),
+                file_kind,
+            },
+            Self::Common {
+                category,
+                file,
+                file_kind,
+            } => BgPath::Common {
+                category: Cow::Owned(category.into_owned()),
+                file: Cow::Owned(file.into_owned()),
+                file_kind,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for BgPath<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Territory {
+                expansion,
+                region,
+                kind,
+                zone,
+                part,
+                file,
+                file_kind,
+            } => write!(
+                f,
+                "bg/{expansion}/{region}/{}/{zone}/{}/{file}.{}",
+                kind.as_str(),
+                part.as_str(),
+                file_kind.as_str(),
+            ),
+            Self::Common {
+                category,
+                file,
+                file_kind,
+            } => write!(f, "bgcommon/{category}/{file}.{}", file_kind.as_str()),
+        }
+    }
+}
+
+// util
+
+fn dir_part(input: &str) -> IResult<&str, &str> {
+    terminated(raw_part, tag("/")).parse(input)
+}
+
+fn file_name(input: &str) -> IResult<&str, (&str, BgFileKind)> {
+    context(
+        "file name",
+        separated_pair(
+            take_till(|c| c == '.'),
+            tag("."),
+            simple_part_enum::<BgFileKind>,
+        ),
+    )
+    .parse(input)
+}
+
+// bg and bgcommon
+
+pub(crate) fn bg_path(input: &str) -> IResult<&str, GamePath<'_>> {
+    map(alt((bg_territory_path, bgcommon_path)), GamePath::Bg).parse(input)
+}
+
+// bg/{expansion}/{region}/{kind}/{zone}
+
+fn bg_territory_path(input: &str) -> IResult<&str, BgPath<'_>> {
+    map(
+        (
+            preceded(tag("bg/"), dir_part),
+            dir_part,
+            context(
+                "territory kind",
+                terminated(simple_part_enum::<TerritoryKind>, tag("/")),
+            ),
+            dir_part,
+            context(
+                "territory directory",
+                terminated(simple_part_enum::<BgPart>, tag("/")),
+            ),
+            file_name,
+        ),
+        |(expansion, region, kind, zone, part, (file, file_kind))| BgPath::Territory {
+            expansion: expansion.into(),
+            region: region.into(),
+            kind,
+            zone: zone.into(),
+            part,
+            file: file.into(),
+            file_kind,
+        },
+    )
+    .parse(input)
+}
+
+// bgcommon/{category}
+
+fn bgcommon_path(input: &str) -> IResult<&str, BgPath<'_>> {
+    map(
+        preceded(tag("bgcommon/"), (dir_part, file_name)),
+        |(category, (file, file_kind))| BgPath::Common {
+            category: category.into(),
+            file: file.into(),
+            file_kind,
+        },
+    )
+    .parse(input)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{BgFileKind, BgPart, BgPath, TerritoryKind};
+    use crate::{GamePath, test::test_path};
+
+    #[test]
+    fn territory_level() {
+        const PATH: &str = "bg/ffxiv/sea_s1/fld/s1f1/level/bg.lgb";
+        test_path(
+            PATH,
+            GamePath::Bg(BgPath::Territory {
+                expansion: "ffxiv".into(),
+                region: "sea_s1".into(),
+                kind: TerritoryKind::Field,
+                zone: "s1f1".into(),
+                part: BgPart::Level,
+                file: "bg".into(),
+                file_kind: BgFileKind::Lgb,
+            }),
+        );
+    }
+
+    #[test]
+    fn territory_bgparts() {
+        const PATH: &str = "bg/ex1/01_roc_r2/fld/r2f1/bgparts/r2f1_a1_gate1.mdl";
+        test_path(
+            PATH,
+            GamePath::Bg(BgPath::Territory {
+                expansion: "ex1".into(),
+                region: "01_roc_r2".into(),
+                kind: TerritoryKind::Field,
+                zone: "r2f1".into(),
+                part: BgPart::Parts,
+                file: "r2f1_a1_gate1".into(),
+                file_kind: BgFileKind::Mdl,
+            }),
+        );
+    }
+
+    #[test]
+    fn territory_collision() {
+        const PATH: &str = "bg/ffxiv/sea_s1/twn/s1t1/collision/tr0000.pcb";
+        test_path(
+            PATH,
+            GamePath::Bg(BgPath::Territory {
+                expansion: "ffxiv".into(),
+                region: "sea_s1".into(),
+                kind: TerritoryKind::Town,
+                zone: "s1t1".into(),
+                part: BgPart::Collision,
+                file: "tr0000".into(),
+                file_kind: BgFileKind::Pcb,
+            }),
+        );
+    }
+
+    #[test]
+    fn territory_terrain() {
+        const PATH: &str = "bg/ffxiv/wil_w1/fld/w1f1/bgplate/terrain.tera";
+        test_path(
+            PATH,
+            GamePath::Bg(BgPath::Territory {
+                expansion: "ffxiv".into(),
+                region: "wil_w1".into(),
+                kind: TerritoryKind::Field,
+                zone: "w1f1".into(),
+                part: BgPart::Plate,
+                file: "terrain".into(),
+                file_kind: BgFileKind::Tera,
+            }),
+        );
+    }
+
+    #[test]
+    fn common() {
+        const PATH: &str = "bgcommon/hou/indoor/general/0001/bgparts/fun_b0_m0001.mdl";
+        test_path(
+            PATH,
+            GamePath::Bg(BgPath::Common {
+                category: "hou".into(),
+                file: "indoor/general/0001/bgparts/fun_b0_m0001".into(),
+                file_kind: BgFileKind::Mdl,
+            }),
+        );
+    }
+
+    #[test]
+    fn common_texture() {
+        const PATH: &str = "bgcommon/world/aet/shared/texture/w_aet_001a_d.tex";
+        test_path(
+            PATH,
+            GamePath::Bg(BgPath::Common {
+                category: "world".into(),
+                file: "aet/shared/texture/w_aet_001a_d".into(),
+                file_kind: BgFileKind::Tex,
+            }),
+        );
+    }
+}
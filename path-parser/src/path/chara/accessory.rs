@@ -1,17 +1,19 @@
 use nom::{
     Parser,
     branch::alt,
-    bytes::complete::{tag, take_till},
+    bytes::complete::take_till,
     combinator::{map, map_res},
+    error::context,
     sequence::{delimited, preceded, separated_pair, terminated},
 };
 
 use crate::{
-    EquipSlot, GamePath, IResult, Result, check_repeat_id, equip_slot, n_digit_id, path_id,
+    EquipSlot, GamePath, IResult, Result, check_repeat_id, equip_slot, n_digit_id, path_id, tag,
     types::{ModelInfo, model_info},
 };
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum AccessoryPath {
     Imc(u16),
     Mdl {
@@ -34,6 +36,46 @@ pub enum AccessoryPath {
     // NOTE: Avfx do not exist for accessories yet
 }
 
+impl std::fmt::Display for AccessoryPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Imc(id) => write!(f, "chara/accessory/a{id:<04}/a{id:<04}.imc"),
+            Self::Mdl {
+                primary_id,
+                info,
+                slot,
+            } => write!(
+                f,
+                "chara/accessory/a{primary_id:<04}/model/c{info:<04}a{primary_id:<04}_{slot}.mdl",
+                info = info.path_raw_id(),
+                slot = slot.as_str(),
+            ),
+            Self::Mtrl {
+                primary_id,
+                variant_id,
+                model_info,
+                slot,
+            } => write!(
+                f,
+                "chara/accessory/a{primary_id:<04}/material/v{variant_id:<04}/mt_c{info:<04}a{primary_id:<04}_{slot}_a.mtrl",
+                info = model_info.path_raw_id(),
+                slot = slot.as_str(),
+            ),
+            Self::Tex {
+                primary_id,
+                variant_id,
+                model_info,
+                slot,
+            } => write!(
+                f,
+                "chara/accessory/a{primary_id:<04}/texture/v{variant_id:<02}_c{info:<04}a{primary_id:<04}_{slot}_norm.tex",
+                info = model_info.path_raw_id(),
+                slot = slot.as_str(),
+            ),
+        }
+    }
+}
+
 // util
 
 fn file_repeat(input: &str) -> IResult<&str, (ModelInfo, u16)> {
@@ -62,12 +104,15 @@ pub(crate) fn chara_accessory_path(input: &str) -> IResult<&str, GamePath<'_>> {
 
 fn imc_path(primary_id: u16) -> impl Fn(&str) -> IResult<&str, AccessoryPath> {
     move |input: &str| {
-        map_res(
-            terminated(path_id("a"), tag(".imc")),
-            |repeat_primary_id| -> Result<AccessoryPath> {
-                check_repeat_id(primary_id, repeat_primary_id)?;
-                Ok(AccessoryPath::Imc(primary_id))
-            },
+        context(
+            "imc file name",
+            map_res(
+                terminated(path_id("a"), tag(".imc")),
+                |repeat_primary_id| -> Result<AccessoryPath> {
+                    check_repeat_id(primary_id, repeat_primary_id)?;
+                    Ok(AccessoryPath::Imc(primary_id))
+                },
+            ),
         )
         .parse(input)
     }
@@ -77,20 +122,23 @@ fn imc_path(primary_id: u16) -> impl Fn(&str) -> IResult<&str, AccessoryPath> {
 
 fn mdl_path(primary_id: u16) -> impl Fn(&str) -> IResult<&str, AccessoryPath> {
     move |input: &str| {
-        map_res(
-            delimited(
-                tag("model/"),
-                separated_pair(file_repeat, tag("_"), equip_slot),
-                tag(".mdl"),
+        context(
+            "model file name",
+            map_res(
+                delimited(
+                    tag("model/"),
+                    separated_pair(file_repeat, tag("_"), equip_slot),
+                    tag(".mdl"),
+                ),
+                |((info, repeat_primary_id), slot)| -> Result<AccessoryPath> {
+                    check_repeat_id(primary_id, repeat_primary_id)?;
+                    Ok(AccessoryPath::Mdl {
+                        primary_id,
+                        info,
+                        slot,
+                    })
+                },
             ),
-            |((info, repeat_primary_id), slot)| -> Result<AccessoryPath> {
-                check_repeat_id(primary_id, repeat_primary_id)?;
-                Ok(AccessoryPath::Mdl {
-                    primary_id,
-                    info,
-                    slot,
-                })
-            },
         )
         .parse(input)
     }
@@ -100,24 +148,27 @@ fn mdl_path(primary_id: u16) -> impl Fn(&str) -> IResult<&str, AccessoryPath> {
 
 fn mtrl_path(primary_id: u16) -> impl Fn(&str) -> IResult<&str, AccessoryPath> {
     move |input: &str| {
-        map_res(
-            (
-                delimited(tag("material/"), path_id("v"), tag("/")),
-                delimited(
-                    tag("mt_"),
-                    separated_pair(file_repeat, tag("_"), equip_slot),
-                    (take_till(|c| c == '.'), tag(".mtrl")),
+        context(
+            "material file name",
+            map_res(
+                (
+                    delimited(tag("material/"), path_id("v"), tag("/")),
+                    delimited(
+                        tag("mt_"),
+                        separated_pair(file_repeat, tag("_"), equip_slot),
+                        (take_till(|c| c == '.'), tag(".mtrl")),
+                    ),
                 ),
+                |(variant_id, ((model_info, repeat_primary_id), slot))| -> Result<AccessoryPath> {
+                    check_repeat_id(primary_id, repeat_primary_id)?;
+                    Ok(AccessoryPath::Mtrl {
+                        primary_id,
+                        variant_id,
+                        model_info,
+                        slot,
+                    })
+                },
             ),
-            |(variant_id, ((model_info, repeat_primary_id), slot))| -> Result<AccessoryPath> {
-                check_repeat_id(primary_id, repeat_primary_id)?;
-                Ok(AccessoryPath::Mtrl {
-                    primary_id,
-                    variant_id,
-                    model_info,
-                    slot,
-                })
-            },
         )
         .parse(input)
     }
@@ -127,23 +178,26 @@ fn mtrl_path(primary_id: u16) -> impl Fn(&str) -> IResult<&str, AccessoryPath> {
 
 fn tex_path(primary_id: u16) -> impl Fn(&str) -> IResult<&str, AccessoryPath> {
     move |input: &str| {
-        map_res(
-            (
-                delimited(tag("texture/v"), n_digit_id::<u8>(2), tag("_")),
-                terminated(
-                    separated_pair(file_repeat, tag("_"), equip_slot),
-                    (take_till(|c| c == '.'), tag(".tex")),
+        context(
+            "texture file name",
+            map_res(
+                (
+                    delimited(tag("texture/v"), n_digit_id::<u8>(2), tag("_")),
+                    terminated(
+                        separated_pair(file_repeat, tag("_"), equip_slot),
+                        (take_till(|c| c == '.'), tag(".tex")),
+                    ),
                 ),
+                |(variant_id, ((model_info, repeat_primary_id), slot))| -> Result<AccessoryPath> {
+                    check_repeat_id(primary_id, repeat_primary_id)?;
+                    Ok(AccessoryPath::Tex {
+                        primary_id,
+                        model_info,
+                        variant_id,
+                        slot,
+                    })
+                },
             ),
-            |(variant_id, ((model_info, repeat_primary_id), slot))| -> Result<AccessoryPath> {
-                check_repeat_id(primary_id, repeat_primary_id)?;
-                Ok(AccessoryPath::Tex {
-                    primary_id,
-                    model_info,
-                    variant_id,
-                    slot,
-                })
-            },
         )
         .parse(input)
     }
@@ -1,20 +1,22 @@
-use std::str::FromStr;
+use std::{borrow::Cow, str::FromStr};
 
 use nom::{
     Parser,
     branch::alt,
-    bytes::complete::{tag, take, take_till, take_until},
+    bytes::complete::{take, take_till, take_until},
     character::complete::{digit1, one_of},
     combinator::{map, map_res, opt},
+    error::context,
     sequence::{delimited, preceded, terminated},
 };
 
 use crate::{
-    GamePath, IResult, n_digit_id, path_id, simple_part_enum,
+    GamePath, IResult, n_digit_id, path_id, simple_part_enum, tag,
     types::{ModelInfo, SkeletonSlot, model_info, model_info_with_raw, skeleton_slot},
 };
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CharacterPath<'a> {
     Mdl {
         primary_id: u16,
@@ -36,12 +38,12 @@ pub enum CharacterPath<'a> {
         body_type: BodyType,
         slot: Option<BodyTypeSlot>,
     },
-    Catchlight(&'a str),
+    Catchlight(Cow<'a, str>),
     Eye {
         id: u8,
-        kind: &'a str,
+        kind: Cow<'a, str>,
     },
-    Skin(&'a str),
+    Skin(Cow<'a, str>),
     Decal {
         kind: DecalType,
         primary_id: u64,
@@ -51,12 +53,12 @@ pub enum CharacterPath<'a> {
         model_info: ModelInfo,
         slot: SkeletonSlot,
     },
-    Tmb(&'a str),
+    Tmb(Cow<'a, str>),
     Pap {
         primary_id: u16,
         model_info: ModelInfo,
-        category: Option<&'a str>,
-        key: &'a str,
+        category: Option<Cow<'a, str>>,
+        key: Cow<'a, str>,
     },
     Atch(ModelInfo),
 }
@@ -161,6 +163,224 @@ impl std::fmt::Display for DecalType {
     }
 }
 
+impl CharacterPath<'_> {
+    pub fn into_owned(self) -> CharacterPath<'static> {
+        match self {
+            Self::Mdl {
+                primary_id,
+                model_info,
+                body_type,
+                slot,
+            } => CharacterPath::Mdl {
+                primary_id,
+                model_info,
+                body_type,
+                slot,
+            },
+            Self::Mtrl {
+                primary_id,
+                variant_id,
+                model_info,
+                body_type,
+                slot,
+            } => CharacterPath::Mtrl {
+                primary_id,
+                variant_id,
+                model_info,
+                body_type,
+                slot,
+            },
+            Self::Tex {
+                primary_id,
+                variant_id,
+                model_info,
+                body_type,
+                slot,
+            } => CharacterPath::Tex {
+                primary_id,
+                variant_id,
+                model_info,
+                body_type,
+                slot,
+            },
+            Self::Catchlight(catchlight) => {
+                CharacterPath::Catchlight(Cow::Owned(catchlight.into_owned()))
+            }
+            Self::Eye { id, kind } => CharacterPath::Eye {
+                id,
+                kind: Cow::Owned(kind.into_owned()),
+            },
+            Self::Skin(skin) => CharacterPath::Skin(Cow::Owned(skin.into_owned())),
+            Self::Decal { kind, primary_id } => CharacterPath::Decal { kind, primary_id },
+            Self::Skeleton {
+                primary_id,
+                model_info,
+                slot,
+            } => CharacterPath::Skeleton {
+                primary_id,
+                model_info,
+                slot,
+            },
+            Self::Tmb(key) => CharacterPath::Tmb(Cow::Owned(key.into_owned())),
+            Self::Pap {
+                primary_id,
+                model_info,
+                category,
+                key,
+            } => CharacterPath::Pap {
+                primary_id,
+                model_info,
+                category: category.map(|category| Cow::Owned(category.into_owned())),
+                key: Cow::Owned(key.into_owned()),
+            },
+            Self::Atch(model_info) => CharacterPath::Atch(model_info),
+        }
+    }
+}
+
+impl std::fmt::Display for CharacterPath<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Mdl {
+                primary_id,
+                model_info,
+                body_type,
+                slot,
+            } => {
+                write_obj_dir(f, model_info, body_type, primary_id)?;
+                write!(f, "model/")?;
+                write_file_repeat(f, model_info, body_type, primary_id)?;
+                write_slot(f, slot)?;
+                write!(f, ".mdl")
+            }
+            Self::Mtrl {
+                primary_id,
+                variant_id,
+                model_info,
+                body_type,
+                slot,
+            } => {
+                write_obj_dir(f, model_info, body_type, primary_id)?;
+                write!(f, "material/")?;
+                if let Some(variant_id) = variant_id {
+                    write!(f, "v{variant_id:<04}/")?;
+                }
+
+                write!(f, "mt_")?;
+                write_file_repeat(f, model_info, body_type, primary_id)?;
+                write_slot(f, slot)?;
+                write!(f, "_a.mtrl")
+            }
+            Self::Tex {
+                primary_id,
+                variant_id,
+                model_info,
+                body_type,
+                slot,
+            } => {
+                write_obj_dir(f, model_info, body_type, primary_id)?;
+                write!(f, "texture/")?;
+                if let Some(variant_id) = variant_id {
+                    write!(f, "v{variant_id:<02}_")?;
+                }
+
+                write_file_repeat(f, model_info, body_type, primary_id)?;
+                write_slot(f, slot)?;
+                write!(f, "_norm.tex")
+            }
+            Self::Catchlight(ref catchlight) => {
+                write!(f, "chara/common/texture/catchlight{catchlight}.tex")
+            }
+            Self::Eye { id, ref kind } => {
+                write!(f, "chara/common/texture/eye/eye{id:<02}_{kind}.tex")
+            }
+            Self::Skin(ref skin) => write!(f, "chara/common/texture/skin{skin}.tex"),
+            Self::Decal { kind, primary_id } => {
+                let prefix = match kind {
+                    DecalType::Face => '_',
+                    DecalType::Equip => '-',
+                };
+
+                write!(
+                    f,
+                    "chara/common/texture/decal_{}/{prefix}decal_{primary_id}.tex",
+                    kind.as_str(),
+                )
+            }
+            Self::Skeleton {
+                primary_id,
+                model_info,
+                slot,
+            } => write!(
+                f,
+                "chara/human/c{info:<04}/skeleton/{slot}/{abbr}{primary_id:<04}/skl_c{info:<04}{abbr}{primary_id:<04}.sklb",
+                info = model_info.path_raw_id(),
+                slot = slot.as_str(),
+                abbr = slot.abbreviation(),
+            ),
+            Self::Tmb(ref key) => write!(f, "chara/action/{key}.tmb"),
+            Self::Pap {
+                primary_id,
+                model_info,
+                ref category,
+                ref key,
+            } => {
+                write!(
+                    f,
+                    "chara/human/c{:<04}/animation/a{primary_id:<04}/",
+                    model_info.path_raw_id(),
+                )?;
+                if let Some(category) = category {
+                    write!(f, "bt_{category}/")?;
+                }
+
+                write!(f, "{key}.pap")
+            }
+            Self::Atch(model_info) => write!(
+                f,
+                "chara/xls/attachOffset/c{:<04}.atch",
+                model_info.path_raw_id(),
+            ),
+        }
+    }
+}
+
+fn write_obj_dir(
+    f: &mut std::fmt::Formatter<'_>,
+    model_info: ModelInfo,
+    body_type: BodyType,
+    primary_id: u16,
+) -> std::fmt::Result {
+    write!(
+        f,
+        "chara/human/c{:<04}/obj/{}/{}{primary_id:<04}/",
+        model_info.path_raw_id(),
+        body_type.as_str(),
+        body_type.abbreviation(),
+    )
+}
+
+fn write_file_repeat(
+    f: &mut std::fmt::Formatter<'_>,
+    model_info: ModelInfo,
+    body_type: BodyType,
+    primary_id: u16,
+) -> std::fmt::Result {
+    write!(
+        f,
+        "c{:<04}{}{primary_id:<04}",
+        model_info.path_raw_id(),
+        body_type.abbreviation(),
+    )
+}
+
+fn write_slot(f: &mut std::fmt::Formatter<'_>, slot: Option<BodyTypeSlot>) -> std::fmt::Result {
+    match slot {
+        Some(slot) => write!(f, "_{}", slot.as_str()),
+        None => Ok(()),
+    }
+}
+
 // util
 
 fn file_repeat(
@@ -170,9 +390,9 @@ fn file_repeat(
 ) -> impl Fn(&str) -> IResult<&str, (&str, &str, &str)> {
     move |input: &str| {
         (
-            tag(&*format!("c{raw_model_info:<04}")),
+            tag(&format!("c{raw_model_info:<04}")),
             tag(body_type.abbreviation()),
-            tag(&*format!("{primary_id:<04}")),
+            tag(&format!("{primary_id:<04}")),
         )
             .parse(input)
     }
@@ -237,21 +457,24 @@ fn mdl_path(
     body_type: BodyType,
 ) -> impl Fn(&str) -> IResult<&str, CharacterPath<'_>> {
     move |input: &str| {
-        map(
-            delimited(
-                tag("model/"),
-                (
-                    file_repeat(primary_id, model_info.0, body_type),
-                    opt(preceded(tag("_"), body_type_slot)),
+        context(
+            "model file name",
+            map(
+                delimited(
+                    tag("model/"),
+                    (
+                        file_repeat(primary_id, model_info.0, body_type),
+                        opt(preceded(tag("_"), body_type_slot)),
+                    ),
+                    tag(".mdl"),
                 ),
-                tag(".mdl"),
+                |(_repeat, slot)| CharacterPath::Mdl {
+                    primary_id,
+                    model_info: model_info.1,
+                    body_type,
+                    slot,
+                },
             ),
-            |(_repeat, slot)| CharacterPath::Mdl {
-                primary_id,
-                model_info: model_info.1,
-                body_type,
-                slot,
-            },
         )
         .parse(input)
     }
@@ -265,22 +488,25 @@ fn mtrl_path(
     body_type: BodyType,
 ) -> impl Fn(&str) -> IResult<&str, CharacterPath<'_>> {
     move |input: &str| {
-        map(
-            (
-                preceded(tag("material/"), opt(terminated(path_id("v"), tag("/")))),
-                preceded(tag("mt_"), file_repeat(primary_id, model_info.0, body_type)),
-                terminated(
-                    opt(preceded(tag("_"), body_type_slot)),
-                    (take_till(|c| c == '.'), tag(".mtrl")),
+        context(
+            "material file name",
+            map(
+                (
+                    preceded(tag("material/"), opt(terminated(path_id("v"), tag("/")))),
+                    preceded(tag("mt_"), file_repeat(primary_id, model_info.0, body_type)),
+                    terminated(
+                        opt(preceded(tag("_"), body_type_slot)),
+                        (take_till(|c| c == '.'), tag(".mtrl")),
+                    ),
                 ),
+                |(variant_id, _repeat, slot)| CharacterPath::Mtrl {
+                    primary_id,
+                    variant_id,
+                    model_info: model_info.1,
+                    body_type,
+                    slot,
+                },
             ),
-            |(variant_id, _repeat, slot)| CharacterPath::Mtrl {
-                primary_id,
-                variant_id,
-                model_info: model_info.1,
-                body_type,
-                slot,
-            },
         )
         .parse(input)
     }
@@ -294,25 +520,28 @@ fn tex_path(
     body_type: BodyType,
 ) -> impl Fn(&str) -> IResult<&str, CharacterPath<'_>> {
     move |input: &str| {
-        map(
-            (
-                preceded(
-                    (tag("texture/"), opt(tag("--"))),
-                    opt(delimited(tag("v"), n_digit_id::<u8>(2), tag("_"))),
-                ),
-                file_repeat(primary_id, model_info.0, body_type),
-                terminated(
-                    opt(preceded(tag("_"), body_type_slot)),
-                    (take_till(|c| c == '.'), tag(".tex")),
+        context(
+            "texture file name",
+            map(
+                (
+                    preceded(
+                        (tag("texture/"), opt(tag("--"))),
+                        opt(delimited(tag("v"), n_digit_id::<u8>(2), tag("_"))),
+                    ),
+                    file_repeat(primary_id, model_info.0, body_type),
+                    terminated(
+                        opt(preceded(tag("_"), body_type_slot)),
+                        (take_till(|c| c == '.'), tag(".tex")),
+                    ),
                 ),
+                |(variant_id, _repeat, slot)| CharacterPath::Tex {
+                    primary_id,
+                    variant_id,
+                    model_info: model_info.1,
+                    body_type,
+                    slot,
+                },
             ),
-            |(variant_id, _repeat, slot)| CharacterPath::Tex {
-                primary_id,
-                variant_id,
-                model_info: model_info.1,
-                body_type,
-                slot,
-            },
         )
         .parse(input)
     }
@@ -327,7 +556,7 @@ fn catchlight_path(input: &str) -> IResult<&str, CharacterPath<'_>> {
             take_until(".tex"),
             tag(".tex"),
         ),
-        CharacterPath::Catchlight,
+        |catchlight: &str| CharacterPath::Catchlight(catchlight.into()),
     )
     .parse(input)
 }
@@ -340,7 +569,10 @@ fn eye_path(input: &str) -> IResult<&str, CharacterPath<'_>> {
             delimited(tag("common/texture/eye/eye"), n_digit_id::<u8>(2), tag("_")),
             terminated(take_till(|c| c == '.'), tag(".tex")),
         ),
-        |(id, kind)| CharacterPath::Eye { id, kind },
+        |(id, kind)| CharacterPath::Eye {
+            id,
+            kind: kind.into(),
+        },
     )
     .parse(input)
 }
@@ -350,7 +582,7 @@ fn eye_path(input: &str) -> IResult<&str, CharacterPath<'_>> {
 fn skin_path(input: &str) -> IResult<&str, CharacterPath<'_>> {
     map(
         delimited(tag("common/texture/skin"), take_until(".tex"), tag(".tex")),
-        CharacterPath::Skin,
+        |skin: &str| CharacterPath::Skin(skin.into()),
     )
     .parse(input)
 }
@@ -390,9 +622,9 @@ fn skeleton_path(input: &str) -> IResult<&str, CharacterPath<'_>> {
         delimited(
             alt((tag("eid_"), tag("skl_"), tag("phy_"), tag("kdi_"))),
             (
-                tag(&*format!("c{:<04}", model_info.0)),
+                tag(&format!("c{:<04}", model_info.0)),
                 tag(slot.abbreviation()),
-                tag(&*format!("{primary_id:<04}")),
+                tag(&format!("{primary_id:<04}")),
             ),
             alt((
                 tag(".eid"),
@@ -416,7 +648,7 @@ fn skeleton_path(input: &str) -> IResult<&str, CharacterPath<'_>> {
 fn tmb_path(input: &str) -> IResult<&str, CharacterPath<'_>> {
     map(
         delimited(tag("action/"), take_until(".tmb"), tag(".tmb")),
-        CharacterPath::Tmb,
+        |key: &str| CharacterPath::Tmb(key.into()),
     )
     .parse(input)
 }
@@ -440,8 +672,8 @@ fn pap_path(input: &str) -> IResult<&str, CharacterPath<'_>> {
         |(model_info, primary_id, category, anim_key)| CharacterPath::Pap {
             primary_id,
             model_info,
-            category,
-            key: anim_key,
+            category: category.map(Cow::from),
+            key: anim_key.into(),
         },
     )
     .parse(input)
@@ -630,7 +862,10 @@ mod test {
     fn catchlight() {
         const PATH: &str = "chara/common/texture/catchlight_2.tex";
 
-        test_path(PATH, GamePath::Character(CharacterPath::Catchlight("_2")));
+        test_path(
+            PATH,
+            GamePath::Character(CharacterPath::Catchlight("_2".into())),
+        );
     }
 
     #[test]
@@ -640,7 +875,7 @@ mod test {
             PATH,
             GamePath::Character(CharacterPath::Eye {
                 id: 2,
-                kind: "base",
+                kind: "base".into(),
             }),
         );
     }
@@ -649,7 +884,10 @@ mod test {
     fn skin() {
         const PATH: &str = "chara/common/texture/skin_mask.tex";
 
-        test_path(PATH, GamePath::Character(CharacterPath::Skin("_mask")));
+        test_path(
+            PATH,
+            GamePath::Character(CharacterPath::Skin("_mask".into())),
+        );
     }
 
     #[test]
@@ -709,7 +947,7 @@ mod test {
 
         test_path(
             PATH,
-            GamePath::Character(CharacterPath::Tmb("magic/2ff_sage/mgc024")),
+            GamePath::Character(CharacterPath::Tmb("magic/2ff_sage/mgc024".into())),
         );
     }
 
@@ -722,13 +960,13 @@ mod test {
             PATH,
             GamePath::Character(CharacterPath::Pap {
                 primary_id: 1,
-                category: Some("common"),
+                category: Some("common".into()),
                 model_info: ModelInfo {
                     race: Some(Race::Midlander),
                     gender: Gender::Male,
                     kind: ModelKind::Adult,
                 },
-                key: "ability/cnj_white/abl025",
+                key: "ability/cnj_white/abl025".into(),
             }),
         );
     }
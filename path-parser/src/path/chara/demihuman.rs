@@ -1,16 +1,18 @@
 use nom::{
     Parser,
     branch::alt,
-    bytes::complete::{tag, take_till},
+    bytes::complete::take_till,
     combinator::{map, map_res},
+    error::context,
     sequence::{delimited, separated_pair, terminated},
 };
 
 use crate::{
-    EquipSlot, GamePath, IResult, Result, check_repeat_id, equip_slot, n_digit_id, path_id,
+    EquipSlot, GamePath, IResult, Result, check_repeat_id, equip_slot, n_digit_id, path_id, tag,
 };
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum DemihumanPath {
     Imc {
         primary_id: u16,
@@ -44,6 +46,64 @@ pub enum DemihumanPath {
     },
 }
 
+impl std::fmt::Display for DemihumanPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Imc {
+                primary_id,
+                secondary_id,
+            } => write!(
+                f,
+                "chara/demihuman/d{primary_id:<04}/obj/equipment/e{secondary_id:<04}/e{secondary_id:<04}.imc"
+            ),
+            Self::Mdl {
+                primary_id,
+                secondary_id,
+                slot,
+            } => write!(
+                f,
+                "chara/demihuman/d{primary_id:<04}/obj/equipment/e{secondary_id:<04}/model/d{primary_id:<04}e{secondary_id:<04}_{slot}.mdl",
+                slot = slot.as_str(),
+            ),
+            Self::Mtrl {
+                primary_id,
+                secondary_id,
+                variant_id,
+                slot,
+            } => write!(
+                f,
+                "chara/demihuman/d{primary_id:<04}/obj/equipment/e{secondary_id:<04}/material/v{variant_id:<04}/mt_d{primary_id:<04}e{secondary_id:<04}_{slot}_a.mtrl",
+                slot = slot.as_str(),
+            ),
+            Self::Tex {
+                primary_id,
+                secondary_id,
+                variant_id,
+                slot,
+            } => write!(
+                f,
+                "chara/demihuman/d{primary_id:<04}/obj/equipment/e{secondary_id:<04}/texture/v{variant_id:<02}_d{primary_id:<04}e{secondary_id:<04}_{slot}_norm.tex",
+                slot = slot.as_str(),
+            ),
+            Self::Skeleton {
+                primary_id,
+                secondary_id,
+            } => write!(
+                f,
+                "chara/demihuman/d{primary_id:<04}/skeleton/base/b{secondary_id:<04}/skl_d{primary_id:<04}b{secondary_id:<04}.sklb"
+            ),
+            Self::Avfx {
+                primary_id,
+                secondary_id,
+                effect_id,
+            } => write!(
+                f,
+                "chara/demihuman/d{primary_id:<04}/obj/equipment/e{secondary_id:<04}/vfx/eff/ve{effect_id:<04}.avfx"
+            ),
+        }
+    }
+}
+
 // util
 
 fn file_repeat(input: &str) -> IResult<&str, (u16, u16)> {
@@ -80,15 +140,18 @@ fn chara_demihuman_path_simple(input: &str) -> IResult<&str, GamePath<'_>> {
 
 fn imc_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str, DemihumanPath> {
     move |input: &str| {
-        map_res(
-            terminated(path_id("e"), tag(".imc")),
-            |repeat_secondary_id| -> Result<DemihumanPath> {
-                check_repeat_id(secondary_id, repeat_secondary_id)?;
-                Ok(DemihumanPath::Imc {
-                    primary_id,
-                    secondary_id,
-                })
-            },
+        context(
+            "imc file name",
+            map_res(
+                terminated(path_id("e"), tag(".imc")),
+                |repeat_secondary_id| -> Result<DemihumanPath> {
+                    check_repeat_id(secondary_id, repeat_secondary_id)?;
+                    Ok(DemihumanPath::Imc {
+                        primary_id,
+                        secondary_id,
+                    })
+                },
+            ),
         )
         .parse(input)
     }
@@ -98,21 +161,24 @@ fn imc_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str
 
 fn mdl_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str, DemihumanPath> {
     move |input: &str| {
-        map_res(
-            delimited(
-                tag("model/"),
-                separated_pair(file_repeat, tag("_"), equip_slot),
-                tag(".mdl"),
+        context(
+            "model file name",
+            map_res(
+                delimited(
+                    tag("model/"),
+                    separated_pair(file_repeat, tag("_"), equip_slot),
+                    tag(".mdl"),
+                ),
+                |((repeat_primary_id, repeat_secondary_id), slot)| -> Result<DemihumanPath> {
+                    check_repeat_id(primary_id, repeat_primary_id)?;
+                    check_repeat_id(secondary_id, repeat_secondary_id)?;
+                    Ok(DemihumanPath::Mdl {
+                        primary_id,
+                        secondary_id,
+                        slot,
+                    })
+                },
             ),
-            |((repeat_primary_id, repeat_secondary_id), slot)| -> Result<DemihumanPath> {
-                check_repeat_id(primary_id, repeat_primary_id)?;
-                check_repeat_id(secondary_id, repeat_secondary_id)?;
-                Ok(DemihumanPath::Mdl {
-                    primary_id,
-                    secondary_id,
-                    slot,
-                })
-            },
         )
         .parse(input)
     }
@@ -122,25 +188,28 @@ fn mdl_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str
 
 fn mtrl_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str, DemihumanPath> {
     move |input: &str| {
-        map_res(
-            (
-                delimited(tag("material/"), path_id("v"), tag("/")),
-                delimited(
-                    tag("mt_"),
-                    separated_pair(file_repeat, tag("_"), equip_slot),
-                    (take_till(|c| c == '.'), tag(".mtrl")),
+        context(
+            "material file name",
+            map_res(
+                (
+                    delimited(tag("material/"), path_id("v"), tag("/")),
+                    delimited(
+                        tag("mt_"),
+                        separated_pair(file_repeat, tag("_"), equip_slot),
+                        (take_till(|c| c == '.'), tag(".mtrl")),
+                    ),
                 ),
+                |(variant_id, ((repeat_primary_id, repeat_secondary_id), slot))| -> Result<DemihumanPath> {
+                    check_repeat_id(primary_id, repeat_primary_id)?;
+                    check_repeat_id(secondary_id, repeat_secondary_id)?;
+                    Ok(DemihumanPath::Mtrl {
+                        primary_id,
+                        secondary_id,
+                        variant_id,
+                        slot,
+                    })
+                },
             ),
-            |(variant_id, ((repeat_primary_id, repeat_secondary_id), slot))| -> Result<DemihumanPath> {
-                check_repeat_id(primary_id, repeat_primary_id)?;
-                check_repeat_id(secondary_id, repeat_secondary_id)?;
-                Ok(DemihumanPath::Mtrl {
-                    primary_id,
-                    secondary_id,
-                    variant_id,
-                    slot,
-                })
-            },
         )
         .parse(input)
     }
@@ -150,24 +219,27 @@ fn mtrl_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&st
 
 fn tex_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str, DemihumanPath> {
     move |input: &str| {
-        map_res(
-            (
-                delimited(tag("texture/v"), n_digit_id::<u8>(2), tag("_")),
-                terminated(
-                    separated_pair(file_repeat, tag("_"), equip_slot),
-                    (take_till(|c| c == '.'), tag(".tex")),
+        context(
+            "texture file name",
+            map_res(
+                (
+                    delimited(tag("texture/v"), n_digit_id::<u8>(2), tag("_")),
+                    terminated(
+                        separated_pair(file_repeat, tag("_"), equip_slot),
+                        (take_till(|c| c == '.'), tag(".tex")),
+                    ),
                 ),
+                |(variant_id, ((repeated_primary_id, repeated_secondary_id), slot))| -> Result<DemihumanPath> {
+                    check_repeat_id(primary_id, repeated_primary_id)?;
+                    check_repeat_id(secondary_id, repeated_secondary_id)?;
+                    Ok(DemihumanPath::Tex {
+                        primary_id,
+                        secondary_id,
+                        variant_id,
+                        slot,
+                    })
+                },
             ),
-            |(variant_id, ((repeated_primary_id, repeated_secondary_id), slot))| -> Result<DemihumanPath> {
-                check_repeat_id(primary_id, repeated_primary_id)?;
-                check_repeat_id(secondary_id, repeated_secondary_id)?;
-                Ok(DemihumanPath::Tex {
-                    primary_id,
-                    secondary_id,
-                    variant_id,
-                    slot,
-                })
-            },
         )
         .parse(input)
     }
@@ -176,24 +248,27 @@ fn tex_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str
 // chara/demihuman/.../skeleton
 
 fn chara_demihuman_path_skeleton(input: &str) -> IResult<&str, GamePath<'_>> {
-    map_res(
-        (
-            delimited(tag("demihuman/"), path_id("d"), tag("/")),
-            delimited(tag("skeleton/base/"), path_id("b"), tag("/")),
-            delimited(
-                alt((tag("eid_"), tag("skl_"), tag("phy_"))),
-                (path_id("d"), path_id("b")),
-                alt((tag(".eid"), tag(".sklb"), tag(".phyb"), tag(".skp"))),
+    context(
+        "skeleton file name",
+        map_res(
+            (
+                delimited(tag("demihuman/"), path_id("d"), tag("/")),
+                delimited(tag("skeleton/base/"), path_id("b"), tag("/")),
+                delimited(
+                    alt((tag("eid_"), tag("skl_"), tag("phy_"))),
+                    (path_id("d"), path_id("b")),
+                    alt((tag(".eid"), tag(".sklb"), tag(".phyb"), tag(".skp"))),
+                ),
             ),
+            |(primary_id, secondary_id, (repeat_primary_id, repeat_secondary_id))| -> Result<GamePath> {
+                check_repeat_id(primary_id, repeat_primary_id)?;
+                check_repeat_id(secondary_id, repeat_secondary_id)?;
+                Ok(GamePath::Demihuman(DemihumanPath::Skeleton {
+                    primary_id,
+                    secondary_id,
+                }))
+            },
         ),
-        |(primary_id, secondary_id, (repeat_primary_id, repeat_secondary_id))| -> Result<GamePath> {
-            check_repeat_id(primary_id, repeat_primary_id)?;
-            check_repeat_id(secondary_id, repeat_secondary_id)?;
-            Ok(GamePath::Demihuman(DemihumanPath::Skeleton {
-                primary_id,
-                secondary_id,
-            }))
-        },
     )
     .parse(input)
 }
@@ -202,13 +277,16 @@ fn chara_demihuman_path_skeleton(input: &str) -> IResult<&str, GamePath<'_>> {
 
 fn avfx_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str, DemihumanPath> {
     move |input: &str| {
-        map(
-            delimited(tag("vfx/eff/"), path_id("ve"), tag(".avfx")),
-            |effect_id| DemihumanPath::Avfx {
-                primary_id,
-                secondary_id,
-                effect_id,
-            },
+        context(
+            "vfx file name",
+            map(
+                delimited(tag("vfx/eff/"), path_id("ve"), tag(".avfx")),
+                |effect_id| DemihumanPath::Avfx {
+                    primary_id,
+                    secondary_id,
+                    effect_id,
+                },
+            ),
         )
         .parse(input)
     }
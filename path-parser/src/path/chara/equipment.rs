@@ -2,17 +2,19 @@ use affects_common::EquipSlot;
 use nom::{
     Parser,
     branch::alt,
-    bytes::complete::{tag, take_till},
+    bytes::complete::take_till,
     combinator::{map, map_res},
+    error::context,
     sequence::{delimited, preceded, terminated},
 };
 
 use crate::{
-    GamePath, IResult, ModelInfo, Result, check_repeat_id, equip_slot, n_digit_id, path_id,
+    GamePath, IResult, ModelInfo, Result, check_repeat_id, equip_slot, n_digit_id, path_id, tag,
     types::model_info,
 };
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum EquipmentPath {
     Imc(u16),
     Mtrl {
@@ -38,6 +40,49 @@ pub enum EquipmentPath {
     },
 }
 
+impl std::fmt::Display for EquipmentPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Imc(id) => write!(f, "chara/equipment/e{id:<04}/e{id:<04}.imc"),
+            Self::Mtrl {
+                primary_id,
+                variant_id,
+                model_info,
+                slot,
+            } => write!(
+                f,
+                "chara/equipment/e{primary_id:<04}/material/v{variant_id:<04}/mt_c{info:<04}e{primary_id:<04}_{slot}_a.mtrl",
+                info = model_info.path_raw_id(),
+                slot = slot.as_str(),
+            ),
+            Self::Mdl { id, info, slot } => write!(
+                f,
+                "chara/equipment/e{id:<04}/model/c{info:<04}e{id:<04}_{slot}.mdl",
+                info = info.path_raw_id(),
+                slot = slot.as_str(),
+            ),
+            Self::Tex {
+                primary_id,
+                variant_id,
+                model_info,
+                slot,
+            } => write!(
+                f,
+                "chara/equipment/e{primary_id:<04}/texture/v{variant_id:<02}_c{info:<04}e{primary_id:<04}_{slot}_norm.tex",
+                info = model_info.path_raw_id(),
+                slot = slot.as_str(),
+            ),
+            Self::Avfx {
+                primary_id,
+                effect_id,
+            } => write!(
+                f,
+                "chara/equipment/e{primary_id:<04}/vfx/eff/ve{effect_id:<04}.avfx"
+            ),
+        }
+    }
+}
+
 // util
 
 fn file_repeat(input: &str) -> IResult<&str, (ModelInfo, u16)> {
@@ -67,12 +112,15 @@ pub(crate) fn chara_equipment_path(input: &str) -> IResult<&str, GamePath<'_>> {
 
 fn imc_path(primary_id: u16) -> impl Fn(&str) -> IResult<&str, EquipmentPath> {
     move |input: &str| {
-        map_res(
-            terminated(path_id("e"), tag(".imc")),
-            |repeat_id| -> Result<EquipmentPath> {
-                check_repeat_id(primary_id, repeat_id)?;
-                Ok(EquipmentPath::Imc(primary_id))
-            },
+        context(
+            "imc file name",
+            map_res(
+                terminated(path_id("e"), tag(".imc")),
+                |repeat_id| -> Result<EquipmentPath> {
+                    check_repeat_id(primary_id, repeat_id)?;
+                    Ok(EquipmentPath::Imc(primary_id))
+                },
+            ),
         )
         .parse(input)
     }
@@ -82,19 +130,22 @@ fn imc_path(primary_id: u16) -> impl Fn(&str) -> IResult<&str, EquipmentPath> {
 
 fn mdl_path(primary_id: u16) -> impl Fn(&str) -> IResult<&str, EquipmentPath> {
     move |input: &str| {
-        map_res(
-            (
-                delimited(tag("model/"), file_repeat, tag("_")),
-                terminated(equip_slot, tag(".mdl")),
+        context(
+            "model file name",
+            map_res(
+                (
+                    delimited(tag("model/"), file_repeat, tag("_")),
+                    terminated(equip_slot, tag(".mdl")),
+                ),
+                |((info, repeat_id), slot)| -> Result<EquipmentPath> {
+                    check_repeat_id(primary_id, repeat_id)?;
+                    Ok(EquipmentPath::Mdl {
+                        id: primary_id,
+                        info,
+                        slot,
+                    })
+                },
             ),
-            |((info, repeat_id), slot)| -> Result<EquipmentPath> {
-                check_repeat_id(primary_id, repeat_id)?;
-                Ok(EquipmentPath::Mdl {
-                    id: primary_id,
-                    info,
-                    slot,
-                })
-            },
         )
         .parse(input)
     }
@@ -104,17 +155,20 @@ fn mdl_path(primary_id: u16) -> impl Fn(&str) -> IResult<&str, EquipmentPath> {
 
 fn mtrl_path(primary_id: u16) -> impl Fn(&str) -> IResult<&str, EquipmentPath> {
     move |input: &str| {
-        map_res(
-            (terminated(mtrl_variant, tag("/")), mtrl_simple_file_name),
-            |(variant_id, (info, e_id, slot))| -> Result<EquipmentPath> {
-                check_repeat_id(primary_id, e_id)?;
-                Ok(EquipmentPath::Mtrl {
-                    primary_id,
-                    variant_id,
-                    model_info: info,
-                    slot,
-                })
-            },
+        context(
+            "material file name",
+            map_res(
+                (terminated(mtrl_variant, tag("/")), mtrl_simple_file_name),
+                |(variant_id, (info, e_id, slot))| -> Result<EquipmentPath> {
+                    check_repeat_id(primary_id, e_id)?;
+                    Ok(EquipmentPath::Mtrl {
+                        primary_id,
+                        variant_id,
+                        model_info: info,
+                        slot,
+                    })
+                },
+            ),
         )
         .parse(input)
     }
@@ -143,21 +197,24 @@ fn mtrl_simple_file_name(input: &str) -> IResult<&str, (ModelInfo, u16, EquipSlo
 
 fn tex_path(primary_id: u16) -> impl Fn(&str) -> IResult<&str, EquipmentPath> {
     move |input: &str| {
-        map_res(
-            (
-                delimited(tag("texture/v"), n_digit_id::<u8>(2), tag("_")),
-                terminated(file_repeat, tag("_")),
-                terminated(terminated(equip_slot, take_till(|c| c == '.')), tag(".tex")),
+        context(
+            "texture file name",
+            map_res(
+                (
+                    delimited(tag("texture/v"), n_digit_id::<u8>(2), tag("_")),
+                    terminated(file_repeat, tag("_")),
+                    terminated(terminated(equip_slot, take_till(|c| c == '.')), tag(".tex")),
+                ),
+                |(variant_id, (info, repeat_id), slot)| -> Result<EquipmentPath> {
+                    check_repeat_id(primary_id, repeat_id)?;
+                    Ok(EquipmentPath::Tex {
+                        primary_id,
+                        variant_id,
+                        model_info: info,
+                        slot,
+                    })
+                },
             ),
-            |(variant_id, (info, repeat_id), slot)| -> Result<EquipmentPath> {
-                check_repeat_id(primary_id, repeat_id)?;
-                Ok(EquipmentPath::Tex {
-                    primary_id,
-                    variant_id,
-                    model_info: info,
-                    slot,
-                })
-            },
         )
         .parse(input)
     }
@@ -167,12 +224,15 @@ fn tex_path(primary_id: u16) -> impl Fn(&str) -> IResult<&str, EquipmentPath> {
 
 fn avfx_path(primary_id: u16) -> impl Fn(&str) -> IResult<&str, EquipmentPath> {
     move |input: &str| {
-        map(
-            terminated(path_id("vfx/eff/ve"), tag(".avfx")),
-            |variant_id| EquipmentPath::Avfx {
-                primary_id,
-                effect_id: variant_id,
-            },
+        context(
+            "vfx file name",
+            map(
+                terminated(path_id("vfx/eff/ve"), tag(".avfx")),
+                |variant_id| EquipmentPath::Avfx {
+                    primary_id,
+                    effect_id: variant_id,
+                },
+            ),
         )
         .parse(input)
     }
@@ -257,4 +317,21 @@ mod test {
             }),
         );
     }
+
+    #[test]
+    fn npc_mdl() {
+        const PATH: &str = "chara/equipment/e0864/model/c0103e0864_met.mdl";
+        let path = GamePath::Equipment(EquipmentPath::Mdl {
+            id: 864,
+            info: ModelInfo {
+                race: Some(Race::Midlander),
+                gender: Gender::Male,
+                kind: ModelKind::Unknown(3),
+            },
+            slot: EquipSlot::Head,
+        });
+
+        test_path(PATH, path.clone());
+        assert_eq!(PATH, path.to_string());
+    }
 }
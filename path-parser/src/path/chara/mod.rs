@@ -1,4 +1,4 @@
-use nom::{Parser, branch::alt, bytes::complete::tag, sequence::preceded};
+use nom::{Parser, branch::alt, sequence::preceded};
 
 pub use self::{
     accessory::AccessoryPath,
@@ -15,6 +15,7 @@ use crate::{
         demihuman::chara_demihuman_path, equipment::chara_equipment_path,
         monster::chara_monster_path, weapon::chara_weapon_path,
     },
+    tag,
 };
 
 mod accessory;
@@ -1,14 +1,16 @@
 use nom::{
     Parser,
     branch::alt,
-    bytes::complete::{tag, take_till},
+    bytes::complete::take_till,
     combinator::{map, map_res},
+    error::context,
     sequence::{delimited, terminated},
 };
 
-use crate::{GamePath, IResult, Result, check_repeat_id, n_digit_id, path_id};
+use crate::{GamePath, IResult, Result, check_repeat_id, n_digit_id, path_id, tag};
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum MonsterPath {
     Imc {
         primary_id: u16,
@@ -39,6 +41,58 @@ pub enum MonsterPath {
     },
 }
 
+impl std::fmt::Display for MonsterPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Imc {
+                primary_id,
+                secondary_id,
+            } => write!(
+                f,
+                "chara/monster/m{primary_id:<04}/obj/body/b{secondary_id:<04}/b{secondary_id:<04}.imc"
+            ),
+            Self::Mdl {
+                primary_id,
+                secondary_id,
+            } => write!(
+                f,
+                "chara/monster/m{primary_id:<04}/obj/body/b{secondary_id:<04}/model/m{primary_id:<04}b{secondary_id:<04}.mdl"
+            ),
+            Self::Skeleton {
+                primary_id,
+                secondary_id,
+            } => write!(
+                f,
+                "chara/monster/m{primary_id:<04}/skeleton/base/b{secondary_id:<04}/skl_m{primary_id:<04}b{secondary_id:<04}.sklb"
+            ),
+            Self::Mtrl {
+                primary_id,
+                secondary_id,
+                variant_id,
+            } => write!(
+                f,
+                "chara/monster/m{primary_id:<04}/obj/body/b{secondary_id:<04}/material/v{variant_id:<04}/mt_m{primary_id:<04}b{secondary_id:<04}_a.mtrl"
+            ),
+            Self::Tex {
+                primary_id,
+                secondary_id,
+                variant_id,
+            } => write!(
+                f,
+                "chara/monster/m{primary_id:<04}/obj/body/b{secondary_id:<04}/texture/v{variant_id:<02}_m{primary_id:<04}b{secondary_id:<04}_norm.tex"
+            ),
+            Self::Avfx {
+                primary_id,
+                secondary_id,
+                effect_id,
+            } => write!(
+                f,
+                "chara/monster/m{primary_id:<04}/obj/body/b{secondary_id:<04}/vfx/eff/vm{effect_id:<04}.avfx"
+            ),
+        }
+    }
+}
+
 // util
 
 fn file_repeat(input: &str) -> IResult<&str, (u16, u16)> {
@@ -75,15 +129,18 @@ fn chara_monster_path_normal(input: &str) -> IResult<&str, GamePath<'_>> {
 
 fn imc_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str, MonsterPath> {
     move |input: &str| {
-        map_res(
-            terminated(path_id("b"), tag(".imc")),
-            |repeat_id| -> Result<MonsterPath> {
-                check_repeat_id(secondary_id, repeat_id)?;
-                Ok(MonsterPath::Imc {
-                    primary_id,
-                    secondary_id,
-                })
-            },
+        context(
+            "imc file name",
+            map_res(
+                terminated(path_id("b"), tag(".imc")),
+                |repeat_id| -> Result<MonsterPath> {
+                    check_repeat_id(secondary_id, repeat_id)?;
+                    Ok(MonsterPath::Imc {
+                        primary_id,
+                        secondary_id,
+                    })
+                },
+            ),
         )
         .parse(input)
     }
@@ -93,17 +150,20 @@ fn imc_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str
 
 fn mdl_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str, MonsterPath> {
     move |input: &str| {
-        map_res(
-            delimited(tag("model/"), file_repeat, tag(".mdl")),
-            |(repeat_primary_id, repeat_secondary_id)| -> Result<MonsterPath> {
-                check_repeat_id(primary_id, repeat_primary_id)?;
-                check_repeat_id(secondary_id, repeat_secondary_id)?;
+        context(
+            "model file name",
+            map_res(
+                delimited(tag("model/"), file_repeat, tag(".mdl")),
+                |(repeat_primary_id, repeat_secondary_id)| -> Result<MonsterPath> {
+                    check_repeat_id(primary_id, repeat_primary_id)?;
+                    check_repeat_id(secondary_id, repeat_secondary_id)?;
 
-                Ok(MonsterPath::Mdl {
-                    primary_id,
-                    secondary_id,
-                })
-            },
+                    Ok(MonsterPath::Mdl {
+                        primary_id,
+                        secondary_id,
+                    })
+                },
+            ),
         )
         .parse(input)
     }
@@ -113,24 +173,27 @@ fn mdl_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str
 
 fn mtrl_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str, MonsterPath> {
     move |input: &str| {
-        map_res(
-            (
-                delimited(tag("material/"), path_id("v"), tag("/")),
-                delimited(
-                    tag("mt_"),
-                    file_repeat,
-                    (tag("_"), take_till(|c| c == '.'), tag(".mtrl")),
+        context(
+            "material file name",
+            map_res(
+                (
+                    delimited(tag("material/"), path_id("v"), tag("/")),
+                    delimited(
+                        tag("mt_"),
+                        file_repeat,
+                        (tag("_"), take_till(|c| c == '.'), tag(".mtrl")),
+                    ),
                 ),
+                |(variant_id, (repeat_primary_id, repeat_secondary_id))| -> Result<MonsterPath> {
+                    check_repeat_id(primary_id, repeat_primary_id)?;
+                    check_repeat_id(secondary_id, repeat_secondary_id)?;
+                    Ok(MonsterPath::Mtrl {
+                        primary_id,
+                        secondary_id,
+                        variant_id,
+                    })
+                },
             ),
-            |(variant_id, (repeat_primary_id, repeat_secondary_id))| -> Result<MonsterPath> {
-                check_repeat_id(primary_id, repeat_primary_id)?;
-                check_repeat_id(secondary_id, repeat_secondary_id)?;
-                Ok(MonsterPath::Mtrl {
-                    primary_id,
-                    secondary_id,
-                    variant_id,
-                })
-            },
         )
         .parse(input)
     }
@@ -140,20 +203,23 @@ fn mtrl_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&st
 
 fn tex_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str, MonsterPath> {
     move |input: &str| {
-        map_res(
-            (
-                delimited(tag("texture/v"), n_digit_id::<u8>(2), tag("_")),
-                terminated(file_repeat, (take_till(|c| c == '.'), tag(".tex"))),
+        context(
+            "texture file name",
+            map_res(
+                (
+                    delimited(tag("texture/v"), n_digit_id::<u8>(2), tag("_")),
+                    terminated(file_repeat, (take_till(|c| c == '.'), tag(".tex"))),
+                ),
+                |(variant_id, (repeat_primary_id, repeat_secondary_id))| -> Result<MonsterPath> {
+                    check_repeat_id(primary_id, repeat_primary_id)?;
+                    check_repeat_id(secondary_id, repeat_secondary_id)?;
+                    Ok(MonsterPath::Tex {
+                        primary_id,
+                        secondary_id,
+                        variant_id,
+                    })
+                },
             ),
-            |(variant_id, (repeat_primary_id, repeat_secondary_id))| -> Result<MonsterPath> {
-                check_repeat_id(primary_id, repeat_primary_id)?;
-                check_repeat_id(secondary_id, repeat_secondary_id)?;
-                Ok(MonsterPath::Tex {
-                    primary_id,
-                    secondary_id,
-                    variant_id,
-                })
-            },
         )
         .parse(input)
     }
@@ -162,24 +228,27 @@ fn tex_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str
 // chara/monster/.../skeleton
 
 fn chara_monster_path_skeleton(input: &str) -> IResult<&str, GamePath<'_>> {
-    map_res(
-        (
-            delimited(tag("monster/"), path_id("m"), tag("/")),
-            delimited(tag("skeleton/base/"), path_id("b"), tag("/")),
-            delimited(
-                alt((tag("eid_"), tag("skl_"), tag("phy_"))),
-                file_repeat,
-                alt((tag(".eid"), tag(".sklb"), tag(".phyb"), tag(".skp"))),
+    context(
+        "skeleton file name",
+        map_res(
+            (
+                delimited(tag("monster/"), path_id("m"), tag("/")),
+                delimited(tag("skeleton/base/"), path_id("b"), tag("/")),
+                delimited(
+                    alt((tag("eid_"), tag("skl_"), tag("phy_"))),
+                    file_repeat,
+                    alt((tag(".eid"), tag(".sklb"), tag(".phyb"), tag(".skp"))),
+                ),
             ),
+            |(primary_id, secondary_id, (repeat_primary_id, repeat_secondary_id))| -> Result<GamePath> {
+                check_repeat_id(primary_id, repeat_primary_id)?;
+                check_repeat_id(secondary_id, repeat_secondary_id)?;
+                crate::Result::Ok(GamePath::Monster(MonsterPath::Skeleton {
+                    primary_id,
+                    secondary_id,
+                }))
+            },
         ),
-        |(primary_id, secondary_id, (repeat_primary_id, repeat_secondary_id))| -> Result<GamePath> {
-            check_repeat_id(primary_id, repeat_primary_id)?;
-            check_repeat_id(secondary_id, repeat_secondary_id)?;
-            crate::Result::Ok(GamePath::Monster(MonsterPath::Skeleton {
-                primary_id,
-                secondary_id,
-            }))
-        },
     )
     .parse(input)
 }
@@ -188,13 +257,16 @@ fn chara_monster_path_skeleton(input: &str) -> IResult<&str, GamePath<'_>> {
 
 fn avfx_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str, MonsterPath> {
     move |input: &str| {
-        map(
-            delimited(tag("vfx/eff/"), path_id("vm"), tag(".avfx")),
-            |effect_id| MonsterPath::Avfx {
-                primary_id,
-                secondary_id,
-                effect_id,
-            },
+        context(
+            "vfx file name",
+            map(
+                delimited(tag("vfx/eff/"), path_id("vm"), tag(".avfx")),
+                |effect_id| MonsterPath::Avfx {
+                    primary_id,
+                    secondary_id,
+                    effect_id,
+                },
+            ),
         )
         .parse(input)
     }
@@ -1,14 +1,16 @@
 use nom::{
     Parser,
     branch::alt,
-    bytes::complete::{tag, take_till},
+    bytes::complete::take_till,
     combinator::{map, map_res},
+    error::context,
     sequence::{delimited, preceded, terminated},
 };
 
-use crate::{GamePath, IResult, Result, check_repeat_id, n_digit_id, path_id};
+use crate::{GamePath, IResult, Result, check_repeat_id, n_digit_id, path_id, tag};
 
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum WeaponPath {
     Imc {
         primary_id: u16,
@@ -39,6 +41,58 @@ pub enum WeaponPath {
     },
 }
 
+impl std::fmt::Display for WeaponPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match *self {
+            Self::Imc {
+                primary_id,
+                secondary_id,
+            } => write!(
+                f,
+                "chara/weapon/w{primary_id:<04}/obj/body/b{secondary_id:<04}/b{secondary_id:<04}.imc"
+            ),
+            Self::Mdl {
+                primary_id,
+                secondary_id,
+            } => write!(
+                f,
+                "chara/weapon/w{primary_id:<04}/obj/body/b{secondary_id:<04}/model/w{primary_id:<04}b{secondary_id:<04}.mdl"
+            ),
+            Self::Mtrl {
+                primary_id,
+                secondary_id,
+                variant_id,
+            } => write!(
+                f,
+                "chara/weapon/w{primary_id:<04}/obj/body/b{secondary_id:<04}/material/v{variant_id:<04}/mt_w{primary_id:<04}b{secondary_id:<04}_a.mtrl"
+            ),
+            Self::Tex {
+                primary_id,
+                secondary_id,
+                variant_id,
+            } => write!(
+                f,
+                "chara/weapon/w{primary_id:<04}/obj/body/b{secondary_id:<04}/texture/v{variant_id:<02}_w{primary_id:<04}b{secondary_id:<04}_norm.tex"
+            ),
+            Self::Skeleton {
+                primary_id,
+                secondary_id,
+            } => write!(
+                f,
+                "chara/weapon/w{primary_id:<04}/skeleton/base/b{secondary_id:<04}/skl_w{primary_id:<04}b{secondary_id:<04}.sklb"
+            ),
+            Self::Avfx {
+                primary_id,
+                secondary_id,
+                effect_id,
+            } => write!(
+                f,
+                "chara/weapon/w{primary_id:<04}/obj/body/b{secondary_id:<04}/vfx/eff/vw{effect_id:<04}.avfx"
+            ),
+        }
+    }
+}
+
 // util
 
 fn file_repeat(input: &str) -> IResult<&str, (u16, u16)> {
@@ -75,15 +129,18 @@ fn chara_weapon_path_simple(input: &str) -> IResult<&str, GamePath<'_>> {
 
 fn imc_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str, WeaponPath> {
     move |input: &str| {
-        map_res(
-            terminated(path_id("b"), tag(".imc")),
-            |repeat_secondary_id| -> Result<WeaponPath> {
-                check_repeat_id(secondary_id, repeat_secondary_id)?;
-                Ok(WeaponPath::Imc {
-                    primary_id,
-                    secondary_id,
-                })
-            },
+        context(
+            "imc file name",
+            map_res(
+                terminated(path_id("b"), tag(".imc")),
+                |repeat_secondary_id| -> Result<WeaponPath> {
+                    check_repeat_id(secondary_id, repeat_secondary_id)?;
+                    Ok(WeaponPath::Imc {
+                        primary_id,
+                        secondary_id,
+                    })
+                },
+            ),
         )
         .parse(input)
     }
@@ -93,16 +150,19 @@ fn imc_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str
 
 fn mdl_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str, WeaponPath> {
     move |input: &str| {
-        map_res(
-            delimited(tag("model/"), file_repeat, tag(".mdl")),
-            |(repeat_primary_id, repeat_secondary_id)| -> Result<WeaponPath> {
-                check_repeat_id(primary_id, repeat_primary_id)?;
-                check_repeat_id(secondary_id, repeat_secondary_id)?;
-                Ok(WeaponPath::Mdl {
-                    primary_id,
-                    secondary_id,
-                })
-            },
+        context(
+            "model file name",
+            map_res(
+                delimited(tag("model/"), file_repeat, tag(".mdl")),
+                |(repeat_primary_id, repeat_secondary_id)| -> Result<WeaponPath> {
+                    check_repeat_id(primary_id, repeat_primary_id)?;
+                    check_repeat_id(secondary_id, repeat_secondary_id)?;
+                    Ok(WeaponPath::Mdl {
+                        primary_id,
+                        secondary_id,
+                    })
+                },
+            ),
         )
         .parse(input)
     }
@@ -112,24 +172,27 @@ fn mdl_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str
 
 fn mtrl_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str, WeaponPath> {
     move |input: &str| {
-        map_res(
-            (
-                delimited(tag("material/"), path_id("v"), tag("/")),
-                delimited(
-                    tag("mt_"),
-                    file_repeat,
-                    (tag("_"), take_till(|c| c == '.'), tag(".mtrl")),
+        context(
+            "material file name",
+            map_res(
+                (
+                    delimited(tag("material/"), path_id("v"), tag("/")),
+                    delimited(
+                        tag("mt_"),
+                        file_repeat,
+                        (tag("_"), take_till(|c| c == '.'), tag(".mtrl")),
+                    ),
                 ),
+                |(variant_id, (repeat_primary_id, repeat_secondary_id))| -> Result<WeaponPath> {
+                    check_repeat_id(primary_id, repeat_primary_id)?;
+                    check_repeat_id(secondary_id, repeat_secondary_id)?;
+                    Ok(WeaponPath::Mtrl {
+                        primary_id,
+                        secondary_id,
+                        variant_id,
+                    })
+                },
             ),
-            |(variant_id, (repeat_primary_id, repeat_secondary_id))| -> Result<WeaponPath> {
-                check_repeat_id(primary_id, repeat_primary_id)?;
-                check_repeat_id(secondary_id, repeat_secondary_id)?;
-                Ok(WeaponPath::Mtrl {
-                    primary_id,
-                    secondary_id,
-                    variant_id,
-                })
-            },
         )
         .parse(input)
     }
@@ -139,24 +202,27 @@ fn mtrl_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&st
 
 fn tex_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str, WeaponPath> {
     move |input: &str| {
-        map_res(
-            (
-                preceded(tag("texture/v"), n_digit_id::<u8>(2)),
-                delimited(
-                    tag("_"),
-                    file_repeat,
-                    (take_till(|c| c == '.'), tag(".tex")),
+        context(
+            "texture file name",
+            map_res(
+                (
+                    preceded(tag("texture/v"), n_digit_id::<u8>(2)),
+                    delimited(
+                        tag("_"),
+                        file_repeat,
+                        (take_till(|c| c == '.'), tag(".tex")),
+                    ),
                 ),
+                |(variant_id, (repeat_primary_id, repeat_secondary_id))| -> Result<WeaponPath> {
+                    check_repeat_id(primary_id, repeat_primary_id)?;
+                    check_repeat_id(secondary_id, repeat_secondary_id)?;
+                    Ok(WeaponPath::Tex {
+                        primary_id,
+                        secondary_id,
+                        variant_id,
+                    })
+                },
             ),
-            |(variant_id, (repeat_primary_id, repeat_secondary_id))| -> Result<WeaponPath> {
-                check_repeat_id(primary_id, repeat_primary_id)?;
-                check_repeat_id(secondary_id, repeat_secondary_id)?;
-                Ok(WeaponPath::Tex {
-                    primary_id,
-                    secondary_id,
-                    variant_id,
-                })
-            },
         )
         .parse(input)
     }
@@ -165,24 +231,27 @@ fn tex_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str
 // chara/weapon/.../skeleton
 
 fn chara_weapon_path_skeleton(input: &str) -> IResult<&str, GamePath<'_>> {
-    map_res(
-        (
-            delimited(tag("weapon/"), path_id("w"), tag("/")),
-            delimited(tag("skeleton/base/"), path_id("b"), tag("/")),
-            delimited(
-                alt((tag("eid_"), tag("skl_"), tag("phy_"))),
-                file_repeat,
-                alt((tag(".eid"), tag(".sklb"), tag(".phyb"), tag(".skp"))),
+    context(
+        "skeleton file name",
+        map_res(
+            (
+                delimited(tag("weapon/"), path_id("w"), tag("/")),
+                delimited(tag("skeleton/base/"), path_id("b"), tag("/")),
+                delimited(
+                    alt((tag("eid_"), tag("skl_"), tag("phy_"))),
+                    file_repeat,
+                    alt((tag(".eid"), tag(".sklb"), tag(".phyb"), tag(".skp"))),
+                ),
             ),
+            |(primary_id, secondary_id, (repeat_primary_id, repeat_secondary_id))| -> Result<GamePath> {
+                check_repeat_id(primary_id, repeat_primary_id)?;
+                check_repeat_id(secondary_id, repeat_secondary_id)?;
+                Ok(GamePath::Weapon(WeaponPath::Skeleton {
+                    primary_id,
+                    secondary_id,
+                }))
+            },
         ),
-        |(primary_id, secondary_id, (repeat_primary_id, repeat_secondary_id))| -> Result<GamePath> {
-            check_repeat_id(primary_id, repeat_primary_id)?;
-            check_repeat_id(secondary_id, repeat_secondary_id)?;
-            Ok(GamePath::Weapon(WeaponPath::Skeleton {
-                primary_id,
-                secondary_id,
-            }))
-        },
     )
     .parse(input)
 }
@@ -191,13 +260,16 @@ fn chara_weapon_path_skeleton(input: &str) -> IResult<&str, GamePath<'_>> {
 
 fn avfx_path(primary_id: u16, secondary_id: u16) -> impl Fn(&str) -> IResult<&str, WeaponPath> {
     move |input: &str| {
-        map(
-            delimited(tag("vfx/eff/"), path_id("vw"), tag(".avfx")),
-            |effect_id| WeaponPath::Avfx {
-                primary_id,
-                secondary_id,
-                effect_id,
-            },
+        context(
+            "vfx file name",
+            map(
+                delimited(tag("vfx/eff/"), path_id("vw"), tag(".avfx")),
+                |effect_id| WeaponPath::Avfx {
+                    primary_id,
+                    secondary_id,
+                    effect_id,
+                },
+            ),
         )
         .parse(input)
     }
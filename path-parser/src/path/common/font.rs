@@ -1,12 +1,12 @@
 use nom::{
     Parser,
     branch::alt,
-    bytes::complete::{tag, take_till, take_while},
+    bytes::complete::{take_till, take_while},
     combinator::{map, map_res, opt},
     sequence::{preceded, separated_pair, terminated},
 };
 
-use crate::{GamePath, IResult};
+use crate::{GamePath, IResult, tag};
 
 // common/font
 
@@ -15,16 +15,20 @@ pub(crate) fn common_font_path(input: &str) -> IResult<&str, GamePath<'_>> {
 }
 
 fn common_font_tex(input: &str) -> IResult<&str, GamePath<'_>> {
-    map(terminated(take_till(|c| c == '.'), tag(".tex")), |part| {
-        GamePath::FontTexture(part)
-    })
+    map(
+        terminated(take_till(|c| c == '.'), tag(".tex")),
+        |part: &str| GamePath::FontTexture(part.into()),
+    )
     .parse(input)
 }
 
 fn common_font_fdt(input: &str) -> IResult<&str, GamePath<'_>> {
     map(
         terminated(common_font_fdt_name, tag(".fdt")),
-        |(name, size)| GamePath::FontFile { family: name, size },
+        |(name, size)| GamePath::FontFile {
+            family: name.into(),
+            size,
+        },
     )
     .parse(input)
 }
@@ -51,7 +55,7 @@ mod test {
     pub fn tex() {
         const PATH: &str = "common/font/fontIcon_Ps5.tex";
 
-        test_path(PATH, GamePath::FontTexture("fontIcon_Ps5"));
+        test_path(PATH, GamePath::FontTexture("fontIcon_Ps5".into()));
     }
 
     #[test]
@@ -61,7 +65,7 @@ mod test {
         test_path(
             PATH,
             GamePath::FontFile {
-                family: "AXIS",
+                family: "AXIS".into(),
                 size: 12,
             },
         );
@@ -74,7 +78,7 @@ mod test {
         test_path(
             PATH,
             GamePath::FontFile {
-                family: "Meidinger",
+                family: "Meidinger".into(),
                 size: 16,
             },
         );
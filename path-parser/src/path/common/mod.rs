@@ -1,8 +1,8 @@
 mod font;
 
-use nom::{Parser, bytes::complete::tag, sequence::preceded};
+use nom::{Parser, sequence::preceded};
 
-use crate::{GamePath, IResult, path::common::font::common_font_path};
+use crate::{GamePath, IResult, path::common::font::common_font_path, tag};
 
 pub(crate) fn common_path(input: &str) -> IResult<&str, GamePath<'_>> {
     preceded(tag("common/"), common_font_path).parse(input)
@@ -1,5 +1,11 @@
+pub mod bg;
 pub mod chara;
 pub mod common;
+pub mod sound;
 pub mod ui;
+pub mod vfx;
 
-pub(crate) use self::{chara::chara_path, common::common_path, ui::ui_path};
+pub(crate) use self::{
+    bg::bg_path, chara::chara_path, common::common_path, sound::sound_path, ui::ui_path,
+    vfx::vfx_path,
+};
@@ -0,0 +1,180 @@
+use std::borrow::Cow;
+
+use nom::{
+    Parser,
+    branch::alt,
+    bytes::complete::take_till,
+    combinator::map,
+    error::context,
+    sequence::{preceded, terminated},
+};
+
+use crate::{GamePath, IResult, raw_part, tag};
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SoundPath<'a> {
+    Music {
+        expansion: Cow<'a, str>,
+        file: Cow<'a, str>,
+    },
+    Battle(Cow<'a, str>),
+    EmoteVoice(Cow<'a, str>),
+    Footstep(Cow<'a, str>),
+    Vfx(Cow<'a, str>),
+    Other {
+        category: Cow<'a, str>,
+        file: Cow<'a, str>,
+    },
+}
+
+impl SoundPath<'_> {
+    pub fn into_owned(self) -> SoundPath<'static> {
+        match self {
+            Self::Music { expansion, file } => SoundPath::Music {
+                expansion: Cow::Owned(expansion.into_owned()),
+                file: Cow::Owned(file.into_owned()),
+            },
+            Self::Battle(file) => SoundPath::Battle(Cow::Owned(file.into_owned())),
+            Self::EmoteVoice(file) => SoundPath::EmoteVoice(Cow::Owned(file.into_owned())),
+            Self::Footstep(file) => SoundPath::Footstep(Cow::Owned(file.into_owned())),
+            Self::Vfx(file) => SoundPath::Vfx(Cow::Owned(file.into_owned())),
+            Self::Other { category, file } => SoundPath::Other {
+                category: Cow::Owned(category.into_owned()),
+                file: Cow::Owned(file.into_owned()),
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for SoundPath<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Music { expansion, file } => write!(f, "music/{expansion}/{file}.scd"),
+            Self::Battle(file) => write!(f, "sound/battle/{file}.scd"),
+            Self::EmoteVoice(file) => write!(f, "sound/voice/vo_emote/{file}.scd"),
+            Self::Footstep(file) => write!(f, "sound/foot/{file}.scd"),
+            Self::Vfx(file) => write!(f, "sound/vfx/{file}.scd"),
+            Self::Other { category, file } => write!(f, "sound/{category}/{file}.scd"),
+        }
+    }
+}
+
+// util
+
+fn file_name(input: &str) -> IResult<&str, &str> {
+    context(
+        "sound file name",
+        terminated(take_till(|c| c == '.'), tag(".scd")),
+    )
+    .parse(input)
+}
+
+// music and sound
+
+pub(crate) fn sound_path(input: &str) -> IResult<&str, GamePath<'_>> {
+    map(alt((music_path, sound_category_path)), GamePath::Sound).parse(input)
+}
+
+// music/{expansion}
+
+fn music_path(input: &str) -> IResult<&str, SoundPath<'_>> {
+    map(
+        preceded(tag("music/"), (terminated(raw_part, tag("/")), file_name)),
+        |(expansion, file)| SoundPath::Music {
+            expansion: expansion.into(),
+            file: file.into(),
+        },
+    )
+    .parse(input)
+}
+
+// sound/{category}
+
+fn sound_category_path(input: &str) -> IResult<&str, SoundPath<'_>> {
+    preceded(
+        tag("sound/"),
+        alt((
+            map(preceded(tag("battle/"), file_name), |file| {
+                SoundPath::Battle(file.into())
+            }),
+            map(preceded(tag("voice/vo_emote/"), file_name), |file| {
+                SoundPath::EmoteVoice(file.into())
+            }),
+            map(preceded(tag("foot/"), file_name), |file| {
+                SoundPath::Footstep(file.into())
+            }),
+            map(preceded(tag("vfx/"), file_name), |file| {
+                SoundPath::Vfx(file.into())
+            }),
+            map(
+                (terminated(raw_part, tag("/")), file_name),
+                |(category, file)| SoundPath::Other {
+                    category: category.into(),
+                    file: file.into(),
+                },
+            ),
+        )),
+    )
+    .parse(input)
+}
+
+#[cfg(test)]
+mod test {
+    use super::SoundPath;
+    use crate::{GamePath, test::test_path};
+
+    #[test]
+    fn music() {
+        const PATH: &str = "music/ffxiv/BGM_Field_Gri_01.scd";
+        test_path(
+            PATH,
+            GamePath::Sound(SoundPath::Music {
+                expansion: "ffxiv".into(),
+                file: "BGM_Field_Gri_01".into(),
+            }),
+        );
+    }
+
+    #[test]
+    fn battle() {
+        const PATH: &str = "sound/battle/mon/7001.scd";
+        test_path(PATH, GamePath::Sound(SoundPath::Battle("mon/7001".into())));
+    }
+
+    #[test]
+    fn emote_voice() {
+        const PATH: &str = "sound/voice/vo_emote/1001001.scd";
+        test_path(
+            PATH,
+            GamePath::Sound(SoundPath::EmoteVoice("1001001".into())),
+        );
+    }
+
+    #[test]
+    fn footstep() {
+        const PATH: &str = "sound/foot/dirt.scd";
+        test_path(PATH, GamePath::Sound(SoundPath::Footstep("dirt".into())));
+    }
+
+    #[test]
+    fn vfx() {
+        const PATH: &str = "sound/vfx/ability/se_vfx_abi_swd001c1t.scd";
+        test_path(
+            PATH,
+            GamePath::Sound(SoundPath::Vfx("ability/se_vfx_abi_swd001c1t".into())),
+        );
+    }
+
+    #[test]
+    fn other() {
+        const PATH: &str = "sound/system/se_ui.scd";
+        test_path(
+            PATH,
+            GamePath::Sound(SoundPath::Other {
+                category: "system".into(),
+                file: "se_ui".into(),
+            }),
+        );
+    }
+}
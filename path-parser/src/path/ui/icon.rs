@@ -1,12 +1,11 @@
 use nom::{
     Parser,
-    bytes::complete::tag,
     character::complete::u64,
     combinator::{map, opt},
     sequence::{delimited, terminated},
 };
 
-use crate::{GamePath, IResult, Language, simple_part_enum};
+use crate::{GamePath, IResult, Language, simple_part_enum, tag};
 
 pub(crate) fn ui_icon_path(input: &str) -> IResult<&str, GamePath<'_>> {
     map(
@@ -1,12 +1,12 @@
 use nom::{
     Parser,
-    bytes::complete::{tag, take},
+    bytes::complete::take,
     character::complete::one_of,
     combinator::{map, opt},
     sequence::{delimited, preceded, terminated},
 };
 
-use crate::{GamePath, IResult, n_digit_id};
+use crate::{GamePath, IResult, n_digit_id, tag};
 
 const ALPHA: &str = "abcdefghijklmnopqrstuvwxyz";
 
@@ -19,13 +19,13 @@ pub(crate) fn ui_map_path(input: &str) -> IResult<&str, GamePath<'_>> {
     map(
         (
             preceded(
-                (tag(primary_id), tag(&*format!("{variant:<02}"))),
+                (tag(primary_id), tag(&format!("{variant:<02}"))),
                 opt(one_of(ALPHA)),
             ), // FIXME
             terminated(opt(preceded(tag("_"), one_of(ALPHA))), tag(".tex")),
         ),
         |(suffix, extra)| GamePath::Map {
-            primary_id,
+            primary_id: primary_id.into(),
             variant,
             suffix,
             extra,
@@ -44,7 +44,7 @@ mod test {
         test_path(
             PATH,
             GamePath::Map {
-                primary_id: "z6r1",
+                primary_id: "z6r1".into(),
                 variant: 2,
                 suffix: None,
                 extra: None,
@@ -58,7 +58,7 @@ mod test {
         test_path(
             PATH,
             GamePath::Map {
-                primary_id: "z6r1",
+                primary_id: "z6r1".into(),
                 variant: 2,
                 suffix: Some('d'),
                 extra: None,
@@ -72,7 +72,7 @@ mod test {
         test_path(
             PATH,
             GamePath::Map {
-                primary_id: "z6r1",
+                primary_id: "z6r1".into(),
                 variant: 2,
                 suffix: None,
                 extra: Some('m'),
@@ -86,7 +86,7 @@ mod test {
         test_path(
             PATH,
             GamePath::Map {
-                primary_id: "z6r1",
+                primary_id: "z6r1".into(),
                 variant: 2,
                 suffix: Some('d'),
                 extra: Some('m'),
@@ -1,6 +1,6 @@
-use nom::{Parser, branch::alt, bytes::complete::tag, sequence::preceded};
+use nom::{Parser, branch::alt, sequence::preceded};
 
-use crate::{GamePath, IResult};
+use crate::{GamePath, IResult, tag};
 
 mod icon;
 mod map;
@@ -0,0 +1,368 @@
+use std::borrow::Cow;
+
+use nom::{
+    Parser,
+    branch::alt,
+    bytes::complete::take_till,
+    combinator::map,
+    error::context,
+    sequence::{delimited, preceded, separated_pair, terminated},
+};
+
+use crate::{GamePath, IResult, path_id, raw_part, simple_part_enum, tag};
+
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum VfxPath<'a> {
+    Common {
+        file: Cow<'a, str>,
+        kind: VfxFileKind,
+    },
+    Monster {
+        primary_id: u16,
+        file: Cow<'a, str>,
+        kind: VfxFileKind,
+    },
+    Weapon {
+        primary_id: u16,
+        file: Cow<'a, str>,
+        kind: VfxFileKind,
+    },
+    Mount {
+        file: Cow<'a, str>,
+        kind: VfxFileKind,
+    },
+    Omen {
+        file: Cow<'a, str>,
+        kind: VfxFileKind,
+    },
+    Action {
+        key: Cow<'a, str>,
+        file: Cow<'a, str>,
+        kind: VfxFileKind,
+    },
+    Other {
+        category: Cow<'a, str>,
+        file: Cow<'a, str>,
+        kind: VfxFileKind,
+    },
+}
+
+enum_str! {
+    pub enum VfxFileKind {
+        Avfx => "avfx",
+        Atex => "atex",
+    }
+}
+
+impl VfxPath<'_> {
+    /// the path after the category directory, without the file extension
+    pub fn file(&self) -> &str {
+        match self {
+            Self::Common { file, .. }
+            | Self::Monster { file, .. }
+            | Self::Weapon { file, .. }
+            | Self::Mount { file, .. }
+            | Self::Omen { file, .. }
+            | Self::Action { file, .. }
+            | Self::Other { file, .. } => file,
+        }
+    }
+
+    /// the file name without any directories or extension. this is what the
+    /// game's VFX sheet refers to effects by
+    pub fn file_stem(&self) -> &str {
+        let file = self.file();
+        file.rsplit('/').next().unwrap_or(file)
+    }
+
+    pub fn kind(&self) -> VfxFileKind {
+        match *self {
+            Self::Common { kind, .. }
+            | Self::Monster { kind, .. }
+            | Self::Weapon { kind, .. }
+            | Self::Mount { kind, .. }
+            | Self::Omen { kind, .. }
+            | Self::Action { kind, .. }
+            | Self::Other { kind, .. } => kind,
+        }
+    }
+
+    pub fn into_owned(self) -> VfxPath<'static> {
+        let owned = |s: Cow<'_, str>| Cow::Owned(s.into_owned());
+        match self {
+            Self::Common { file, kind } => VfxPath::Common {
+                file: owned(file),
+                kind,
+            },
+            Self::Monster {
+                primary_id,
+                file,
+                kind,
+            } => VfxPath::Monster {
+                primary_id,
+                file: owned(file),
+                kind,
+            },
+            Self::Weapon {
+                primary_id,
+                file,
+                kind,
+            } => VfxPath::Weapon {
+                primary_id,
+                file: owned(file),
+                kind,
+            },
+            Self::Mount { file, kind } => VfxPath::Mount {
+                file: owned(file),
+                kind,
+            },
+            Self::Omen { file, kind } => VfxPath::Omen {
+                file: owned(file),
+                kind,
+            },
+            Self::Action { key, file, kind } => VfxPath::Action {
+                key: owned(key),
+                file: owned(file),
+                kind,
+            },
+            Self::Other {
+                category,
+                file,
+                kind,
+            } => VfxPath::Other {
+                category: owned(category),
+                file: owned(file),
+                kind,
+            },
+        }
+    }
+}
+
+impl std::fmt::Display for VfxPath<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "vfx/")?;
+        match self {
+            Self::Common { .. } => write!(f, "common/")?,
+            Self::Monster { primary_id, .. } => write!(f, "monster/m{primary_id:<04}/")?,
+            Self::Weapon { primary_id, .. } => write!(f, "weapon/w{primary_id:<04}/")?,
+            Self::Mount { .. } => write!(f, "mount/")?,
+            Self::Omen { .. } => write!(f, "omen/")?,
+            Self::Action { key, .. } => write!(f, "action/{key}/")?,
+            Self::Other { category, .. } => write!(f, "{category}/")?,
+        }
+
+        write!(f, "{}.{}", self.file(), self.kind().as_str())
+    }
+}
+
+// util
+
+fn file_name(input: &str) -> IResult<&str, (&str, VfxFileKind)> {
+    context(
+        "file name",
+        separated_pair(
+            take_till(|c| c == '.'),
+            tag("."),
+            simple_part_enum::<VfxFileKind>,
+        ),
+    )
+    .parse(input)
+}
+
+// vfx
+
+pub(crate) fn vfx_path(input: &str) -> IResult<&str, GamePath<'_>> {
+    map(
+        preceded(
+            tag("vfx/"),
+            alt((
+                vfx_simple_path,
+                vfx_monster_path,
+                vfx_weapon_path,
+                vfx_action_path,
+                vfx_other_path,
+            )),
+        ),
+        GamePath::Vfx,
+    )
+    .parse(input)
+}
+
+// vfx/common, vfx/mount and vfx/omen
+
+fn vfx_simple_path(input: &str) -> IResult<&str, VfxPath<'_>> {
+    alt((
+        map(preceded(tag("common/"), file_name), |(file, kind)| {
+            VfxPath::Common {
+                file: file.into(),
+                kind,
+            }
+        }),
+        map(preceded(tag("mount/"), file_name), |(file, kind)| {
+            VfxPath::Mount {
+                file: file.into(),
+                kind,
+            }
+        }),
+        map(preceded(tag("omen/"), file_name), |(file, kind)| {
+            VfxPath::Omen {
+                file: file.into(),
+                kind,
+            }
+        }),
+    ))
+    .parse(input)
+}
+
+// vfx/monster
+
+fn vfx_monster_path(input: &str) -> IResult<&str, VfxPath<'_>> {
+    map(
+        (
+            delimited(tag("monster/"), path_id("m"), tag("/")),
+            file_name,
+        ),
+        |(primary_id, (file, kind))| VfxPath::Monster {
+            primary_id,
+            file: file.into(),
+            kind,
+        },
+    )
+    .parse(input)
+}
+
+// vfx/weapon
+
+fn vfx_weapon_path(input: &str) -> IResult<&str, VfxPath<'_>> {
+    map(
+        (delimited(tag("weapon/"), path_id("w"), tag("/")), file_name),
+        |(primary_id, (file, kind))| VfxPath::Weapon {
+            primary_id,
+            file: file.into(),
+            kind,
+        },
+    )
+    .parse(input)
+}
+
+// vfx/action
+
+fn vfx_action_path(input: &str) -> IResult<&str, VfxPath<'_>> {
+    map(
+        (delimited(tag("action/"), raw_part, tag("/")), file_name),
+        |(key, (file, kind))| VfxPath::Action {
+            key: key.into(),
+            file: file.into(),
+            kind,
+        },
+    )
+    .parse(input)
+}
+
+// vfx/...
+
+fn vfx_other_path(input: &str) -> IResult<&str, VfxPath<'_>> {
+    map(
+        (terminated(raw_part, tag("/")), file_name),
+        |(category, (file, kind))| VfxPath::Other {
+            category: category.into(),
+            file: file.into(),
+            kind,
+        },
+    )
+    .parse(input)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{VfxFileKind, VfxPath};
+    use crate::{GamePath, test::test_path};
+
+    #[test]
+    fn common() {
+        const PATH: &str = "vfx/common/eff/cmat_ligct0c1.avfx";
+        test_path(
+            PATH,
+            GamePath::Vfx(VfxPath::Common {
+                file: "eff/cmat_ligct0c1".into(),
+                kind: VfxFileKind::Avfx,
+            }),
+        );
+    }
+
+    #[test]
+    fn monster() {
+        const PATH: &str = "vfx/monster/m0489/eff/m0489sp05c0m.avfx";
+        test_path(
+            PATH,
+            GamePath::Vfx(VfxPath::Monster {
+                primary_id: 489,
+                file: "eff/m0489sp05c0m".into(),
+                kind: VfxFileKind::Avfx,
+            }),
+        );
+    }
+
+    #[test]
+    fn weapon() {
+        const PATH: &str = "vfx/weapon/w2101/texture/w2101_efc01.atex";
+        test_path(
+            PATH,
+            GamePath::Vfx(VfxPath::Weapon {
+                primary_id: 2101,
+                file: "texture/w2101_efc01".into(),
+                kind: VfxFileKind::Atex,
+            }),
+        );
+    }
+
+    #[test]
+    fn omen() {
+        const PATH: &str = "vfx/omen/eff/general_1bf.avfx";
+        test_path(
+            PATH,
+            GamePath::Vfx(VfxPath::Omen {
+                file: "eff/general_1bf".into(),
+                kind: VfxFileKind::Avfx,
+            }),
+        );
+    }
+
+    #[test]
+    fn action() {
+        const PATH: &str = "vfx/action/ab_swd_abl001/eff/abi_swd001c1t.avfx";
+        test_path(
+            PATH,
+            GamePath::Vfx(VfxPath::Action {
+                key: "ab_swd_abl001".into(),
+                file: "eff/abi_swd001c1t".into(),
+                kind: VfxFileKind::Avfx,
+            }),
+        );
+    }
+
+    #[test]
+    fn other() {
+        const PATH: &str = "vfx/lockon/eff/lockon5_t0h.avfx";
+        test_path(
+            PATH,
+            GamePath::Vfx(VfxPath::Other {
+                category: "lockon".into(),
+                file: "eff/lockon5_t0h".into(),
+                kind: VfxFileKind::Avfx,
+            }),
+        );
+    }
+
+    #[test]
+    fn file_stem() {
+        let path = VfxPath::Action {
+            key: "ab_swd_abl001".into(),
+            file: "eff/abi_swd001c1t".into(),
+            kind: VfxFileKind::Avfx,
+        };
+
+        assert_eq!("abi_swd001c1t", path.file_stem());
+    }
+}
@@ -1,6 +1,6 @@
-use nom_language::error::convert_error;
+use crate::{Error, GamePath, ParseError};
 
-use crate::GamePath;
+mod roundtrip;
 
 pub(crate) fn test_path(path: &str, expected: GamePath) {
     let res = super::game_path(path);
@@ -8,8 +8,7 @@ pub(crate) fn test_path(path: &str, expected: GamePath) {
         Ok(r) => assert_eq!(("", expected), r),
         Err(e) => match e {
             nom::Err::Incomplete(needed) => panic!("needed: {:?}", needed),
-            nom::Err::Error(e) => panic!("{}", convert_error(path, e)),
-            nom::Err::Failure(e) => panic!("{}", convert_error(path, e)),
+            nom::Err::Error(e) | nom::Err::Failure(e) => panic!("{}", e.into_error(path)),
         },
     }
 }
@@ -24,3 +23,103 @@ pub fn test_raw_part_multi() {
     let path = "hello/world";
     assert_eq!(Ok(("/world", "hello")), super::raw_part(path));
 }
+
+#[test]
+pub fn format_canonical() {
+    const PATHS: &[&str] = &[
+        "chara/equipment/e0863/material/v0006/mt_c0101e0863_sho_a.mtrl",
+        "chara/equipment/e0864/model/c0101e0864_met.mdl",
+        "chara/weapon/w2021/obj/body/b0001/model/w2021b0001.mdl",
+        "chara/human/c1701/obj/hair/h0173/model/c1701h0173_hir.mdl",
+        "chara/human/c0101/animation/a0001/bt_common/ability/cnj_white/abl025.pap",
+        "chara/common/texture/decal_equip/-decal_110.tex",
+        "ui/icon/039000/hq/039110.tex",
+        "ui/map/z6r1/02/z6r102d_m.tex",
+    ];
+
+    for &path in PATHS {
+        let (_, parsed) = super::game_path(path).unwrap();
+        assert_eq!(path, parsed.to_string());
+    }
+}
+
+#[test]
+pub fn into_owned() {
+    let path = String::from("chara/human/c0101/animation/a0001/bt_common/resident/idle.pap");
+    let owned = {
+        let (_, parsed) = super::game_path(&path).unwrap();
+        parsed.into_owned()
+    };
+
+    drop(path);
+    assert_eq!(
+        "chara/human/c0101/animation/a0001/bt_common/resident/idle.pap",
+        owned.to_string(),
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+pub fn serde_roundtrip() {
+    use crate::GamePathBuf;
+
+    const PATHS: &[&str] = &[
+        "chara/equipment/e0863/material/v0006/mt_c0101e0863_sho_a.mtrl",
+        "chara/human/c0101/animation/a0001/bt_common/resident/idle.pap",
+        "ui/icon/039000/hq/039110.tex",
+        "ui/map/z6r1/02/z6r102d_m.tex",
+    ];
+
+    for &path in PATHS {
+        let parsed = GamePath::parse(path).unwrap().into_owned();
+        let json = serde_json::to_string(&parsed).unwrap();
+        let deserialized = serde_json::from_str::<GamePathBuf>(&json).unwrap();
+        assert_eq!(parsed, deserialized);
+        assert_eq!(path, deserialized.to_string());
+    }
+}
+
+#[test]
+pub fn error_mismatched_id() {
+    const PATH: &str = "chara/equipment/e0863/material/v0006/mt_c0101e0864_sho_a.mtrl";
+    assert_eq!(
+        Err(Error::Parse(ParseError {
+            offset: 22,
+            recognised: "chara/equipment/e0863/".into(),
+            context: Some("material file name"),
+            expected: Vec::new(),
+            cause: Some(Box::new(Error::MismatchedPathIds {
+                expected: 863,
+                actual: 864,
+            })),
+        })),
+        GamePath::parse(PATH),
+    );
+}
+
+#[test]
+pub fn error_expected() {
+    const PATH: &str = "chara/equipment/e0863/modl/c0101e0863_met.mdl";
+    let Err(Error::Parse(error)) = GamePath::parse(PATH) else {
+        panic!("path should not parse");
+    };
+
+    assert_eq!(22, error.offset);
+    assert_eq!("chara/equipment/e0863/", error.recognised);
+    assert!(error.expected.iter().any(|expected| expected == "model/"));
+    assert!(
+        error
+            .expected
+            .iter()
+            .any(|expected| expected == "material/")
+    );
+}
+
+#[test]
+pub fn error_incomplete() {
+    const PATH: &str = "chara/human/c0101/obj/hair/h0001/model/c0101h0001_hir.mdl.bak";
+    assert_eq!(
+        Err(Error::IncompleteParse { offset: 57 }),
+        GamePath::parse(PATH),
+    );
+}
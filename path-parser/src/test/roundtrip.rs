@@ -0,0 +1,569 @@
+use affects_common::EquipSlot;
+use proptest::{prelude::*, sample::select};
+
+use crate::{
+    GamePath, Language,
+    path::{
+        bg::{BgFileKind, BgPart, BgPath, TerritoryKind},
+        chara::{
+            AccessoryPath, BodyType, BodyTypeSlot, CharacterPath, DecalType, DemihumanPath,
+            EquipmentPath, MonsterPath, WeaponPath,
+        },
+        sound::SoundPath,
+        vfx::{VfxFileKind, VfxPath},
+    },
+    types::{ModelInfo, SkeletonSlot},
+};
+
+const RAW_MODEL_INFOS: &[u16] = &[
+    101, 102, 104, 201, 202, 204, 301, 304, 401, 404, 501, 504, 601, 604, 701, 704, 801, 804, 901,
+    904, 1001, 1004, 1101, 1104, 1201, 1204, 1301, 1304, 1401, 1404, 1501, 1504, 1601, 1604, 1701,
+    1704, 1801, 1804, 9104, 9204,
+];
+
+const EQUIP_SLOTS: &[EquipSlot] = &[
+    EquipSlot::Head,
+    EquipSlot::Hands,
+    EquipSlot::Legs,
+    EquipSlot::Feet,
+    EquipSlot::Body,
+    EquipSlot::Ears,
+    EquipSlot::Neck,
+    EquipSlot::RFinger,
+    EquipSlot::LFinger,
+    EquipSlot::Wrists,
+];
+
+const BODY_TYPES: &[BodyType] = &[
+    BodyType::Body,
+    BodyType::Ear,
+    BodyType::Face,
+    BodyType::Hair,
+    BodyType::Tail,
+];
+
+const BODY_TYPE_SLOTS: &[BodyTypeSlot] = &[
+    BodyTypeSlot::Ear,
+    BodyTypeSlot::Face,
+    BodyTypeSlot::Hair,
+    BodyTypeSlot::Tail,
+    BodyTypeSlot::Iris,
+    BodyTypeSlot::Accessory,
+    BodyTypeSlot::Etc,
+    BodyTypeSlot::Head,
+    BodyTypeSlot::Hands,
+    BodyTypeSlot::Legs,
+    BodyTypeSlot::Feet,
+    BodyTypeSlot::Body,
+    BodyTypeSlot::Ears,
+    BodyTypeSlot::Neck,
+    BodyTypeSlot::RFinger,
+    BodyTypeSlot::LFinger,
+    BodyTypeSlot::Wrists,
+];
+
+const SKELETON_SLOTS: &[SkeletonSlot] = &[
+    SkeletonSlot::Head,
+    SkeletonSlot::Hands,
+    SkeletonSlot::Legs,
+    SkeletonSlot::Feet,
+    SkeletonSlot::Body,
+    SkeletonSlot::Ears,
+    SkeletonSlot::Neck,
+    SkeletonSlot::RFinger,
+    SkeletonSlot::LFinger,
+    SkeletonSlot::Wrists,
+    SkeletonSlot::Base,
+    SkeletonSlot::Face,
+    SkeletonSlot::Hair,
+];
+
+const LANGUAGES: &[Language] = &[
+    Language::English,
+    Language::Japanese,
+    Language::German,
+    Language::French,
+];
+
+fn id() -> impl Strategy<Value = u16> {
+    0..10_000_u16
+}
+
+fn variant() -> impl Strategy<Value = u8> {
+    0..100_u8
+}
+
+fn model_info() -> impl Strategy<Value = ModelInfo> {
+    select(RAW_MODEL_INFOS).prop_map(|id| ModelInfo::try_from(id).unwrap())
+}
+
+fn equip_slot() -> impl Strategy<Value = EquipSlot> {
+    select(EQUIP_SLOTS)
+}
+
+fn monster_path() -> impl Strategy<Value = MonsterPath> {
+    prop_oneof![
+        (id(), id()).prop_map(|(primary_id, secondary_id)| MonsterPath::Imc {
+            primary_id,
+            secondary_id,
+        }),
+        (id(), id()).prop_map(|(primary_id, secondary_id)| MonsterPath::Mdl {
+            primary_id,
+            secondary_id,
+        }),
+        (id(), id()).prop_map(|(primary_id, secondary_id)| MonsterPath::Skeleton {
+            primary_id,
+            secondary_id,
+        }),
+        (id(), id(), id()).prop_map(|(primary_id, secondary_id, variant_id)| {
+            MonsterPath::Mtrl {
+                primary_id,
+                secondary_id,
+                variant_id,
+            }
+        }),
+        (id(), id(), variant()).prop_map(|(primary_id, secondary_id, variant_id)| {
+            MonsterPath::Tex {
+                primary_id,
+                secondary_id,
+                variant_id,
+            }
+        }),
+        (id(), id(), id()).prop_map(|(primary_id, secondary_id, effect_id)| {
+            MonsterPath::Avfx {
+                primary_id,
+                secondary_id,
+                effect_id,
+            }
+        }),
+    ]
+}
+
+fn weapon_path() -> impl Strategy<Value = WeaponPath> {
+    prop_oneof![
+        (id(), id()).prop_map(|(primary_id, secondary_id)| WeaponPath::Imc {
+            primary_id,
+            secondary_id,
+        }),
+        (id(), id()).prop_map(|(primary_id, secondary_id)| WeaponPath::Mdl {
+            primary_id,
+            secondary_id,
+        }),
+        (id(), id(), id()).prop_map(|(primary_id, secondary_id, variant_id)| {
+            WeaponPath::Mtrl {
+                primary_id,
+                secondary_id,
+                variant_id,
+            }
+        }),
+        (id(), id(), variant()).prop_map(|(primary_id, secondary_id, variant_id)| {
+            WeaponPath::Tex {
+                primary_id,
+                secondary_id,
+                variant_id,
+            }
+        }),
+        (id(), id()).prop_map(|(primary_id, secondary_id)| WeaponPath::Skeleton {
+            primary_id,
+            secondary_id,
+        }),
+        (id(), id(), id()).prop_map(|(primary_id, secondary_id, effect_id)| {
+            WeaponPath::Avfx {
+                primary_id,
+                secondary_id,
+                effect_id,
+            }
+        }),
+    ]
+}
+
+fn demihuman_path() -> impl Strategy<Value = DemihumanPath> {
+    prop_oneof![
+        (id(), id()).prop_map(|(primary_id, secondary_id)| DemihumanPath::Imc {
+            primary_id,
+            secondary_id,
+        }),
+        (id(), id(), equip_slot()).prop_map(|(primary_id, secondary_id, slot)| {
+            DemihumanPath::Mdl {
+                primary_id,
+                secondary_id,
+                slot,
+            }
+        }),
+        (id(), id(), id(), equip_slot()).prop_map(
+            |(primary_id, secondary_id, variant_id, slot)| DemihumanPath::Mtrl {
+                primary_id,
+                secondary_id,
+                variant_id,
+                slot,
+            }
+        ),
+        (id(), id(), variant(), equip_slot()).prop_map(
+            |(primary_id, secondary_id, variant_id, slot)| DemihumanPath::Tex {
+                primary_id,
+                secondary_id,
+                variant_id,
+                slot,
+            }
+        ),
+        (id(), id()).prop_map(|(primary_id, secondary_id)| DemihumanPath::Skeleton {
+            primary_id,
+            secondary_id,
+        }),
+        (id(), id(), id()).prop_map(|(primary_id, secondary_id, effect_id)| {
+            DemihumanPath::Avfx {
+                primary_id,
+                secondary_id,
+                effect_id,
+            }
+        }),
+    ]
+}
+
+fn equipment_path() -> impl Strategy<Value = EquipmentPath> {
+    prop_oneof![
+        id().prop_map(EquipmentPath::Imc),
+        (id(), id(), model_info(), equip_slot()).prop_map(
+            |(primary_id, variant_id, model_info, slot)| EquipmentPath::Mtrl {
+                primary_id,
+                variant_id,
+                model_info,
+                slot,
+            }
+        ),
+        (id(), model_info(), equip_slot()).prop_map(|(id, info, slot)| EquipmentPath::Mdl {
+            id,
+            info,
+            slot,
+        }),
+        (id(), variant(), model_info(), equip_slot()).prop_map(
+            |(primary_id, variant_id, model_info, slot)| EquipmentPath::Tex {
+                primary_id,
+                variant_id,
+                model_info,
+                slot,
+            }
+        ),
+        (id(), id()).prop_map(|(primary_id, effect_id)| EquipmentPath::Avfx {
+            primary_id,
+            effect_id,
+        }),
+    ]
+}
+
+fn accessory_path() -> impl Strategy<Value = AccessoryPath> {
+    prop_oneof![
+        id().prop_map(AccessoryPath::Imc),
+        (id(), model_info(), equip_slot()).prop_map(|(primary_id, info, slot)| {
+            AccessoryPath::Mdl {
+                primary_id,
+                info,
+                slot,
+            }
+        }),
+        (id(), id(), model_info(), equip_slot()).prop_map(
+            |(primary_id, variant_id, model_info, slot)| AccessoryPath::Mtrl {
+                primary_id,
+                variant_id,
+                model_info,
+                slot,
+            }
+        ),
+        (id(), variant(), model_info(), equip_slot()).prop_map(
+            |(primary_id, variant_id, model_info, slot)| AccessoryPath::Tex {
+                primary_id,
+                variant_id,
+                model_info,
+                slot,
+            }
+        ),
+    ]
+}
+
+fn character_obj_parts() -> impl Strategy<Value = (u16, ModelInfo, BodyType, Option<BodyTypeSlot>)>
+{
+    (
+        id(),
+        model_info(),
+        select(BODY_TYPES),
+        proptest::option::of(select(BODY_TYPE_SLOTS)),
+    )
+}
+
+fn character_obj_path() -> impl Strategy<Value = CharacterPath<'static>> {
+    prop_oneof![
+        character_obj_parts().prop_map(|(primary_id, model_info, body_type, slot)| {
+            CharacterPath::Mdl {
+                primary_id,
+                model_info,
+                body_type,
+                slot,
+            }
+        }),
+        (character_obj_parts(), proptest::option::of(id())).prop_map(
+            |((primary_id, model_info, body_type, slot), variant_id)| CharacterPath::Mtrl {
+                primary_id,
+                variant_id,
+                model_info,
+                body_type,
+                slot,
+            }
+        ),
+        (character_obj_parts(), proptest::option::of(variant())).prop_map(
+            |((primary_id, model_info, body_type, slot), variant_id)| CharacterPath::Tex {
+                primary_id,
+                variant_id,
+                model_info,
+                body_type,
+                slot,
+            }
+        ),
+    ]
+}
+
+fn character_other_path() -> impl Strategy<Value = CharacterPath<'static>> {
+    prop_oneof![
+        select(&["", "_2", "_a"][..])
+            .prop_map(|catchlight| CharacterPath::Catchlight(catchlight.into())),
+        (variant(), select(&["base", "norm", "mask"][..])).prop_map(|(id, kind)| {
+            CharacterPath::Eye {
+                id,
+                kind: kind.into(),
+            }
+        }),
+        select(&["", "_mask", "_m"][..]).prop_map(|skin| CharacterPath::Skin(skin.into())),
+        (
+            select(&[DecalType::Face, DecalType::Equip][..]),
+            any::<u64>()
+        )
+            .prop_map(|(kind, primary_id)| CharacterPath::Decal { kind, primary_id }),
+        (id(), model_info(), select(SKELETON_SLOTS)).prop_map(|(primary_id, model_info, slot)| {
+            CharacterPath::Skeleton {
+                primary_id,
+                model_info,
+                slot,
+            }
+        }),
+        select(&["magic/2ff_sage/mgc024", "emote/joy"][..])
+            .prop_map(|key| CharacterPath::Tmb(key.into())),
+        (
+            id(),
+            model_info(),
+            select(&[None, Some("common"), Some("2ax_emp")][..]),
+            select(&["ability/cnj_white/abl025", "resident/idle"][..]),
+        )
+            .prop_map(|(primary_id, model_info, category, key)| {
+                CharacterPath::Pap {
+                    primary_id,
+                    model_info,
+                    category: category.map(Into::into),
+                    key: key.into(),
+                }
+            }),
+        model_info().prop_map(CharacterPath::Atch),
+    ]
+}
+
+fn bg_path() -> impl Strategy<Value = BgPath<'static>> {
+    let file_kind = || {
+        select(
+            &[
+                BgFileKind::Lgb,
+                BgFileKind::Sgb,
+                BgFileKind::Lvb,
+                BgFileKind::Mdl,
+                BgFileKind::Mtrl,
+                BgFileKind::Tex,
+                BgFileKind::Pcb,
+                BgFileKind::Tera,
+            ][..],
+        )
+    };
+
+    prop_oneof![
+        (
+            select(&[("ffxiv", "sea_s1", "s1f1"), ("ex1", "01_roc_r2", "r2f1")][..]),
+            select(
+                &[
+                    TerritoryKind::Field,
+                    TerritoryKind::Town,
+                    TerritoryKind::Dungeon,
+                    TerritoryKind::Housing,
+                ][..]
+            ),
+            select(
+                &[
+                    BgPart::Level,
+                    BgPart::Plate,
+                    BgPart::Parts,
+                    BgPart::Texture,
+                    BgPart::Material,
+                    BgPart::Collision,
+                ][..]
+            ),
+            select(&["bg", "0000", "r2f1_a1_gate1"][..]),
+            file_kind(),
+        )
+            .prop_map(|((expansion, region, zone), kind, part, file, file_kind)| {
+                BgPath::Territory {
+                    expansion: expansion.into(),
+                    region: region.into(),
+                    kind,
+                    zone: zone.into(),
+                    part,
+                    file: file.into(),
+                    file_kind,
+                }
+            }),
+        (
+            select(&["hou", "world", "nature"][..]),
+            select(
+                &[
+                    "indoor/general/0001/bgparts/fun_b0_m0001",
+                    "aet/shared/w_aet_001a"
+                ][..]
+            ),
+            file_kind(),
+        )
+            .prop_map(|(category, file, file_kind)| BgPath::Common {
+                category: category.into(),
+                file: file.into(),
+                file_kind,
+            }),
+    ]
+}
+
+fn vfx_path() -> impl Strategy<Value = VfxPath<'static>> {
+    let file = || select(&["eff/abi_swd001c1t", "texture/ptc_smoke01", "cmat_ligct0c1"][..]);
+    let kind = || select(&[VfxFileKind::Avfx, VfxFileKind::Atex][..]);
+
+    prop_oneof![
+        (file(), kind()).prop_map(|(file, kind)| VfxPath::Common {
+            file: file.into(),
+            kind,
+        }),
+        (id(), file(), kind()).prop_map(|(primary_id, file, kind)| VfxPath::Monster {
+            primary_id,
+            file: file.into(),
+            kind,
+        }),
+        (id(), file(), kind()).prop_map(|(primary_id, file, kind)| VfxPath::Weapon {
+            primary_id,
+            file: file.into(),
+            kind,
+        }),
+        (file(), kind()).prop_map(|(file, kind)| VfxPath::Mount {
+            file: file.into(),
+            kind,
+        }),
+        (file(), kind()).prop_map(|(file, kind)| VfxPath::Omen {
+            file: file.into(),
+            kind,
+        }),
+        (
+            select(&["ab_swd_abl001", "mgc_2ff_sage001"][..]),
+            file(),
+            kind()
+        )
+            .prop_map(|(key, file, kind)| VfxPath::Action {
+                key: key.into(),
+                file: file.into(),
+                kind,
+            }),
+        (select(&["lockon", "channeling", "cut"][..]), file(), kind()).prop_map(
+            |(category, file, kind)| VfxPath::Other {
+                category: category.into(),
+                file: file.into(),
+                kind,
+            }
+        ),
+    ]
+}
+
+fn sound_path() -> impl Strategy<Value = SoundPath<'static>> {
+    let file = || select(&["mon/7001", "1001001", "se_vfx_abi_swd001c1t"][..]);
+
+    prop_oneof![
+        (
+            select(&["ffxiv", "ex1", "ex5"][..]),
+            select(&["BGM_Field_Gri_01", "BGM_EX1_Ban_01"][..]),
+        )
+            .prop_map(|(expansion, file)| SoundPath::Music {
+                expansion: expansion.into(),
+                file: file.into(),
+            }),
+        file().prop_map(|file| SoundPath::Battle(file.into())),
+        file().prop_map(|file| SoundPath::EmoteVoice(file.into())),
+        file().prop_map(|file| SoundPath::Footstep(file.into())),
+        file().prop_map(|file| SoundPath::Vfx(file.into())),
+        (select(&["system", "instruments", "zingle"][..]), file()).prop_map(|(category, file)| {
+            SoundPath::Other {
+                category: category.into(),
+                file: file.into(),
+            }
+        }),
+    ]
+}
+
+fn other_path() -> impl Strategy<Value = GamePath<'static>> {
+    prop_oneof![
+        (
+            0..1_000_000_u64,
+            0..1_000_000_u64,
+            proptest::option::of(select(LANGUAGES)),
+            any::<bool>(),
+            any::<bool>(),
+        )
+            .prop_map(|(group, primary_id, language, hq, hires)| GamePath::Icon {
+                group,
+                primary_id,
+                language,
+                hq,
+                hires,
+            }),
+        (
+            select(&["z6r1", "s1f1"][..]),
+            variant(),
+            proptest::option::of(proptest::char::range('a', 'z')),
+            proptest::option::of(proptest::char::range('a', 'z')),
+        )
+            .prop_map(|(primary_id, variant, suffix, extra)| GamePath::Map {
+                primary_id: primary_id.into(),
+                variant,
+                suffix,
+                extra,
+            }),
+        select(&["fontIcon_Ps5", "font1"][..]).prop_map(|name| GamePath::FontTexture(name.into())),
+        (select(&["AXIS", "Meidinger"][..]), any::<u8>()).prop_map(|(family, size)| {
+            GamePath::FontFile {
+                family: family.into(),
+                size,
+            }
+        }),
+    ]
+}
+
+fn game_path() -> impl Strategy<Value = GamePath<'static>> {
+    prop_oneof![
+        monster_path().prop_map(GamePath::Monster),
+        weapon_path().prop_map(GamePath::Weapon),
+        demihuman_path().prop_map(GamePath::Demihuman),
+        equipment_path().prop_map(GamePath::Equipment),
+        accessory_path().prop_map(GamePath::Accessory),
+        character_obj_path().prop_map(GamePath::Character),
+        character_other_path().prop_map(GamePath::Character),
+        bg_path().prop_map(GamePath::Bg),
+        vfx_path().prop_map(GamePath::Vfx),
+        sound_path().prop_map(GamePath::Sound),
+        other_path(),
+    ]
+}
+
+proptest! {
+    #[test]
+    fn format_then_parse(path in game_path()) {
+        let formatted = path.to_string();
+        let parsed = crate::game_path(&formatted).map(|(left, parsed)| (left.len(), parsed));
+        prop_assert_eq!(parsed, Ok((0, path)));
+    }
+}
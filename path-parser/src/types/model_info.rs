@@ -5,7 +5,8 @@ use crate::{
     types::{Gender, Race},
 };
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ModelInfo {
     pub race: Option<Race>,
     pub gender: Gender,
@@ -26,12 +27,12 @@ impl TryFrom<u16> for ModelInfo {
             0102 => ModelInfo {
                 race: Some(Race::Midlander),
                 gender: Gender::Male,
-                kind: ModelKind::Unknown,
+                kind: ModelKind::Unknown(2),
             },
             0103 => ModelInfo {
                 race: Some(Race::Midlander),
                 gender: Gender::Male,
-                kind: ModelKind::Unknown,
+                kind: ModelKind::Unknown(3),
             },
             0104 => ModelInfo {
                 race: Some(Race::Midlander),
@@ -46,12 +47,12 @@ impl TryFrom<u16> for ModelInfo {
             0202 => ModelInfo {
                 race: Some(Race::Midlander),
                 gender: Gender::Female,
-                kind: ModelKind::Unknown,
+                kind: ModelKind::Unknown(2),
             },
             0203 => ModelInfo {
                 race: Some(Race::Midlander),
                 gender: Gender::Female,
-                kind: ModelKind::Unknown,
+                kind: ModelKind::Unknown(3),
             },
             0204 => ModelInfo {
                 race: Some(Race::Midlander),
@@ -261,6 +262,86 @@ impl ModelInfo {
 
         Some(id)
     }
+
+    /// the id used for this model in paths, like `801` for `c0801`. `None`
+    /// for combinations the game has no model for, like a highlander npc
+    pub fn to_raw_id(self) -> Option<u16> {
+        let id = self.path_raw_id();
+        (Self::try_from(id) == Ok(self)).then_some(id)
+    }
+
+    /// like [`ModelInfo::to_raw_id`], but without checking the game has the
+    /// model. used when writing paths, which only hold model infos the parser
+    /// gave out
+    pub(crate) fn path_raw_id(self) -> u16 {
+        let kind = match self.kind {
+            ModelKind::Adult => 1,
+            ModelKind::Unknown(kind) => kind as u16,
+            ModelKind::Child => 4,
+        };
+
+        match self.to_path_id() {
+            Some(id) => id as u16 * 100 + kind,
+            None => match self.gender {
+                Gender::Male => 9100 + kind,
+                Gender::Female => 9200 + kind,
+            },
+        }
+    }
+}
+
+/// every model info the game uses, by raw id
+#[allow(clippy::zero_prefixed_literal)]
+const RAW_IDS: [u16; 42] = [
+    0101, 0102, 0103, 0104, 0201, 0202, 0203, 0204, 0301, 0304, 0401, 0404, 0501, 0504, 0601, 0604,
+    0701, 0704, 0801, 0804, 0901, 0904, 1001, 1004, 1101, 1104, 1201, 1204, 1301, 1304, 1401, 1404,
+    1501, 1504, 1601, 1604, 1701, 1704, 1801, 1804, 9104, 9204,
+];
+
+impl ModelInfo {
+    /// every model info the game uses
+    pub fn all() -> impl Iterator<Item = Self> {
+        RAW_IDS.into_iter().filter_map(|id| Self::try_from(id).ok())
+    }
+
+    /// the model the game loads a model or body file from when this one has
+    /// none of its own, following the game's racial deformer tree. midlander
+    /// males are the root of the tree and have no fallback
+    pub fn fallback(self) -> Option<Self> {
+        #[allow(clippy::zero_prefixed_literal)]
+        let parent = match self.to_raw_id()? {
+            0101 | 9104 | 9204 => return None,
+            // npc and child models fall back to the adult of their race
+            0102..=0104 => 0101,
+            0202..=0204 => 0201,
+            id if id % 100 == 4 => id - 3,
+            // males
+            0301 | 0501 | 0701 | 0901 | 1101 => 0101,
+            1301 | 1701 => 0501,
+            1501 => 0901,
+            // females
+            0201 => 0101,
+            0401 | 0601 | 0801 => 0201,
+            1001 | 1401 | 1801 => 0601,
+            1201 => 1101,
+            1601 => 1001,
+            _ => return None,
+        };
+
+        Self::try_from(parent).ok()
+    }
+
+    /// this model followed by every model it falls back to, in the order the
+    /// game tries them
+    pub fn fallback_chain(self) -> impl Iterator<Item = Self> {
+        std::iter::successors(Some(self), |info| info.fallback())
+    }
+
+    /// every other model that falls back to this one, directly or through
+    /// other models
+    pub fn dependents(self) -> impl Iterator<Item = Self> {
+        Self::all().filter(move |info| *info != self && info.fallback_chain().any(|i| i == self))
+    }
 }
 
 impl std::fmt::Display for ModelInfo {
@@ -272,11 +353,14 @@ impl std::fmt::Display for ModelInfo {
     }
 }
 
-#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ModelKind {
     Adult,
     Child,
-    Unknown,
+    /// the npc models only midlanders have. holds the last digit of the id,
+    /// as `c0102` and `c0103` are different models
+    Unknown(u8),
 }
 
 impl ModelKind {
@@ -284,7 +368,7 @@ impl ModelKind {
         match self {
             Self::Adult => "",
             Self::Child => " (Child)",
-            Self::Unknown => " (Unknown)",
+            Self::Unknown(_) => " (Unknown)",
         }
     }
 }
@@ -303,3 +387,91 @@ pub fn model_info_with_raw(input: &str) -> IResult<&str, (u16, ModelInfo)> {
 pub fn raw_model_info(input: &str) -> IResult<&str, u16> {
     n_digit_id::<u16>(4).parse(input)
 }
+
+#[cfg(test)]
+mod test {
+    use super::{ModelInfo, RAW_IDS};
+    use crate::types::{Gender, ModelKind, Race};
+
+    fn info(race: Race, gender: Gender, kind: ModelKind) -> ModelInfo {
+        ModelInfo {
+            race: Some(race),
+            gender,
+            kind,
+        }
+    }
+
+    #[test]
+    fn raw_ids_round_trip() {
+        for id in RAW_IDS {
+            let info = ModelInfo::try_from(id).unwrap();
+            assert_eq!(Some(id), info.to_raw_id(), "{info}");
+        }
+
+        assert_eq!(RAW_IDS.len(), ModelInfo::all().count());
+    }
+
+    #[test]
+    fn unused_models_have_no_raw_id() {
+        assert_eq!(
+            None,
+            info(Race::Highlander, Gender::Male, ModelKind::Unknown(2)).to_raw_id()
+        );
+        assert_eq!(
+            None,
+            ModelInfo {
+                race: None,
+                gender: Gender::Female,
+                kind: ModelKind::Adult,
+            }
+            .to_raw_id()
+        );
+    }
+
+    #[test]
+    fn every_model_reaches_the_root() {
+        let root = info(Race::Midlander, Gender::Male, ModelKind::Adult);
+        for model in ModelInfo::all().filter(|model| model.race.is_some()) {
+            assert_eq!(Some(root), model.fallback_chain().last(), "{model}");
+        }
+    }
+
+    #[test]
+    fn fallback_chain() {
+        let chain = info(Race::Lalafell, Gender::Female, ModelKind::Child)
+            .fallback_chain()
+            .flat_map(ModelInfo::to_raw_id)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![1204, 1201, 1101, 101], chain);
+    }
+
+    #[test]
+    fn dependents() {
+        let dependents = info(Race::Roegadyn, Gender::Male, ModelKind::Adult)
+            .dependents()
+            .flat_map(ModelInfo::to_raw_id)
+            .collect::<Vec<_>>();
+        assert_eq!(vec![904, 1501, 1504], dependents);
+    }
+
+    #[test]
+    fn fallback_edges() {
+        let fallback = |id: u16| {
+            ModelInfo::try_from(id)
+                .unwrap()
+                .fallback()
+                .and_then(ModelInfo::to_raw_id)
+        };
+
+        for id in [1001, 1401, 1801] {
+            assert_eq!(Some(601), fallback(id), "{id}");
+        }
+
+        assert_eq!(Some(101), fallback(701));
+        assert_eq!(Some(901), fallback(1501));
+        assert_eq!(Some(101), fallback(103));
+        assert_eq!(Some(201), fallback(203));
+        assert_eq!(None, fallback(101));
+        assert_eq!(None, fallback(9104));
+    }
+}